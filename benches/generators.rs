@@ -0,0 +1,159 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Measures the throughput of the generators on the hot path of the workload.
+//!
+//! The generators backed by files, e.g. the histogram, file and replay generators, are left out, since they can't be
+//! constructed without an input file.
+
+use std::io::Read;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::{rngs::StdRng, SeedableRng};
+use yay::{
+    generator::{
+        constant::ConstantUsizeGenerator,
+        discrete::{Choice, DiscreteGenerator},
+        sequential::SequentialUsizeGenerator,
+        uniform::UniformUsizeGenerator,
+        zipfian::ZipfianUsizeGenerator,
+        Generator, NumberGenerator, Seedable,
+    },
+    utils::RandomBytes,
+};
+
+const ITEMS: usize = 1_000_000;
+
+fn bench_next<G: Generator>(c: &mut Criterion, name: &str, generator: G) {
+    c.bench_function(name, |b| b.iter(|| black_box(generator.next())));
+}
+
+fn uniform(c: &mut Criterion) {
+    bench_next(c, "uniform", UniformUsizeGenerator::new(0, ITEMS - 1));
+    bench_next(
+        c,
+        "uniform_seeded",
+        UniformUsizeGenerator::new(0, ITEMS - 1).with_rng(StdRng::seed_from_u64(0)),
+    );
+}
+
+fn constant(c: &mut Criterion) {
+    bench_next(c, "constant", ConstantUsizeGenerator::new(ITEMS));
+}
+
+fn sequential(c: &mut Criterion) {
+    bench_next(c, "sequential", SequentialUsizeGenerator::new(0, ITEMS - 1));
+}
+
+fn discrete(c: &mut Criterion) {
+    // The operation mix of the workload A.
+    let choices = vec![
+        Choice {
+            val: "read",
+            weight: 0.5,
+        },
+        Choice {
+            val: "update",
+            weight: 0.5,
+        },
+    ];
+    bench_next(c, "discrete", DiscreteGenerator::new(choices).unwrap());
+}
+
+fn zipfian(c: &mut Criterion) {
+    let mut group = c.benchmark_group("zipfian");
+    for items in [1_000, ITEMS, 100 * ITEMS] {
+        let generator = ZipfianUsizeGenerator::new(0, items - 1);
+        group.bench_with_input(BenchmarkId::from_parameter(items), &generator, |b, g| {
+            b.iter(|| black_box(g.next()))
+        });
+    }
+    group.finish();
+}
+
+/// The values drawn by `next_batch` versus as many `next()` calls, on boxed generators like the ones of the workload.
+fn batch(c: &mut Criterion) {
+    const BATCH: usize = 1000;
+
+    let generators: [(&str, Box<dyn NumberGenerator<Output = usize> + Send + Sync>); 3] = [
+        (
+            "uniform",
+            Box::new(UniformUsizeGenerator::new(0, ITEMS - 1)),
+        ),
+        (
+            "uniform_seeded",
+            Box::new(UniformUsizeGenerator::new(0, ITEMS - 1).with_rng(StdRng::seed_from_u64(0))),
+        ),
+        (
+            "zipfian",
+            Box::new(ZipfianUsizeGenerator::new(0, ITEMS - 1)),
+        ),
+    ];
+    let mut group = c.benchmark_group("batch");
+    group.throughput(Throughput::Elements(BATCH as u64));
+    let mut out = Vec::with_capacity(BATCH);
+    for (name, generator) in &generators {
+        group.bench_with_input(BenchmarkId::new("next", name), generator, |b, g| {
+            b.iter(|| {
+                out.clear();
+                for _ in 0..BATCH {
+                    out.push(g.next());
+                }
+                black_box(&out);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("next_batch", name), generator, |b, g| {
+            b.iter(|| {
+                out.clear();
+                g.next_batch(BATCH, &mut out);
+                black_box(&out);
+            })
+        });
+    }
+    group.finish();
+}
+
+fn random_bytes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("random_bytes");
+    for size in [100, 4096, 1 << 20] {
+        let mut buf = vec![0; size];
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("alphanumeric", size), &size, |b, &size| {
+            b.iter(|| RandomBytes::new(size).read_exact(&mut buf).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("binary", size), &size, |b, &size| {
+            b.iter(|| RandomBytes::new_binary(size).read_exact(&mut buf).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("compressible", size), &size, |b, &size| {
+            b.iter(|| {
+                RandomBytes::new_with_compressibility(size, 0.5)
+                    .read_exact(&mut buf)
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    uniform,
+    constant,
+    sequential,
+    discrete,
+    zipfian,
+    batch,
+    random_bytes
+);
+criterion_main!(benches);
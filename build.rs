@@ -0,0 +1,30 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+fn main() {
+    #[cfg(feature = "grpc")]
+    grpc();
+}
+
+/// Generate the client and the server of the YCSB service, `protox` compiles the proto without a `protoc` binary.
+#[cfg(feature = "grpc")]
+fn grpc() {
+    const PROTO: &str = "proto/ycsb.proto";
+
+    println!("cargo:rerun-if-changed={PROTO}");
+    let fds = protox::compile([PROTO], ["proto"]).expect("failed to compile the proto");
+    tonic_build::configure()
+        .compile_fds(fds)
+        .expect("failed to generate the grpc service");
+}
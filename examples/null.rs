@@ -0,0 +1,47 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+//! Runs the default core workload against the no-op backend, to measure the op generation rate of the workload itself.
+
+use std::time::Instant;
+
+use anyhow::Result;
+use yay::{
+    db::{null::NullDb, Db},
+    workload::{CoreWorkload, CoreWorkloadConfig, Workload},
+};
+
+const TRANSACTIONS: usize = 1_000_000;
+
+fn main() -> Result<()> {
+    let workload = CoreWorkload::new(CoreWorkloadConfig::default())?;
+    let db = NullDb::new();
+
+    db.init()?;
+    let start = Instant::now();
+    for _ in 0..TRANSACTIONS {
+        workload.transaction(db)?;
+    }
+    let elapsed = start.elapsed();
+    db.cleanup()?;
+
+    println!(
+        "{TRANSACTIONS} transactions in {elapsed:?}, {:.0} ops/s",
+        TRANSACTIONS as f64 / elapsed.as_secs_f64()
+    );
+    for (op, report) in workload.measurements().report().operations {
+        println!("{op:?}: {report:?}");
+    }
+    Ok(())
+}
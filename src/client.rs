@@ -0,0 +1,524 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+
+use crate::{
+    db::{Capabilities, Db},
+    measurement::Report,
+    workload::{CoreWorkload, Workload},
+};
+
+/// The interval to update the progress bar.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Drives a [`CoreWorkload`] with a pool of client threads.
+///
+/// Each client thread creates its own [`Db`] instance with the db factory, and calls `init()` before and `cleanup()`
+/// after its share of the operations. The work is split evenly across the threads: the `insert_count` records of the
+/// workload in the load phase, and its `operation_count` transactions in the run phase. A phase fails before `init()` if
+/// the [`Db::capabilities`] lack any operation of the phase.
+///
+/// A phase stops on the first error of any thread, and returns the error. A phase also stops early when it reaches the
+/// max duration or [`Client::stop`] is called, then the in-flight operations complete and the report of the measurements
+/// so far is returned.
+///
+/// The workload is initialized before the first phase, and cleaned up by [`Client::close`] or when the client is
+/// dropped.
+pub struct Client<F> {
+    workload: CoreWorkload,
+    db_factory: F,
+    threads: usize,
+    max_duration: Option<Duration>,
+    warmup_operations: usize,
+    progress: bool,
+    stopped: Arc<AtomicBool>,
+    /// Whether the workload is initialized and not cleaned up yet.
+    initialized: Mutex<bool>,
+}
+
+impl<F> std::fmt::Debug for Client<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("threads", &self.threads)
+            .field("max_duration", &self.max_duration)
+            .field("warmup_operations", &self.warmup_operations)
+            .field("progress", &self.progress)
+            .field("stopped", &self.stopped.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl<F, D> Client<F>
+where
+    F: Fn() -> Result<D> + Sync,
+    D: Db + Clone,
+{
+    /// Create a client.
+    ///
+    /// * `workload` - The workload to drive.
+    /// * `db_factory` - Creates the db instance of each client thread.
+    /// * `threads` - The number of client threads, at least 1.
+    ///
+    /// The run phase does the `operation_count` transactions of the workload, unless it reaches
+    /// [`Client::with_max_duration`] or is stopped by [`Client::stop`] first.
+    pub fn new(workload: CoreWorkload, db_factory: F, threads: usize) -> Self {
+        Self {
+            workload,
+            db_factory,
+            threads: threads.max(1),
+            max_duration: None,
+            warmup_operations: 0,
+            progress: false,
+            stopped: Arc::new(AtomicBool::new(false)),
+            initialized: Mutex::new(false),
+        }
+    }
+
+    /// Limit the wall-clock duration of each phase.
+    pub fn with_max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    /// Do `warmup_operations` transactions in total before the run phase, which are excluded from the measurements.
+    ///
+    /// The warm-up lets the caches of the database fill up before the measurements start. It is limited by the max
+    /// duration like a phase.
+    pub fn with_warmup_operations(mut self, warmup_operations: usize) -> Self {
+        self.warmup_operations = warmup_operations;
+        self
+    }
+
+    /// Show a progress bar of each phase with a planned operation count, see [`ProgressReporter`](crate::status::ProgressReporter).
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// Get the workload driven by the client.
+    pub fn workload(&self) -> &CoreWorkload {
+        &self.workload
+    }
+
+    /// Stop the running phase and the following phases. The in-flight operations complete before the phase returns.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the client is stopped by [`Client::stop`].
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    /// Install a Ctrl-C handler that stops the client.
+    ///
+    /// The handler is process-wide and can only be installed once.
+    #[cfg(feature = "ctrlc")]
+    pub fn stop_on_ctrl_c(&self) -> Result<()> {
+        let stopped = self.stopped.clone();
+        ctrlc::set_handler(move || stopped.store(true, Ordering::Relaxed))?;
+        Ok(())
+    }
+
+    /// Run the load phase, which inserts the `insert_count` records of the workload.
+    ///
+    /// Returns the report of the measurements so far.
+    pub fn load(&self) -> Result<Report> {
+        let check =
+            |capabilities: &Capabilities| self.workload.check_load_capabilities(capabilities);
+        self.execute(self.workload.insert_count(), self.progress, check, |db| {
+            self.workload.insert(db.clone())
+        })
+    }
+
+    /// Run the run phase, which does the `operation_count` transactions of the workload paced to its target throughput,
+    /// unless another limit is reached or the client is stopped first. The limits include the `max_bytes_written` of the
+    /// workload.
+    ///
+    /// Returns the report of the measurements so far, including the inserts of the load phase if any. If warm-up
+    /// operations are set, they are done first without a progress bar, and they are excluded from the measurements and
+    /// the phases of the workload, see [`CoreWorkload::warmup`].
+    pub fn run(&self) -> Result<Report> {
+        let limiter = self.workload.rate_limiter(self.threads);
+        let limiter = &limiter;
+        let transaction = |db: &D| {
+            self.workload
+                .transaction_rate_limited(db.clone(), limiter)
+                .map(|_| ())
+        };
+        let check = |capabilities: &Capabilities| self.workload.check_capabilities(capabilities);
+        if self.warmup_operations > 0 {
+            self.execute(self.warmup_operations, false, check, |db| {
+                self.workload.warmup(|| transaction(db))
+            })?;
+        }
+        self.execute(
+            self.workload.operation_count(),
+            self.progress,
+            check,
+            transaction,
+        )
+    }
+
+    /// Split `count` operations across the client threads, and call `op` for each operation. Each thread calls `check` with the capabilities of its db first, and fails if it fails. A progress
+    /// bar is shown if `progress` is `true`.
+    fn execute(
+        &self,
+        count: usize,
+        progress: bool,
+        check: impl Fn(&Capabilities) -> Result<()> + Sync,
+        op: impl Fn(&D) -> Result<()> + Sync,
+    ) -> Result<Report> {
+        {
+            let mut initialized = self.initialized.lock();
+            if !*initialized {
+                self.workload.init()?;
+                *initialized = true;
+            }
+        }
+
+        let progress = progress.then(|| self.workload.progress_reporter(count, PROGRESS_INTERVAL));
+        let deadline = self.max_duration.map(|duration| Instant::now() + duration);
+        let failed = AtomicBool::new(false);
+        let (failed, check, op) = (&failed, &check, &op);
+
+        std::thread::scope(|s| {
+            let handles = (0..self.threads)
+                .map(|i| {
+                    // The first `count % threads` threads take one more operation.
+                    let share = count / self.threads + usize::from(i < count % self.threads);
+                    std::thread::Builder::new()
+                        .name(format!("yay-client-{i}"))
+                        .spawn_scoped(s, move || {
+                            let res = self.worker(share, deadline, failed, check, op);
+                            if res.is_err() {
+                                failed.store(true, Ordering::Relaxed);
+                            }
+                            res
+                        })
+                        .expect("spawn client thread")
+                })
+                .collect::<Vec<_>>();
+            handles.into_iter().try_for_each(|handle| {
+                handle
+                    .join()
+                    .map_err(|_| anyhow!("client thread panicked"))?
+            })
+        })?;
+
+        if let Some(progress) = progress {
+            progress.stop();
+        }
+        Ok(self.workload.measurements().report())
+    }
+
+    fn worker(
+        &self,
+        count: usize,
+        deadline: Option<Instant>,
+        failed: &AtomicBool,
+        check: &impl Fn(&Capabilities) -> Result<()>,
+        op: &impl Fn(&D) -> Result<()>,
+    ) -> Result<()> {
+        let db = (self.db_factory)()?;
+        check(&db.capabilities())?;
+        db.init()?;
+        let res = (0..count)
+            .take_while(|_| {
+                !failed.load(Ordering::Relaxed)
+                    && !self.stopped.load(Ordering::Relaxed)
+                    && !self.workload.is_write_limit_reached()
+                    && deadline.map_or(true, |deadline| Instant::now() < deadline)
+            })
+            .try_for_each(|_| op(&db));
+        db.cleanup()?;
+        res
+    }
+}
+
+impl<F> Client<F> {
+    /// Cleanup the workload after the last phase.
+    pub fn close(mut self) -> Result<()> {
+        self.shutdown()
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        let initialized = self.initialized.get_mut();
+        if *initialized {
+            *initialized = false;
+            self.workload.cleanup()?;
+        }
+        Ok(())
+    }
+}
+
+impl<F> Drop for Client<F> {
+    fn drop(&mut self) {
+        if let Err(e) = self.shutdown() {
+            tracing::warn!("failed to cleanup the workload: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use super::*;
+    use crate::{
+        db::{memory::MemoryDb, unsupported, DbResult},
+        utils::Value,
+        workload::{CoreWorkloadConfig, Op, PhaseConfig},
+    };
+
+    /// A [`MemoryDb`] that can't scan, like a cache.
+    #[derive(Debug, Clone, Default)]
+    struct ScanlessDb(MemoryDb);
+
+    impl Db for ScanlessDb {
+        type Config = ();
+
+        fn from_config(_: Self::Config) -> DbResult<Self> {
+            Ok(Self::default())
+        }
+
+        fn init(&self) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn cleanup(&self) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn insert(
+            &self,
+            table: String,
+            key: String,
+            values: HashMap<String, Value>,
+        ) -> DbResult<()> {
+            self.0.insert(table, key, values)
+        }
+
+        fn read(
+            &self,
+            table: String,
+            key: String,
+            fields: HashSet<String>,
+        ) -> DbResult<HashMap<String, Value>> {
+            self.0.read(table, key, fields)
+        }
+
+        fn update(
+            &self,
+            table: String,
+            key: String,
+            values: HashMap<String, Value>,
+        ) -> DbResult<()> {
+            self.0.update(table, key, values)
+        }
+
+        fn scan(
+            &self,
+            _: String,
+            _: String,
+            _: usize,
+            _: HashSet<String>,
+        ) -> DbResult<HashMap<String, Vec<Value>>> {
+            Err(unsupported("scan"))
+        }
+
+        fn delete(&self, table: String, key: String) -> DbResult<()> {
+            self.0.delete(table, key)
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                scan: false,
+                ..Capabilities::all()
+            }
+        }
+    }
+
+    #[test]
+    fn test_warmup() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(100)
+            .operation_count(200)
+            .phases(vec![
+                PhaseConfig {
+                    operation_count: 10,
+                    insert_proportion: 1.0,
+                    ..Default::default()
+                },
+                PhaseConfig {
+                    read_proportion: 1.0,
+                    ..Default::default()
+                },
+            ])
+            .build()
+            .unwrap();
+        let db = MemoryDb::new();
+        let client = Client::new(CoreWorkload::new(config).unwrap(), || Ok(db.clone()), 2)
+            .with_warmup_operations(50);
+
+        client.load().unwrap();
+        let report = client.run().unwrap();
+        // The load phase is still measured, and the warm-up stays in the first phase without being measured.
+        assert_eq!(report.operations[&Op::Insert].count, 110);
+        assert_eq!(report.operations[&Op::Read].count, 190);
+        assert_eq!(report.total_operations, 300);
+        assert_eq!(db.len("ycsb"), 160);
+        assert_eq!(client.workload().operations().load(Ordering::Relaxed), 300);
+    }
+
+    #[test]
+    fn test_load_and_run() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(100)
+            .operation_count(1001)
+            .read_proportion(0.5)
+            .update_proportion(0.5)
+            .build()
+            .unwrap();
+        let db = MemoryDb::new();
+        let client = Client::new(CoreWorkload::new(config).unwrap(), || Ok(db.clone()), 4);
+
+        let report = client.load().unwrap();
+        assert_eq!(report.operations[&Op::Insert].count, 100);
+        assert_eq!(db.len("ycsb"), 100);
+
+        let report = client.run().unwrap();
+        let reads = report.operations[&Op::Read].count;
+        let updates = report.operations[&Op::Update].count;
+        assert_eq!(reads + updates, 1001);
+        assert!(reads > 0 && updates > 0);
+        assert_eq!(report.total_operations, 1101);
+        assert_eq!(db.len("ycsb"), 100);
+        client.close().unwrap();
+    }
+
+    #[test]
+    fn test_max_duration() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(100)
+            .operation_count(usize::MAX)
+            .read_proportion(1.0)
+            .build()
+            .unwrap();
+        let db = MemoryDb::new();
+        let client = Client::new(CoreWorkload::new(config).unwrap(), || Ok(db.clone()), 2)
+            .with_max_duration(Duration::from_millis(100));
+        client.load().unwrap();
+
+        // With no practical operation limit, the run phase only stops at the max duration.
+        let start = Instant::now();
+        let report = client.run().unwrap();
+        assert!(
+            start.elapsed() < Duration::from_secs(2),
+            "{:?}",
+            start.elapsed()
+        );
+        assert!(report.operations[&Op::Read].count > 0);
+
+        // A stopped client does nothing more.
+        client.stop();
+        assert!(client.is_stopped());
+        let stopped = client.run().unwrap();
+        assert_eq!(stopped.total_operations, report.total_operations);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(10)
+            .read_proportion(0.5)
+            .update_proportion(0.0)
+            .scan_proportion(0.5)
+            .build()
+            .unwrap();
+        let db = ScanlessDb::default();
+        let client = Client::new(CoreWorkload::new(config).unwrap(), || Ok(db.clone()), 2);
+
+        // The load phase only needs inserts.
+        client.load().unwrap();
+        assert_eq!(db.0.len("ycsb"), 10);
+
+        // The run phase fails before any transaction.
+        let e = client.run().unwrap_err();
+        assert!(
+            e.to_string()
+                .contains("Scan transactions need Scan operations"),
+            "{e}"
+        );
+        assert_eq!(client.workload().operations().load(Ordering::Relaxed), 10);
+
+        // A workload without scans runs.
+        let config = CoreWorkloadConfig::builder()
+            .record_count(10)
+            .read_proportion(1.0)
+            .build()
+            .unwrap();
+        let client = Client::new(CoreWorkload::new(config).unwrap(), || Ok(db.clone()), 2);
+        client.run().unwrap();
+    }
+
+    #[test]
+    fn test_max_bytes_written() {
+        // Each record is 10 fields of 10 bytes.
+        let config = CoreWorkloadConfig::builder()
+            .record_count(100)
+            .fields(10)
+            .field_length_distribution("constant")
+            .max_field_length(10)
+            .read_proportion(0.0)
+            .update_proportion(0.0)
+            .insert_proportion(1.0)
+            .operation_count(usize::MAX)
+            .max_bytes_written(1000);
+
+        let db = MemoryDb::new();
+        let client = Client::new(
+            CoreWorkload::new(config.clone().build().unwrap()).unwrap(),
+            || Ok(db.clone()),
+            1,
+        );
+        let report = client.load().unwrap();
+        assert_eq!(report.operations[&Op::Insert].count, 10);
+        assert_eq!(db.len("ycsb"), 10);
+        assert_eq!(client.workload().bytes_written(), 1000);
+
+        // With no practical operation limit, the run phase stops at the cap, and each other thread may insert one more record
+        // after it is reached.
+        let db = MemoryDb::new();
+        let client = Client::new(
+            CoreWorkload::new(config.max_bytes_written(2000).build().unwrap()).unwrap(),
+            || Ok(db.clone()),
+            4,
+        );
+        client.run().unwrap();
+        let written = client.workload().bytes_written();
+        assert!((2000..=2300).contains(&written), "{written}");
+        assert_eq!(db.len("ycsb") * 100, written);
+    }
+}
@@ -0,0 +1,524 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use itertools::Itertools;
+use parking_lot::Mutex;
+use scylla::{
+    frame::response::result::{CqlValue, Row},
+    transport::errors::{DbError as ScyllaDbError, QueryError},
+    CachingSession, SessionBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{into_columns, not_found, AsyncDb, BoxFuture, DbError, DbResult};
+use crate::utils::Value;
+
+/// The name of the key column.
+const KEY_COLUMN: &str = "\"key\"";
+
+/// Configuration for the [`CassandraDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CassandraDbConfig {
+    /// The addresses of the nodes to discover the cluster from, e.g. `127.0.0.1:9042`.
+    #[serde(default = "default::contact_points")]
+    pub contact_points: Vec<String>,
+    /// The keyspace of the tables, which is created with the `SimpleStrategy` if it doesn't exist.
+    #[serde(default = "default::keyspace")]
+    pub keyspace: String,
+    /// The replication factor of the keyspace, only used if the keyspace is created.
+    #[serde(default = "default::replication_factor")]
+    pub replication_factor: usize,
+    /// The number of fields in a record, which must match the workload.
+    #[serde(default = "default::fields")]
+    pub fields: usize,
+    /// The prefix of the field names, which must match the workload.
+    #[serde(default = "default::field_name_prefix")]
+    pub field_name_prefix: String,
+    /// The max number of prepared statements cached by the session.
+    #[serde(default = "default::statement_cache_size")]
+    pub statement_cache_size: usize,
+}
+
+/// An [`AsyncDb`] backed by a Cassandra or ScyllaDB cluster.
+///
+/// Wrap it in a [`BlockingDb`](super::BlockingDb) to drive it from the client threads.
+///
+/// Each table has a `key text PRIMARY KEY` column and a `blob` column for each field, the schema is derived from the
+/// configured field count and field name prefix. The tables are created on first use. All the statements are prepared
+/// once and cached by the session.
+///
+/// Cassandra partitions the records by the hash of their keys, so the partition keys can't be scanned in their order.
+/// Like the Cassandra binding of YCSB, scans read the records from the token of the start key in the token order with
+/// `token(key) >= token(?)`, which touches the same amount of data, but the records are not in the order of their keys,
+/// see [`Db::scan_is_ordered`](super::Db::scan_is_ordered).
+///
+/// Clones of a [`CassandraDb`] share the same session. The session is connected by the first `init()` call.
+#[derive(Clone)]
+pub struct CassandraDb {
+    config: Arc<CassandraDbConfig>,
+    columns: Arc<Vec<String>>,
+    session: Arc<Mutex<Option<Arc<CachingSession>>>>,
+    /// The tables that are known to exist.
+    tables: Arc<Mutex<HashSet<String>>>,
+}
+
+impl std::fmt::Debug for CassandraDb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CassandraDb")
+            .field("config", &self.config)
+            .field("connected", &self.session.lock().is_some())
+            .finish()
+    }
+}
+
+impl CassandraDb {
+    /// Create a Cassandra backend with the given config. The session is connected by `init()`.
+    pub fn new(config: CassandraDbConfig) -> Self {
+        let columns = (0..config.fields)
+            .map(|i| format!("{prefix}{i}", prefix = config.field_name_prefix))
+            .collect_vec();
+        Self {
+            config: Arc::new(config),
+            columns: Arc::new(columns),
+            session: Arc::default(),
+            tables: Arc::default(),
+        }
+    }
+
+    async fn connect(&self) -> DbResult<()> {
+        if self.session.lock().is_some() {
+            return Ok(());
+        }
+        let session = SessionBuilder::new()
+            .known_nodes(&self.config.contact_points)
+            .build()
+            .await
+            .map_err(anyhow::Error::from)?;
+        session
+            .query_unpaged(
+                format!(
+                    "CREATE KEYSPACE IF NOT EXISTS {} WITH replication = {{'class': 'SimpleStrategy', 'replication_factor': {}}}",
+                    quote(&self.config.keyspace),
+                    self.config.replication_factor
+                ),
+                (),
+            )
+            .await?;
+        let session = CachingSession::from(session, self.config.statement_cache_size);
+        self.session.lock().get_or_insert_with(|| Arc::new(session));
+        Ok(())
+    }
+
+    /// Get the session, after creating `table` if it is not known to exist.
+    async fn session(&self, table: &str) -> DbResult<Arc<CachingSession>> {
+        let session = self
+            .session
+            .lock()
+            .clone()
+            .ok_or_else(|| anyhow!("cassandra is not initialized"))?;
+        if !self.tables.lock().contains(table) {
+            let columns = self
+                .columns
+                .iter()
+                .map(|column| format!(", {} blob", quote(column)))
+                .join("");
+            session
+                .get_session()
+                .query_unpaged(
+                    format!(
+                        "CREATE TABLE IF NOT EXISTS {} ({KEY_COLUMN} text PRIMARY KEY{columns})",
+                        self.table(table)
+                    ),
+                    (),
+                )
+                .await?;
+            self.tables.lock().insert(table.to_string());
+        }
+        Ok(session)
+    }
+
+    /// The qualified name of the table.
+    fn table(&self, table: &str) -> String {
+        format!("{}.{}", quote(&self.config.keyspace), quote(table))
+    }
+
+    /// The columns to select, all the field columns if `fields` is empty.
+    fn projection(&self, fields: HashSet<String>) -> Vec<String> {
+        if fields.is_empty() {
+            self.columns.to_vec()
+        } else {
+            fields.into_iter().collect()
+        }
+    }
+
+    /// Write the fields of `values` into the record. Both inserts and updates are upserts in Cassandra.
+    async fn upsert(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> DbResult<()> {
+        let (fields, values) = unzip(values)?;
+        let cql = format!(
+            "INSERT INTO {} ({KEY_COLUMN}{}) VALUES (?{})",
+            self.table(&table),
+            fields
+                .iter()
+                .map(|field| format!(", {}", quote(field)))
+                .join(""),
+            ", ?".repeat(fields.len())
+        );
+        let params = std::iter::once(CqlValue::Text(key))
+            .chain(values.into_iter().map(CqlValue::Blob))
+            .collect_vec();
+        self.session(&table)
+            .await?
+            .execute_unpaged(cql, params)
+            .await?;
+        Ok(())
+    }
+
+    async fn select(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let columns = self.projection(fields);
+        let cql = format!(
+            "SELECT {} FROM {} WHERE {KEY_COLUMN} = ?",
+            columns.iter().map(|column| quote(column)).join(", "),
+            self.table(&table)
+        );
+        let row = self
+            .session(&table)
+            .await?
+            .execute_unpaged(cql, (&key,))
+            .await?
+            .maybe_first_row()
+            .map_err(anyhow::Error::from)?
+            .ok_or_else(|| not_found(&table, &key))?;
+        Ok(take_row(row, &columns))
+    }
+
+    async fn select_range(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let columns = self.projection(fields);
+        let cql = format!(
+            "SELECT {} FROM {} WHERE token({KEY_COLUMN}) >= token(?) LIMIT ?",
+            columns.iter().map(|column| quote(column)).join(", "),
+            self.table(&table)
+        );
+        let len = len.min(i32::MAX as usize) as i32;
+        let rows = self
+            .session(&table)
+            .await?
+            .execute_unpaged(cql, (&start_key, len))
+            .await?
+            .rows()
+            .map_err(anyhow::Error::from)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| take_row(row, &columns))
+            .collect())
+    }
+
+    async fn remove(&self, table: String, key: String) -> DbResult<()> {
+        let cql = format!("DELETE FROM {} WHERE {KEY_COLUMN} = ?", self.table(&table));
+        self.session(&table)
+            .await?
+            .execute_unpaged(cql, (&key,))
+            .await?;
+        Ok(())
+    }
+}
+
+impl AsyncDb for CassandraDb {
+    type Config = CassandraDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.connect())
+    }
+
+    fn cleanup(&self) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    fn insert(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.upsert(table, key, values))
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Value>>> {
+        Box::pin(self.select(table, key, fields))
+    }
+
+    fn update(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.upsert(table, key, values))
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Vec<Value>>>> {
+        Box::pin(async move {
+            self.select_range(table, start_key, len, fields)
+                .await
+                .map(into_columns)
+        })
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<Vec<HashMap<String, Value>>>> {
+        Box::pin(self.select_range(table, start_key, len, fields))
+    }
+
+    fn scan_is_ordered(&self) -> bool {
+        false
+    }
+
+    fn delete(&self, table: String, key: String) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.remove(table, key))
+    }
+}
+
+impl From<QueryError> for DbError {
+    /// The timeouts, the broken connections and the unavailable or overloaded nodes are [`DbError::Transient`].
+    fn from(error: QueryError) -> Self {
+        let transient = match &error {
+            QueryError::DbError(e, _) => matches!(
+                e,
+                ScyllaDbError::Unavailable { .. }
+                    | ScyllaDbError::Overloaded
+                    | ScyllaDbError::IsBootstrapping
+                    | ScyllaDbError::ReadTimeout { .. }
+                    | ScyllaDbError::WriteTimeout { .. }
+            ),
+            QueryError::IoError(_)
+            | QueryError::TimeoutError
+            | QueryError::RequestTimeout(_)
+            | QueryError::BrokenConnection(_)
+            | QueryError::ConnectionPoolError(_) => true,
+            _ => false,
+        };
+        if transient {
+            Self::Transient(error.into())
+        } else {
+            Self::Other(error.into())
+        }
+    }
+}
+
+/// Split the field/value pairs into the fields and the bytes of the values.
+fn unzip(values: HashMap<String, Value>) -> anyhow::Result<(Vec<String>, Vec<Vec<u8>>)> {
+    Ok(values
+        .into_iter()
+        .map(|(field, value)| Ok((field, value.into_bytes()?)))
+        .collect::<anyhow::Result<Vec<_>>>()?
+        .into_iter()
+        .unzip())
+}
+
+/// Take the non-null blob columns of the row.
+fn take_row(row: Row, columns: &[String]) -> HashMap<String, Value> {
+    columns
+        .iter()
+        .zip(row.columns)
+        .filter_map(|(column, value)| match value {
+            Some(CqlValue::Blob(value)) => Some((column.clone(), value.into())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Quote an identifier.
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn contact_points() -> Vec<String> {
+        vec!["127.0.0.1:9042".to_string()]
+    }
+
+    pub fn keyspace() -> String {
+        "ycsb".to_string()
+    }
+
+    pub fn replication_factor() -> usize {
+        1
+    }
+
+    pub fn fields() -> usize {
+        10
+    }
+
+    pub fn field_name_prefix() -> String {
+        "field".to_string()
+    }
+
+    pub fn statement_cache_size() -> usize {
+        256
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbError;
+
+    /// The contact points of the test cluster separated by commas, the test is skipped if it is not set.
+    const CONTACT_POINTS_ENV: &str = "YAY_TEST_CASSANDRA_CONTACT_POINTS";
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string().into()))
+            .collect()
+    }
+
+    fn bytes(cells: HashMap<String, Value>) -> HashMap<String, Vec<u8>> {
+        cells
+            .into_iter()
+            .map(|(field, value)| (field, value.into_bytes().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let Ok(contact_points) = std::env::var(CONTACT_POINTS_ENV) else {
+            eprintln!("{CONTACT_POINTS_ENV} is not set, skipped");
+            return;
+        };
+        let db = CassandraDb::new(CassandraDbConfig {
+            contact_points: contact_points.split(',').map(str::to_string).collect(),
+            keyspace: "yay_test".to_string(),
+            replication_factor: 1,
+            fields: 2,
+            field_name_prefix: "field".to_string(),
+            statement_cache_size: default::statement_cache_size(),
+        });
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            db.init().await.unwrap();
+            let table = format!("yay_test_{}", std::process::id());
+
+            for key in ["k2", "k1", "k3"] {
+                db.insert(
+                    table.clone(),
+                    key.to_string(),
+                    values(&[("field0", key), ("field1", "b")]),
+                )
+                .await
+                .unwrap();
+            }
+
+            let record = bytes(
+                db.read(table.clone(), "k1".to_string(), HashSet::new())
+                    .await
+                    .unwrap(),
+            );
+            assert_eq!(record.len(), 2);
+            assert_eq!(record["field0"], b"k1");
+
+            db.update(table.clone(), "k1".to_string(), values(&[("field1", "x")]))
+                .await
+                .unwrap();
+            let record = bytes(
+                db.read(
+                    table.clone(),
+                    "k1".to_string(),
+                    HashSet::from(["field1".to_string()]),
+                )
+                .await
+                .unwrap(),
+            );
+            assert_eq!(
+                record,
+                HashMap::from([("field1".to_string(), b"x".to_vec())])
+            );
+
+            // The scan starts from the token of the start key, so only the count is known.
+            let rows = db
+                .scan_rows(table.clone(), "k1".to_string(), 10, HashSet::new())
+                .await
+                .unwrap();
+            assert!(!rows.is_empty() && rows.len() <= 3);
+            assert!(!db.scan_is_ordered());
+
+            for key in ["k1", "k2", "k3"] {
+                db.delete(table.clone(), key.to_string()).await.unwrap();
+            }
+            let e = db
+                .read(table.clone(), "k1".to_string(), HashSet::new())
+                .await
+                .unwrap_err();
+            assert!(matches!(e, DbError::NotFound(_)));
+
+            db.cleanup().await.unwrap();
+        });
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let error = QueryError::DbError(ScyllaDbError::Overloaded, "overloaded".to_string());
+        assert!(matches!(DbError::from(error), DbError::Transient(_)));
+        let error = QueryError::RequestTimeout("timeout".to_string());
+        assert!(matches!(DbError::from(error), DbError::Transient(_)));
+        let error = QueryError::DbError(ScyllaDbError::SyntaxError, "syntax".to_string());
+        assert!(matches!(DbError::from(error), DbError::Other(_)));
+    }
+}
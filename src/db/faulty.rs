@@ -0,0 +1,288 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use rand::{thread_rng, Rng};
+
+use super::{Capabilities, Db, DbError, DbResult};
+use crate::{utils::Value, workload::Operation};
+
+/// A [`Db`] wrapper that injects failures and latency into the operations of the inner db.
+///
+/// The failures are injected into the chosen operation only: the first `failures` calls fail, and the following calls
+/// fail with the given probability. Batch inserts and batch reads count as one insert and one read. A failed call
+/// never reaches the inner db.
+///
+/// The latency is injected into every operation, before the failure check.
+///
+/// The injected failures are [`DbError::Transient`] by default, which the workload retries, see
+/// [`FaultyDb::with_error_kind`].
+///
+/// Clones of a [`FaultyDb`] share the count of the remaining failures.
+#[derive(Debug, Clone)]
+pub struct FaultyDb<D> {
+    db: D,
+    operation: Option<Operation>,
+    failures: Arc<AtomicUsize>,
+    probability: f64,
+    latency: Duration,
+    error_kind: fn(anyhow::Error) -> DbError,
+}
+
+impl<D> FaultyDb<D>
+where
+    D: Db,
+{
+    /// Wrap the db without injecting anything.
+    pub fn new(db: D) -> Self {
+        Self {
+            db,
+            operation: None,
+            failures: Arc::new(AtomicUsize::new(0)),
+            probability: 0.0,
+            latency: Duration::ZERO,
+            error_kind: DbError::Transient,
+        }
+    }
+
+    /// Fail the first `failures` calls of `operation`.
+    pub fn with_failures(mut self, operation: Operation, failures: usize) -> Self {
+        self.operation = Some(operation);
+        self.failures = Arc::new(AtomicUsize::new(failures));
+        self
+    }
+
+    /// Fail the calls of `operation` with the given probability, after the first failures are consumed.
+    ///
+    /// `probability` is clamped to `[0.0, 1.0]`.
+    pub fn with_failure_probability(mut self, operation: Operation, probability: f64) -> Self {
+        self.operation = Some(operation);
+        self.probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sleep `latency` on every call.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Classify the injected failures with `error_kind`, e.g. `DbError::Fatal` to fail the operations without retries.
+    pub fn with_error_kind(mut self, error_kind: fn(anyhow::Error) -> DbError) -> Self {
+        self.error_kind = error_kind;
+        self
+    }
+
+    /// Get the inner db.
+    pub fn inner(&self) -> &D {
+        &self.db
+    }
+
+    /// Get the count of the remaining failures to inject.
+    pub fn remaining_failures(&self) -> usize {
+        self.failures.load(Ordering::Acquire)
+    }
+
+    fn inject(&self, operation: Operation) -> DbResult<()> {
+        if !self.latency.is_zero() {
+            std::thread::sleep(self.latency);
+        }
+
+        if self.operation != Some(operation) {
+            return Ok(());
+        }
+        if self
+            .failures
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |failures| {
+                failures.checked_sub(1)
+            })
+            .is_ok()
+        {
+            return Err((self.error_kind)(anyhow!(
+                "injected failure on {operation:?}"
+            )));
+        }
+        if self.probability > 0.0 && thread_rng().gen_bool(self.probability) {
+            return Err((self.error_kind)(anyhow!(
+                "injected random failure on {operation:?}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<D> Db for FaultyDb<D>
+where
+    D: Db,
+{
+    type Config = D::Config;
+
+    /// Wrap the db created from the config without injecting anything, see [`FaultyDb::new`].
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(D::from_config(config)?))
+    }
+
+    fn init(&self) -> DbResult<()> {
+        self.db.init()
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        self.db.cleanup()
+    }
+
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        self.inject(Operation::Insert)?;
+        self.db.insert(table, key, values)
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        self.inject(Operation::Read)?;
+        self.db.read(table, key, fields)
+    }
+
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        self.inject(Operation::Update)?;
+        self.db.update(table, key, values)
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        self.inject(Operation::Scan)?;
+        self.db.scan(table, start_key, len, fields)
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        self.inject(Operation::Scan)?;
+        self.db.scan_rows(table, start_key, len, fields)
+    }
+
+    fn scan_is_ordered(&self) -> bool {
+        self.db.scan_is_ordered()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.db.capabilities()
+    }
+
+    fn delete(&self, table: String, key: String) -> DbResult<()> {
+        self.inject(Operation::Delete)?;
+        self.db.delete(table, key)
+    }
+
+    fn insert_batch(
+        &self,
+        table: String,
+        records: Vec<(String, HashMap<String, Value>)>,
+    ) -> DbResult<()> {
+        self.inject(Operation::Insert)?;
+        self.db.insert_batch(table, records)
+    }
+
+    fn read_batch(
+        &self,
+        table: String,
+        keys: Vec<String>,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        self.inject(Operation::Read)?;
+        self.db.read_batch(table, keys, fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::memory::MemoryDb,
+        workload::{CoreWorkload, CoreWorkloadConfig, Workload},
+    };
+
+    fn workload() -> CoreWorkload {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(1)
+            .insertion_retry_limit(3)
+            .insertion_retry_interval(0)
+            .build()
+            .unwrap();
+        CoreWorkload::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_failures_below_retry_limit() {
+        let db = FaultyDb::new(MemoryDb::new()).with_failures(Operation::Insert, 3);
+        workload().insert(db.clone()).unwrap();
+        assert_eq!(db.remaining_failures(), 0);
+        assert_eq!(db.inner().len("ycsb"), 1);
+    }
+
+    #[test]
+    fn test_failures_above_retry_limit() {
+        let db = FaultyDb::new(MemoryDb::new()).with_failures(Operation::Insert, 5);
+        let e = workload().insert(db.clone()).unwrap_err();
+        assert!(e.to_string().contains("exceeded retry limit 3"), "{e}");
+        // The first attempt and the 3 retries.
+        assert_eq!(db.remaining_failures(), 1);
+        assert!(db.inner().is_empty());
+    }
+
+    #[test]
+    fn test_failure_probability() {
+        let db = FaultyDb::new(MemoryDb::new()).with_failure_probability(Operation::Read, 1.0);
+        assert!(db
+            .read("t".to_string(), "k".to_string(), HashSet::new())
+            .is_err());
+        // Only the chosen operation fails.
+        db.insert("t".to_string(), "k".to_string(), HashMap::new())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_always_failing() {
+        let db = FaultyDb::new(MemoryDb::new()).with_failure_probability(Operation::Insert, 1.0);
+        let e = workload().insert(db.clone()).unwrap_err();
+        // The limit and the cause of the last failure.
+        let message = e.to_string();
+        assert!(message.contains("exceeded retry limit 3"), "{message}");
+        assert!(
+            message.contains("injected random failure on Insert"),
+            "{message}"
+        );
+        assert!(db.inner().is_empty());
+    }
+}
@@ -0,0 +1,441 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tonic::{transport::Channel, Code, Status};
+
+use self::proto::{
+    ycsb_client::YcsbClient, DeleteRequest, InsertRequest, ReadRequest, ScanRequest, UpdateRequest,
+};
+use super::{into_columns, not_found, AsyncDb, BoxFuture, DbError, DbResult};
+use crate::utils::Value;
+
+/// The generated messages, client and server of the YCSB service defined in `proto/ycsb.proto`.
+///
+/// Implement [`proto::ycsb_server::Ycsb`] to serve a custom key-value store to the [`GrpcDb`].
+#[allow(missing_docs, clippy::all)]
+pub mod proto {
+    tonic::include_proto!("ycsb");
+}
+
+/// Configuration for the [`GrpcDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GrpcDbConfig {
+    /// The endpoint of the service, e.g. `http://127.0.0.1:50051`.
+    #[serde(default = "default::endpoint")]
+    pub endpoint: String,
+    /// The timeout of each request in milliseconds.
+    #[serde(default = "default::timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+/// An [`AsyncDb`] that talks to a service implementing the YCSB service of `proto/ycsb.proto` over gRPC.
+///
+/// Wrap it in a [`BlockingDb`](super::BlockingDb) to drive it from the client threads.
+///
+/// Clones of a [`GrpcDb`] share the same channel, which multiplexes the in-flight requests. The channel is connected by
+/// the first `init()` call.
+#[derive(Debug, Clone)]
+pub struct GrpcDb {
+    config: GrpcDbConfig,
+    client: Arc<Mutex<Option<YcsbClient<Channel>>>>,
+}
+
+impl GrpcDb {
+    /// Create a gRPC backend with the given config.
+    pub fn new(config: GrpcDbConfig) -> Self {
+        Self {
+            config,
+            client: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn client(&self) -> DbResult<YcsbClient<Channel>> {
+        self.client
+            .lock()
+            .clone()
+            .ok_or_else(|| anyhow!("grpc is not initialized").into())
+    }
+
+    async fn connect(&self) -> DbResult<()> {
+        if self.client.lock().is_some() {
+            return Ok(());
+        }
+        let channel = Channel::from_shared(self.config.endpoint.clone())
+            .map_err(anyhow::Error::from)?
+            .timeout(Duration::from_millis(self.config.timeout_ms))
+            .connect()
+            .await
+            .map_err(|e| DbError::Transient(e.into()))?;
+        self.client
+            .lock()
+            .get_or_insert_with(|| YcsbClient::new(channel));
+        Ok(())
+    }
+}
+
+impl AsyncDb for GrpcDb {
+    type Config = GrpcDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.connect())
+    }
+
+    fn cleanup(&self) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    fn insert(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(async move {
+            let request = InsertRequest {
+                table,
+                key,
+                values: into_bytes(values)?,
+            };
+            self.client()?.insert(request).await?;
+            Ok(())
+        })
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Value>>> {
+        Box::pin(async move {
+            let request = ReadRequest {
+                table: table.clone(),
+                key: key.clone(),
+                fields: fields.into_iter().collect(),
+            };
+            match self.client()?.read(request).await {
+                Ok(response) => Ok(from_bytes(response.into_inner().values)),
+                Err(status) if status.code() == Code::NotFound => Err(not_found(&table, &key)),
+                Err(status) => Err(status.into()),
+            }
+        })
+    }
+
+    fn update(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(async move {
+            let request = UpdateRequest {
+                table,
+                key,
+                values: into_bytes(values)?,
+            };
+            self.client()?.update(request).await?;
+            Ok(())
+        })
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Vec<Value>>>> {
+        Box::pin(async move {
+            self.scan_rows(table, start_key, len, fields)
+                .await
+                .map(into_columns)
+        })
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<Vec<HashMap<String, Value>>>> {
+        Box::pin(async move {
+            let request = ScanRequest {
+                table,
+                start_key,
+                len: len as _,
+                fields: fields.into_iter().collect(),
+            };
+            let response = self.client()?.scan(request).await?;
+            Ok(response
+                .into_inner()
+                .records
+                .into_iter()
+                .map(|record| from_bytes(record.values))
+                .collect())
+        })
+    }
+
+    fn delete(&self, table: String, key: String) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(async move {
+            self.client()?.delete(DeleteRequest { table, key }).await?;
+            Ok(())
+        })
+    }
+}
+
+impl From<Status> for DbError {
+    /// Classify the error of a call by its status code.
+    fn from(status: Status) -> Self {
+        let code = status.code();
+        let error = anyhow::Error::from(status);
+        match code {
+            Code::NotFound => Self::NotFound(error),
+            Code::Aborted => Self::Conflict(error),
+            Code::Unavailable | Code::DeadlineExceeded | Code::ResourceExhausted => {
+                Self::Transient(error)
+            }
+            Code::InvalidArgument
+            | Code::Unimplemented
+            | Code::PermissionDenied
+            | Code::Unauthenticated
+            | Code::FailedPrecondition => Self::Fatal(error),
+            _ => Self::Other(error),
+        }
+    }
+}
+
+fn into_bytes(values: HashMap<String, Value>) -> anyhow::Result<HashMap<String, Vec<u8>>> {
+    values
+        .into_iter()
+        .map(|(field, value)| Ok((field, value.into_bytes()?)))
+        .collect()
+}
+
+fn from_bytes(values: HashMap<String, Vec<u8>>) -> HashMap<String, Value> {
+    values
+        .into_iter()
+        .map(|(field, value)| (field, value.into()))
+        .collect()
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn endpoint() -> String {
+        "http://127.0.0.1:50051".to_string()
+    }
+
+    pub fn timeout_ms() -> u64 {
+        1000
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::{
+        transport::{server::TcpIncoming, Server},
+        Request, Response,
+    };
+
+    use super::{
+        proto::{
+            ycsb_server::{Ycsb, YcsbServer},
+            DeleteResponse, InsertResponse, ReadResponse, Record, ScanResponse, UpdateResponse,
+        },
+        *,
+    };
+    use crate::db::{memory::MemoryDb, Db};
+
+    /// A mock of the service that keeps the records in a [`MemoryDb`].
+    #[derive(Debug, Default)]
+    struct MockYcsb {
+        db: MemoryDb,
+    }
+
+    fn status(error: DbError) -> Status {
+        match error {
+            DbError::NotFound(e) => Status::not_found(e.to_string()),
+            e => Status::internal(e.to_string()),
+        }
+    }
+
+    fn bytes(values: HashMap<String, Value>) -> HashMap<String, Vec<u8>> {
+        into_bytes(values).unwrap()
+    }
+
+    #[tonic::async_trait]
+    impl Ycsb for MockYcsb {
+        async fn insert(
+            &self,
+            request: Request<InsertRequest>,
+        ) -> std::result::Result<Response<InsertResponse>, Status> {
+            let request = request.into_inner();
+            self.db
+                .insert(request.table, request.key, from_bytes(request.values))
+                .map_err(status)?;
+            Ok(Response::new(InsertResponse {}))
+        }
+
+        async fn read(
+            &self,
+            request: Request<ReadRequest>,
+        ) -> std::result::Result<Response<ReadResponse>, Status> {
+            let request = request.into_inner();
+            let values = self
+                .db
+                .read(
+                    request.table,
+                    request.key,
+                    request.fields.into_iter().collect(),
+                )
+                .map_err(status)?;
+            Ok(Response::new(ReadResponse {
+                values: bytes(values),
+            }))
+        }
+
+        async fn update(
+            &self,
+            request: Request<UpdateRequest>,
+        ) -> std::result::Result<Response<UpdateResponse>, Status> {
+            let request = request.into_inner();
+            self.db
+                .update(request.table, request.key, from_bytes(request.values))
+                .map_err(status)?;
+            Ok(Response::new(UpdateResponse {}))
+        }
+
+        async fn scan(
+            &self,
+            request: Request<ScanRequest>,
+        ) -> std::result::Result<Response<ScanResponse>, Status> {
+            let request = request.into_inner();
+            let rows = self
+                .db
+                .scan_rows(
+                    request.table,
+                    request.start_key,
+                    request.len as usize,
+                    request.fields.into_iter().collect(),
+                )
+                .map_err(status)?;
+            Ok(Response::new(ScanResponse {
+                records: rows
+                    .into_iter()
+                    .map(|row| Record { values: bytes(row) })
+                    .collect(),
+            }))
+        }
+
+        async fn delete(
+            &self,
+            request: Request<DeleteRequest>,
+        ) -> std::result::Result<Response<DeleteResponse>, Status> {
+            let request = request.into_inner();
+            self.db.delete(request.table, request.key).map_err(status)?;
+            Ok(Response::new(DeleteResponse {}))
+        }
+    }
+
+    #[test]
+    fn test_mock_server() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+            let mock = MockYcsb::default();
+            let records = mock.db.clone();
+            tokio::spawn(
+                Server::builder()
+                    .add_service(YcsbServer::new(mock))
+                    .serve_with_incoming(incoming),
+            );
+
+            let db = GrpcDb::new(GrpcDbConfig {
+                endpoint: format!("http://{addr}"),
+                timeout_ms: 1000,
+            });
+            let e = db
+                .read("t".to_string(), "k1".to_string(), HashSet::new())
+                .await
+                .unwrap_err();
+            assert!(e.to_string().contains("not initialized"), "{e}");
+            db.init().await.unwrap();
+
+            for key in ["k2", "k1", "k3"] {
+                let values = HashMap::from([
+                    ("field0".to_string(), key.as_bytes().to_vec().into()),
+                    ("field1".to_string(), b"b".to_vec().into()),
+                ]);
+                db.insert("t".to_string(), key.to_string(), values)
+                    .await
+                    .unwrap();
+            }
+            assert_eq!(records.len("t"), 3);
+
+            let values = HashMap::from([("field1".to_string(), b"x".to_vec().into())]);
+            db.update("t".to_string(), "k1".to_string(), values)
+                .await
+                .unwrap();
+            let record = db
+                .read(
+                    "t".to_string(),
+                    "k1".to_string(),
+                    HashSet::from(["field1".to_string()]),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                bytes(record),
+                HashMap::from([("field1".to_string(), b"x".to_vec())])
+            );
+
+            let rows = db
+                .scan_rows("t".to_string(), "k2".to_string(), 10, HashSet::new())
+                .await
+                .unwrap();
+            let keys = rows
+                .into_iter()
+                .map(|row| bytes(row).remove("field0").unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+
+            db.delete("t".to_string(), "k1".to_string()).await.unwrap();
+            let e = db
+                .read("t".to_string(), "k1".to_string(), HashSet::new())
+                .await
+                .unwrap_err();
+            assert!(matches!(e, DbError::NotFound(_)));
+            db.cleanup().await.unwrap();
+        });
+    }
+}
@@ -0,0 +1,282 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+
+use parking_lot::RwLock;
+
+use super::{into_columns, not_found, project, Db, DbResult};
+use crate::utils::Value;
+
+type Record = HashMap<String, Vec<u8>>;
+
+/// A [`Db`] that keeps the records in memory, e.g. to test the workloads without a database.
+///
+/// Each table is a map ordered by the record keys, so the scans return the records in the order of their keys. An
+/// update of a missing record creates it, and a delete of a missing record succeeds.
+///
+/// Clones of a [`MemoryDb`] share the same records, so clone one instance for each client thread.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDb {
+    tables: Arc<RwLock<HashMap<String, BTreeMap<String, Record>>>>,
+}
+
+impl MemoryDb {
+    /// Create an empty in-memory backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of records in the table.
+    pub fn len(&self, table: &str) -> usize {
+        self.tables.read().get(table).map_or(0, BTreeMap::len)
+    }
+
+    /// Returns `true` if there is no record in any table.
+    pub fn is_empty(&self) -> bool {
+        self.tables.read().values().all(BTreeMap::is_empty)
+    }
+
+    /// Get the names of the tables with any record, in order.
+    pub fn tables(&self) -> Vec<String> {
+        let mut tables = self
+            .tables
+            .read()
+            .iter()
+            .filter(|(_, records)| !records.is_empty())
+            .map(|(table, _)| table.clone())
+            .collect::<Vec<_>>();
+        tables.sort();
+        tables
+    }
+
+    /// Get the stored field/value pairs of the record, e.g. to inspect what the workload has written.
+    pub fn get(&self, table: &str, key: &str) -> Option<HashMap<String, Vec<u8>>> {
+        self.tables.read().get(table)?.get(key).cloned()
+    }
+}
+
+/// Read all the values into bytes.
+fn into_record(values: HashMap<String, Value>) -> DbResult<Record> {
+    values
+        .into_iter()
+        .map(|(field, value)| Ok((field, value.into_bytes()?)))
+        .collect()
+}
+
+/// Project the record to the fields and wrap the values, see [`project`].
+fn project_values(record: &Record, fields: &HashSet<String>) -> HashMap<String, Value> {
+    project(record.clone(), fields)
+        .map(|(field, value)| (field, value.into()))
+        .collect()
+}
+
+impl Db for MemoryDb {
+    type Config = ();
+
+    fn from_config(_: Self::Config) -> DbResult<Self> {
+        Ok(Self::new())
+    }
+
+    fn init(&self) -> DbResult<()> {
+        Ok(())
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        Ok(())
+    }
+
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        let record = into_record(values)?;
+        self.tables
+            .write()
+            .entry(table)
+            .or_default()
+            .insert(key, record);
+        Ok(())
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let tables = self.tables.read();
+        let record = tables
+            .get(&table)
+            .and_then(|records| records.get(&key))
+            .ok_or_else(|| not_found(&table, &key))?;
+        Ok(project_values(record, &fields))
+    }
+
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        let values = into_record(values)?;
+        self.tables
+            .write()
+            .entry(table)
+            .or_default()
+            .entry(key)
+            .or_default()
+            .extend(values);
+        Ok(())
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        self.scan_rows(table, start_key, len, fields)
+            .map(into_columns)
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let tables = self.tables.read();
+        let Some(records) = tables.get(&table) else {
+            return Ok(vec![]);
+        };
+        Ok(records
+            .range(start_key..)
+            .take(len)
+            .map(|(_, record)| project_values(record, &fields))
+            .collect())
+    }
+
+    fn delete(&self, table: String, key: String) -> DbResult<()> {
+        if let Some(records) = self.tables.write().get_mut(&table) {
+            records.remove(&key);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbError;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string().into()))
+            .collect()
+    }
+
+    fn bytes(mut cells: HashMap<String, Value>) -> BTreeMap<String, Vec<u8>> {
+        cells
+            .drain()
+            .map(|(field, value)| (field, value.into_bytes().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_read_empty_fields_returns_all_fields() {
+        let db = MemoryDb::new();
+        db.insert(
+            "t".to_string(),
+            "k".to_string(),
+            values(&[("f0", "a"), ("f1", "b"), ("f2", "c")]),
+        )
+        .unwrap();
+
+        let all = bytes(
+            db.read("t".to_string(), "k".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(all.len(), 3);
+        assert_eq!(all["f0"], b"a");
+        assert_eq!(all["f1"], b"b");
+        assert_eq!(all["f2"], b"c");
+
+        let projected = bytes(
+            db.read(
+                "t".to_string(),
+                "k".to_string(),
+                HashSet::from(["f1".to_string()]),
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            projected.into_iter().collect::<Vec<_>>(),
+            vec![("f1".to_string(), b"b".to_vec())]
+        );
+    }
+
+    #[test]
+    fn test_update_merges_fields() {
+        let db = MemoryDb::new();
+        db.insert(
+            "t".to_string(),
+            "k".to_string(),
+            values(&[("f0", "a"), ("f1", "b")]),
+        )
+        .unwrap();
+        db.update("t".to_string(), "k".to_string(), values(&[("f1", "x")]))
+            .unwrap();
+
+        let record = db.get("t", "k").unwrap();
+        assert_eq!(record["f0"], b"a");
+        assert_eq!(record["f1"], b"x");
+    }
+
+    #[test]
+    fn test_scan_in_key_order() {
+        let db = MemoryDb::new();
+        for key in ["k3", "k1", "k4", "k2"] {
+            db.insert("t".to_string(), key.to_string(), values(&[("f0", key)]))
+                .unwrap();
+        }
+
+        let rows = db
+            .scan_rows("t".to_string(), "k2".to_string(), 2, HashSet::new())
+            .unwrap();
+        let keys = rows
+            .into_iter()
+            .map(|row| bytes(row)["f0"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+
+        let columns = db
+            .scan("t".to_string(), "k0".to_string(), 10, HashSet::new())
+            .unwrap();
+        assert_eq!(columns["f0"].len(), 4);
+    }
+
+    #[test]
+    fn test_read_missing_record() {
+        let db = MemoryDb::new();
+        db.insert("t".to_string(), "k".to_string(), values(&[("f0", "a")]))
+            .unwrap();
+        db.delete("t".to_string(), "k".to_string()).unwrap();
+        assert!(db.is_empty());
+
+        let e = db
+            .read("t".to_string(), "k".to_string(), HashSet::new())
+            .unwrap_err();
+        assert!(matches!(e, DbError::NotFound(_)));
+        assert!(!e.is_retryable());
+    }
+}
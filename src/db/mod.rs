@@ -0,0 +1,764 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    pin::Pin,
+};
+
+use anyhow::anyhow;
+
+use crate::{utils::Value, workload::Operation};
+
+/// A layer for accessing a database to be benchmarked. Each thread in the client
+/// will be given its own instance of whatever DB class is to be used in the test.
+/// This class should be constructed from its config by from_config(), so the client
+/// can create an instance per thread from a shared config. Any initialization that
+/// touches the database, e.g. connecting to it, should be done by init().
+///
+/// Note that YCSB does not make any use of the return codes returned by this class.
+/// Instead, it keeps a count of the return values and presents them to the user.
+///
+/// The semantics of methods such as insert, update and delete vary from database
+/// to database.  In particular, operations may or may not be durable once these
+/// methods commit, and some systems may return 'success' regardless of whether
+/// or not a tuple with a matching key existed before the call.  Rather than dictate
+/// the exact semantics of these methods, we recommend you either implement them
+/// to match the database's default semantics, or the semantics of your
+/// target application.  For the sake of comparison between experiments we also
+/// recommend you explain the semantics you chose when presenting performance results.
+///
+/// The methods may classify their errors with [`DbError`], which tells the workload whether to retry them.
+pub trait Db {
+    /// Configuration type for db.
+    type Config;
+
+    /// Create a new DB instance with the given config.
+    fn from_config(config: Self::Config) -> DbResult<Self>
+    where
+        Self: Sized;
+
+    /// Initialize any state for this DB.
+    /// Called once per DB instance; there is one DB instance per client thread.
+    fn init(&self) -> DbResult<()>;
+
+    /// Cleanup any state for this DB.
+    /// Called once per DB instance; there is one DB instance per client thread.
+    fn cleanup(&self) -> DbResult<()>;
+
+    /// Insert a record in the database. Any field/value pairs in the specified values HashMap will be written into the
+    /// record with the specified record key.
+    ///
+    /// * `table` - The name of the table
+    /// * `key` - The record key of the record to insert.
+    /// * `values` - A HashMap of field/value pairs to insert in the record
+    ///
+    /// Returns the result of the operation.
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()>;
+
+    /// Read a record from the database. Each field/value pair from the result will be stored in a HashMap.
+    ///
+    /// An empty `fields` set reads all the fields of the record, there is no way to read none of them. The workload
+    /// passes an empty set when it reads all the fields without naming them, so every backend must return the whole
+    /// record for it.
+    ///
+    /// * `table` - The name of the table
+    /// * `key` - The record key of the record to read.
+    /// * `fields` - The list of fields to read, or empty for all of them
+    /// * `result` - A HashMap of field/value pairs for the result
+    ///
+    /// Returns the result of the operation.
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>>;
+
+    /// Update a record in the database. Any field/value pairs in the specified values HashMap will be written into the
+    /// record with the specified record key, overwriting any existing values with the same field name.
+    ///
+    /// * `table` - The name of the table
+    /// * `key` - The record key of the record to write.
+    /// * `values` - A HashMap of field/value pairs to update in the record
+    ///
+    /// Returns the result of the operation.
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()>;
+
+    /// Perform a range scan for a set of records in the database. Each field/value pair from the result will be stored
+    /// in a HashMap.
+    ///
+    /// * `table` - The name of the table
+    /// * `startkey` - The record key of the first record to read.
+    /// * `recordcount` - The number of records to read
+    /// * `fields` - The list of fields to read, or empty for all of them, see [`Db::read`]
+    /// * `result` - A Vector of HashMaps, where each HashMap is a set field/value pairs for one record
+    ///
+    /// Returns the result of the operation.
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>>;
+
+    /// Perform a range scan like [`Db::scan`], but return the field/value pairs of each record separately, in the scan
+    /// order.
+    ///
+    /// The columnar result of [`Db::scan`] is cheap to build and enough to benchmark, but it loses the boundaries of the
+    /// records, e.g. a record that misses a field shifts the values of the following records. The workload verifies
+    /// the scanned records with this method when data integrity is enabled. The default implementation returns an
+    /// error.
+    ///
+    /// * `table` - The name of the table
+    /// * `start_key` - The record key of the first record to read.
+    /// * `len` - The number of records to read
+    /// * `fields` - The list of fields to read, or empty for all of them
+    ///
+    /// Returns the field/value pairs of the records, in the scan order.
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let _ = (table, start_key, len, fields);
+        Err(unsupported("scan_rows"))
+    }
+
+    /// Returns `true` if the records of [`Db::scan`] and [`Db::scan_rows`] are in the order of their keys, which is the
+    /// default.
+    ///
+    /// Backends that return the scanned records in any other order, e.g. the hash-partitioned stores, must return
+    /// `false`, so the workload skips the order-dependent checks of the scanned records when data integrity is enabled.
+    fn scan_is_ordered(&self) -> bool {
+        true
+    }
+
+    /// Delete a record from the database.
+    ///
+    /// * `table` - The name of the table
+    /// * `key` - The record key of the record to delete.
+    ///
+    /// Returns the result of the operation.
+    fn delete(&self, table: String, key: String) -> DbResult<()>;
+
+    /// Returns the operations supported by the backend, which is all of them by default.
+    ///
+    /// Backends that can't do some operations, e.g. an append-only store that can't update or a cache that can't scan,
+    /// should advertise it, so the client refuses to run a workload that issues them instead of failing at runtime.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::all()
+    }
+
+    /// Insert a batch of records in the database. Backends that support batching should override it, the default
+    /// implementation inserts the records one by one.
+    ///
+    /// * `table` - The name of the table
+    /// * `records` - The record keys and the field/value pairs of the records to insert.
+    ///
+    /// Returns the result of the operation.
+    fn insert_batch(
+        &self,
+        table: String,
+        records: Vec<(String, HashMap<String, Value>)>,
+    ) -> DbResult<()> {
+        for (key, values) in records {
+            self.insert(table.clone(), key, values)?;
+        }
+        Ok(())
+    }
+
+    /// Read a batch of records from the database. Backends that support batching should override it, the default
+    /// implementation reads the records one by one.
+    ///
+    /// * `table` - The name of the table
+    /// * `keys` - The record keys of the records to read.
+    /// * `fields` - The list of fields to read, or empty for all of them
+    ///
+    /// Returns the field/value pairs of the records, in the order of `keys`.
+    fn read_batch(
+        &self,
+        table: String,
+        keys: Vec<String>,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        keys.into_iter()
+            .map(|key| self.read(table.clone(), key, fields.clone()))
+            .collect()
+    }
+}
+
+/// The operations supported by a [`Db`], see [`Db::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Whether inserts are supported.
+    pub insert: bool,
+    /// Whether reads are supported.
+    pub read: bool,
+    /// Whether updates are supported.
+    pub update: bool,
+    /// Whether scans are supported.
+    pub scan: bool,
+    /// Whether deletes are supported.
+    pub delete: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl Capabilities {
+    /// All the operations are supported.
+    pub fn all() -> Self {
+        Self {
+            insert: true,
+            read: true,
+            update: true,
+            scan: true,
+            delete: true,
+        }
+    }
+
+    /// Returns `true` if the operation is supported.
+    pub fn supports(&self, operation: Operation) -> bool {
+        match operation {
+            Operation::Insert => self.insert,
+            Operation::Read => self.read,
+            Operation::Update => self.update,
+            Operation::Scan => self.scan,
+            Operation::Delete => self.delete,
+        }
+    }
+}
+
+/// The kind of a failed operation, which decides whether the workload retries it.
+///
+/// The [`Db`] methods return a [`DbResult`], so the backends classify the errors of their drivers, e.g. with a
+/// `From<driver::Error>` conversion that maps the timeouts and the lost connections to [`DbError::Transient`]. The
+/// [`anyhow::Error`]s convert with `?` too, which keeps the kind of a [`DbError`] inside them and takes the others as
+/// [`DbError::Other`]. So do the [`std::io::Error`]s, whose timeouts and connection errors are transient.
+///
+/// The workload only retries the transient errors and the conflicts, and fails fast on all the other errors, see
+/// [`is_retryable`].
+#[derive(Debug)]
+pub enum DbError {
+    /// The record doesn't exist.
+    NotFound(anyhow::Error),
+    /// The operation conflicts with a concurrent one, e.g. an aborted transaction.
+    Conflict(anyhow::Error),
+    /// The operation may succeed if it is tried again, e.g. on a timeout or a lost connection.
+    Transient(anyhow::Error),
+    /// The operation will never succeed, e.g. on a schema mismatch or a permission error.
+    Fatal(anyhow::Error),
+    /// The error is not classified.
+    Other(anyhow::Error),
+}
+
+impl DbError {
+    /// Get the inner error.
+    pub fn error(&self) -> &anyhow::Error {
+        match self {
+            Self::NotFound(e)
+            | Self::Conflict(e)
+            | Self::Transient(e)
+            | Self::Fatal(e)
+            | Self::Other(e) => e,
+        }
+    }
+
+    /// Returns `true` if the operation may succeed when it is tried again, which is the case for the transient errors
+    /// and the conflicts only.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Conflict(_) | Self::Transient(_))
+    }
+}
+
+impl std::fmt::Display for DbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.error().fmt(f)
+    }
+}
+
+impl std::error::Error for DbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error().source()
+    }
+}
+
+impl From<anyhow::Error> for DbError {
+    /// Take the classified error out, or wrap the error as [`DbError::Other`].
+    fn from(error: anyhow::Error) -> Self {
+        error.downcast().unwrap_or_else(Self::Other)
+    }
+}
+
+impl From<std::io::Error> for DbError {
+    /// Classify the timeouts and the connection errors as [`DbError::Transient`], and the others as
+    /// [`DbError::Other`].
+    fn from(error: std::io::Error) -> Self {
+        if is_transient_io(&error) {
+            Self::Transient(error.into())
+        } else {
+            Self::Other(error.into())
+        }
+    }
+}
+
+/// The result of the operations of a [`Db`] or an [`AsyncDb`].
+pub type DbResult<T> = std::result::Result<T, DbError>;
+
+/// Returns `true` if the failed operation may succeed when it is tried again, which is only the case if the error is a
+/// retryable [`DbError`].
+///
+/// Unlike YCSB, which retries any failed insert up to `insertionretrylimit` times, the errors that are not classified
+/// as transient or conflicting, including the [`DbError::Other`]s, are not retried.
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<DbError>()
+        .map_or(false, DbError::is_retryable)
+}
+
+/// Returns `true` if the I/O error is a timeout or a connection error, which may go away when it is tried again.
+pub(crate) fn is_transient_io(error: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+
+    matches!(
+        error.kind(),
+        ErrorKind::TimedOut
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+/// The error of a missing record.
+pub(crate) fn not_found(table: &str, key: &str) -> DbError {
+    DbError::NotFound(anyhow!("record not found, table: {table}, key: {key}"))
+}
+
+/// The error of an operation that the backend doesn't support, which never succeeds.
+pub(crate) fn unsupported(operation: &str) -> DbError {
+    DbError::Fatal(anyhow!("{operation} is not supported"))
+}
+
+/// A boxed future that can be sent across threads.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The asynchronous variant of [`Db`], which is used to access databases with asynchronous clients without blocking a
+/// thread per in-flight operation.
+///
+/// The futures returned by the methods may be polled concurrently, because there can be multiple in-flight operations
+/// of the same instance. They must be [`Send`], so they can be driven on any thread of a multi-threaded runtime.
+///
+/// See [`Db`] for the semantics of the operations.
+pub trait AsyncDb {
+    /// Configuration type for db.
+    type Config;
+
+    /// Create a new DB instance with the given config. See [`Db::from_config`].
+    fn from_config(config: Self::Config) -> DbResult<Self>
+    where
+        Self: Sized;
+
+    /// Initialize any state for this DB.
+    fn init(&self) -> BoxFuture<'_, DbResult<()>>;
+
+    /// Cleanup any state for this DB.
+    fn cleanup(&self) -> BoxFuture<'_, DbResult<()>>;
+
+    /// Insert a record in the database. See [`Db::insert`].
+    fn insert(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>>;
+
+    /// Read a record from the database. See [`Db::read`].
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Value>>>;
+
+    /// Update a record in the database. See [`Db::update`].
+    fn update(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>>;
+
+    /// Perform a range scan for a set of records in the database. See [`Db::scan`].
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Vec<Value>>>>;
+
+    /// Perform a range scan that returns the records separately. See [`Db::scan_rows`].
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<Vec<HashMap<String, Value>>>> {
+        let _ = (table, start_key, len, fields);
+        Box::pin(std::future::ready(Err(unsupported("scan_rows"))))
+    }
+
+    /// Returns `true` if the scanned records are in the order of their keys. See [`Db::scan_is_ordered`].
+    fn scan_is_ordered(&self) -> bool {
+        true
+    }
+
+    /// Delete a record from the database. See [`Db::delete`].
+    fn delete(&self, table: String, key: String) -> BoxFuture<'_, DbResult<()>>;
+
+    /// Returns the operations supported by the backend. See [`Db::capabilities`].
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::all()
+    }
+}
+
+/// An adapter that implements [`Db`] for an [`AsyncDb`] by driving its futures on the given tokio runtime.
+///
+/// Each call blocks the calling client thread until the future completes, while the futures of concurrent calls from
+/// different client threads make progress on the runtime concurrently. The client threads must not be the worker
+/// threads of the runtime, or the calls will panic.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone)]
+pub struct BlockingDb<D> {
+    db: D,
+    handle: tokio::runtime::Handle,
+}
+
+#[cfg(feature = "tokio")]
+impl<D> BlockingDb<D>
+where
+    D: AsyncDb,
+{
+    /// Create an adapter that drives the futures of `db` on the runtime of `handle`.
+    pub fn new(db: D, handle: tokio::runtime::Handle) -> Self {
+        Self { db, handle }
+    }
+
+    /// Get the inner asynchronous db.
+    pub fn inner(&self) -> &D {
+        &self.db
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<D> Db for BlockingDb<D>
+where
+    D: AsyncDb,
+{
+    /// The config of the inner db and the handle of the runtime to drive its futures on.
+    type Config = (D::Config, tokio::runtime::Handle);
+
+    fn from_config((config, handle): Self::Config) -> DbResult<Self> {
+        Ok(Self::new(D::from_config(config)?, handle))
+    }
+
+    fn init(&self) -> DbResult<()> {
+        self.handle.block_on(self.db.init())
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        self.handle.block_on(self.db.cleanup())
+    }
+
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        self.handle.block_on(self.db.insert(table, key, values))
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        self.handle.block_on(self.db.read(table, key, fields))
+    }
+
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        self.handle.block_on(self.db.update(table, key, values))
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        self.handle
+            .block_on(self.db.scan(table, start_key, len, fields))
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        self.handle
+            .block_on(self.db.scan_rows(table, start_key, len, fields))
+    }
+
+    fn scan_is_ordered(&self) -> bool {
+        self.db.scan_is_ordered()
+    }
+
+    fn delete(&self, table: String, key: String) -> DbResult<()> {
+        self.handle.block_on(self.db.delete(table, key))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.db.capabilities()
+    }
+}
+
+/// Transpose the records of a scan into the columnar result of [`Db::scan`].
+pub(crate) fn into_columns(rows: Vec<HashMap<String, Value>>) -> HashMap<String, Vec<Value>> {
+    let mut columns: HashMap<String, Vec<Value>> = HashMap::new();
+    for (field, value) in rows.into_iter().flatten() {
+        columns.entry(field).or_default().push(value);
+    }
+    columns
+}
+
+/// Keep only the given fields of the record, or all of them if `fields` is empty.
+pub(crate) fn project<'a>(
+    record: HashMap<String, Vec<u8>>,
+    fields: &'a HashSet<String>,
+) -> impl Iterator<Item = (String, Vec<u8>)> + 'a {
+    record
+        .into_iter()
+        .filter(move |(field, _)| fields.is_empty() || fields.contains(field))
+}
+
+/// Encode the field/value pairs as `<field len: u32><field><value len: u32><value>` sequences.
+#[cfg(any(feature = "object-store", feature = "rocksdb", feature = "sled"))]
+pub(crate) fn encode(record: &HashMap<String, Vec<u8>>) -> Vec<u8> {
+    let mut buf = vec![];
+    for (field, value) in record {
+        buf.extend_from_slice(&(field.len() as u32).to_le_bytes());
+        buf.extend_from_slice(field.as_bytes());
+        buf.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        buf.extend_from_slice(value);
+    }
+    buf
+}
+
+/// Decode the field/value pairs encoded by [`encode`].
+#[cfg(any(feature = "object-store", feature = "rocksdb", feature = "sled"))]
+pub(crate) fn decode(mut buf: &[u8]) -> anyhow::Result<HashMap<String, Vec<u8>>> {
+    fn take<'a>(buf: &mut &'a [u8]) -> anyhow::Result<&'a [u8]> {
+        if buf.len() < 4 {
+            return Err(anyhow!("corrupted record"));
+        }
+        let len = u32::from_le_bytes(buf[..4].try_into().unwrap()) as usize;
+        if buf.len() < 4 + len {
+            return Err(anyhow!("corrupted record"));
+        }
+        let data = &buf[4..4 + len];
+        *buf = &buf[4 + len..];
+        Ok(data)
+    }
+
+    let mut record = HashMap::new();
+    while !buf.is_empty() {
+        let field = String::from_utf8(take(&mut buf)?.to_vec())?;
+        let value = take(&mut buf)?.to_vec();
+        record.insert(field, value);
+    }
+    Ok(record)
+}
+
+/// Cassandra and ScyllaDB backend.
+#[cfg(feature = "scylla")]
+pub mod cassandra;
+
+/// Fault injecting wrapper.
+pub mod faulty;
+
+/// gRPC backend.
+#[cfg(feature = "grpc")]
+pub mod grpc;
+
+/// In-memory backend.
+pub mod memory;
+
+/// MongoDB backend.
+#[cfg(feature = "mongodb")]
+pub mod mongo;
+
+/// No-op backend.
+pub mod null;
+
+/// Object store backend, e.g. S3, GCS, Azure Blob Storage or the local file system.
+#[cfg(feature = "object-store")]
+pub mod object_store;
+
+/// PostgreSQL backend.
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+/// Redis backend.
+#[cfg(feature = "redis")]
+pub mod redis;
+
+/// RocksDB backend.
+#[cfg(feature = "rocksdb")]
+pub mod rocksdb;
+
+/// Sled backend.
+#[cfg(feature = "sled")]
+pub mod sled;
+
+/// SQLite backend.
+#[cfg(feature = "rusqlite")]
+pub mod sqlite;
+
+#[cfg(test)]
+mod tests {
+    use super::{faulty::FaultyDb, memory::MemoryDb, null::NullDb, *};
+
+    /// Create and initialize a db only through the trait, like a runner that knows nothing but the config type.
+    fn create<D: Db>(config: D::Config) -> D {
+        let db = D::from_config(config).unwrap();
+        db.init().unwrap();
+        db
+    }
+
+    #[test]
+    fn test_from_config() {
+        let db: MemoryDb = create(());
+        db.insert(
+            "t".to_string(),
+            "k".to_string(),
+            HashMap::from([("f0".to_string(), b"v".to_vec().into())]),
+        )
+        .unwrap();
+        let cells = db
+            .read("t".to_string(), "k".to_string(), HashSet::new())
+            .unwrap();
+        assert_eq!(cells["f0"].clone().into_bytes().unwrap(), b"v");
+        db.cleanup().unwrap();
+
+        let db: NullDb = create(());
+        assert!(db
+            .read("t".to_string(), "k".to_string(), HashSet::new())
+            .unwrap()
+            .is_empty());
+
+        let db: FaultyDb<MemoryDb> = create(());
+        assert_eq!(db.remaining_failures(), 0);
+        assert!(db.inner().is_empty());
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        let kinds = [
+            (DbError::NotFound as fn(_) -> _, false),
+            (DbError::Conflict, true),
+            (DbError::Transient, true),
+            (DbError::Fatal, false),
+            (DbError::Other, false),
+        ];
+        for (kind, retryable) in kinds {
+            let error = anyhow::Error::from(kind(anyhow!("error")));
+            assert_eq!(is_retryable(&error), retryable, "{error:?}");
+            // The kind survives the context.
+            assert_eq!(is_retryable(&error.context("context")), retryable);
+        }
+        assert!(!is_retryable(&anyhow!("unclassified error")));
+
+        let error = anyhow::Error::from(DbError::Transient(anyhow!("timeout")));
+        assert!(matches!(DbError::from(error), DbError::Transient(_)));
+        assert!(matches!(
+            DbError::from(anyhow!("unclassified error")),
+            DbError::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_scan_rows_order() {
+        let db = MemoryDb::new();
+        for i in [3, 0, 4, 1, 2] {
+            let values = HashMap::from([
+                ("f0".to_string(), format!("a{i}").into_bytes().into()),
+                ("f1".to_string(), format!("b{i}").into_bytes().into()),
+            ]);
+            db.insert("t".to_string(), format!("k{i}"), values).unwrap();
+        }
+
+        let rows = db
+            .scan_rows("t".to_string(), "k1".to_string(), 3, HashSet::new())
+            .unwrap();
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let mut row = row
+                    .into_iter()
+                    .map(|(field, value)| (field, value.into_bytes().unwrap()))
+                    .collect::<Vec<_>>();
+                row.sort();
+                row
+            })
+            .collect::<Vec<_>>();
+        // Each row keeps its own fields, in the order of the keys.
+        for (row, i) in rows.iter().zip(1..=3) {
+            assert_eq!(
+                row,
+                &vec![
+                    ("f0".to_string(), format!("a{i}").into_bytes()),
+                    ("f1".to_string(), format!("b{i}").into_bytes())
+                ]
+            );
+        }
+
+        // The columns of a scan keep the order of the rows.
+        let columns = into_columns(
+            db.scan_rows("t".to_string(), "k0".to_string(), 5, HashSet::new())
+                .unwrap(),
+        );
+        let column = columns["f1"]
+            .iter()
+            .map(|value| value.clone().into_bytes().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            column,
+            ["b0", "b1", "b2", "b3", "b4"].map(|v| v.as_bytes().to_vec())
+        );
+    }
+}
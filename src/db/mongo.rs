@@ -0,0 +1,441 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use mongodb::{
+    bson::{doc, spec::BinarySubtype, Binary, Bson, Document},
+    error::{Error, ErrorKind, RETRYABLE_WRITE_ERROR, TRANSIENT_TRANSACTION_ERROR},
+    Client, Collection,
+};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::{into_columns, not_found, AsyncDb, BoxFuture, DbError, DbResult};
+use crate::utils::Value;
+
+/// Configuration for the [`MongoDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MongoDbConfig {
+    /// The connection string of the deployment, e.g. `mongodb://127.0.0.1:27017`.
+    #[serde(default = "default::uri")]
+    pub uri: String,
+    /// The database of the collections.
+    #[serde(default = "default::database")]
+    pub database: String,
+    /// The collection to store the records of all the tables in.
+    ///
+    /// If empty, the records of each table are stored in the collection named after the table. Otherwise the tables
+    /// share the collection, so their keys must not collide.
+    #[serde(default = "default::collection")]
+    pub collection: String,
+}
+
+/// An [`AsyncDb`] backed by MongoDB.
+///
+/// Wrap it in a [`BlockingDb`](super::BlockingDb) to drive it from the client threads.
+///
+/// Each record is a document `{ _id: key, field0: ..., ... }` with the values stored as generic binary. Scans read the
+/// documents from the start key in the order of `_id`.
+///
+/// Clones of a [`MongoDb`] share the same client. The client is connected by the first `init()` call.
+#[derive(Debug, Clone)]
+pub struct MongoDb {
+    config: Arc<MongoDbConfig>,
+    client: Arc<Mutex<Option<Client>>>,
+}
+
+impl MongoDb {
+    /// Create a MongoDB backend with the given config. The client is connected by `init()`.
+    pub fn new(config: MongoDbConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            client: Arc::default(),
+        }
+    }
+
+    async fn connect(&self) -> DbResult<()> {
+        if self.client.lock().is_some() {
+            return Ok(());
+        }
+        let client = Client::with_uri_str(&self.config.uri).await?;
+        self.client.lock().get_or_insert(client);
+        Ok(())
+    }
+
+    fn collection(&self, table: &str) -> DbResult<Collection<Document>> {
+        let client = self
+            .client
+            .lock()
+            .clone()
+            .ok_or_else(|| anyhow!("mongodb is not initialized"))?;
+        let name = if self.config.collection.is_empty() {
+            table
+        } else {
+            &self.config.collection
+        };
+        Ok(client.database(&self.config.database).collection(name))
+    }
+
+    async fn insert_document(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> DbResult<()> {
+        let mut document = doc! { "_id": key };
+        document.extend(into_document(values)?);
+        self.collection(&table)?.insert_one(document).await?;
+        Ok(())
+    }
+
+    async fn find_one(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let document = self
+            .collection(&table)?
+            .find_one(doc! { "_id": &key })
+            .projection(projection(fields))
+            .await?
+            .ok_or_else(|| not_found(&table, &key))?;
+        Ok(from_document(document))
+    }
+
+    async fn update_document(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> DbResult<()> {
+        let res = self
+            .collection(&table)?
+            .update_one(
+                doc! { "_id": &key },
+                doc! { "$set": into_document(values)? },
+            )
+            .await?;
+        if res.matched_count == 0 {
+            return Err(not_found(&table, &key));
+        }
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let mut cursor = self
+            .collection(&table)?
+            .find(doc! { "_id": { "$gte": start_key } })
+            .sort(doc! { "_id": 1 })
+            .limit(len.min(i64::MAX as usize) as i64)
+            .projection(projection(fields))
+            .await?;
+        let mut rows = Vec::with_capacity(len);
+        while cursor.advance().await? {
+            rows.push(from_document(cursor.deserialize_current()?));
+        }
+        Ok(rows)
+    }
+
+    async fn delete_document(&self, table: String, key: String) -> DbResult<()> {
+        self.collection(&table)?
+            .delete_one(doc! { "_id": key })
+            .await?;
+        Ok(())
+    }
+}
+
+impl AsyncDb for MongoDb {
+    type Config = MongoDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.connect())
+    }
+
+    fn cleanup(&self) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    fn insert(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.insert_document(table, key, values))
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Value>>> {
+        Box::pin(self.find_one(table, key, fields))
+    }
+
+    fn update(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.update_document(table, key, values))
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Vec<Value>>>> {
+        Box::pin(async move {
+            self.find(table, start_key, len, fields)
+                .await
+                .map(into_columns)
+        })
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<Vec<HashMap<String, Value>>>> {
+        Box::pin(self.find(table, start_key, len, fields))
+    }
+
+    fn delete(&self, table: String, key: String) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.delete_document(table, key))
+    }
+}
+
+impl From<Error> for DbError {
+    /// The network errors, the server selection timeouts and the errors labeled as retryable by the server are
+    /// [`DbError::Transient`].
+    fn from(error: Error) -> Self {
+        let transient = matches!(
+            *error.kind,
+            ErrorKind::Io(_)
+                | ErrorKind::ConnectionPoolCleared { .. }
+                | ErrorKind::ServerSelection { .. }
+        ) || error.contains_label(RETRYABLE_WRITE_ERROR)
+            || error.contains_label(TRANSIENT_TRANSACTION_ERROR);
+        if transient {
+            Self::Transient(error.into())
+        } else {
+            Self::Other(error.into())
+        }
+    }
+}
+
+/// The projection of the fields. It is empty for all the fields, which MongoDB takes as no projection.
+fn projection(fields: HashSet<String>) -> Document {
+    fields
+        .into_iter()
+        .map(|field| (field, Bson::Int32(1)))
+        .collect()
+}
+
+fn into_document(values: HashMap<String, Value>) -> anyhow::Result<Document> {
+    values
+        .into_iter()
+        .map(|(field, value)| {
+            let bytes = value.into_bytes()?;
+            Ok((
+                field,
+                Bson::Binary(Binary {
+                    subtype: BinarySubtype::Generic,
+                    bytes,
+                }),
+            ))
+        })
+        .collect()
+}
+
+/// Take the binary fields of the document, which skips the `_id`.
+fn from_document(document: Document) -> HashMap<String, Value> {
+    document
+        .into_iter()
+        .filter_map(|(field, value)| match value {
+            Bson::Binary(binary) => Some((field, binary.bytes.into())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn uri() -> String {
+        "mongodb://127.0.0.1:27017".to_string()
+    }
+
+    pub fn database() -> String {
+        "ycsb".to_string()
+    }
+
+    pub fn collection() -> String {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::db::DbError;
+
+    /// The connection string of the test deployment, the test is skipped if it is not set.
+    const URI_ENV: &str = "YAY_TEST_MONGODB_URI";
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string().into()))
+            .collect()
+    }
+
+    fn bytes(cells: HashMap<String, Value>) -> HashMap<String, Vec<u8>> {
+        cells
+            .into_iter()
+            .map(|(field, value)| (field, value.into_bytes().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let Ok(uri) = std::env::var(URI_ENV) else {
+            eprintln!("{URI_ENV} is not set, skipped");
+            return;
+        };
+        let db = MongoDb::new(MongoDbConfig {
+            uri,
+            database: "yay_test".to_string(),
+            collection: String::new(),
+        });
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            db.init().await.unwrap();
+            let table = format!("yay_test_{}", std::process::id());
+
+            for key in ["k2", "k1", "k3"] {
+                db.insert(
+                    table.clone(),
+                    key.to_string(),
+                    values(&[("field0", key), ("field1", "b")]),
+                )
+                .await
+                .unwrap();
+            }
+
+            let record = bytes(
+                db.read(table.clone(), "k1".to_string(), HashSet::new())
+                    .await
+                    .unwrap(),
+            );
+            assert_eq!(record.len(), 2);
+            assert_eq!(record["field0"], b"k1");
+
+            db.update(table.clone(), "k1".to_string(), values(&[("field1", "x")]))
+                .await
+                .unwrap();
+            let record = bytes(
+                db.read(
+                    table.clone(),
+                    "k1".to_string(),
+                    HashSet::from(["field1".to_string()]),
+                )
+                .await
+                .unwrap(),
+            );
+            assert_eq!(
+                record,
+                HashMap::from([("field1".to_string(), b"x".to_vec())])
+            );
+
+            let rows = db
+                .scan_rows(table.clone(), "k2".to_string(), 10, HashSet::new())
+                .await
+                .unwrap();
+            let keys = rows
+                .into_iter()
+                .map(|row| bytes(row).remove("field0").unwrap())
+                .collect_vec();
+            assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+            let columns = db
+                .scan(table.clone(), "k0".to_string(), 2, HashSet::new())
+                .await
+                .unwrap();
+            assert_eq!(columns["field0"].len(), 2);
+
+            for key in ["k1", "k2", "k3"] {
+                db.delete(table.clone(), key.to_string()).await.unwrap();
+            }
+            let e = db
+                .read(table.clone(), "k1".to_string(), HashSet::new())
+                .await
+                .unwrap_err();
+            assert!(matches!(e, DbError::NotFound(_)));
+
+            db.cleanup().await.unwrap();
+        });
+    }
+
+    #[test]
+    fn test_unreachable_server() {
+        let db = MongoDb::new(MongoDbConfig {
+            uri: "mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100".to_string(),
+            database: "yay_test".to_string(),
+            collection: String::new(),
+        });
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            db.init().await.unwrap();
+            let e = db
+                .read("t".to_string(), "k1".to_string(), HashSet::new())
+                .await
+                .unwrap_err();
+            assert!(matches!(e, DbError::Transient(_)));
+        });
+        assert!(matches!(
+            DbError::from(Error::from(std::io::ErrorKind::ConnectionReset)),
+            DbError::Transient(_)
+        ));
+    }
+}
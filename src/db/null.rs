@@ -0,0 +1,118 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{Db, DbResult};
+use crate::utils::Value;
+
+/// A [`Db`] that does nothing.
+///
+/// All operations succeed immediately without touching the values, reads return no fields and scans return no
+/// records. It is used to measure the overhead of the workload and the generators themselves.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullDb;
+
+impl NullDb {
+    /// Create a null backend.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Db for NullDb {
+    type Config = ();
+
+    fn from_config(_: Self::Config) -> DbResult<Self> {
+        Ok(Self)
+    }
+
+    fn init(&self) -> DbResult<()> {
+        Ok(())
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        Ok(())
+    }
+
+    fn insert(&self, _: String, _: String, _: HashMap<String, Value>) -> DbResult<()> {
+        Ok(())
+    }
+
+    fn read(&self, _: String, _: String, _: HashSet<String>) -> DbResult<HashMap<String, Value>> {
+        Ok(HashMap::new())
+    }
+
+    fn update(&self, _: String, _: String, _: HashMap<String, Value>) -> DbResult<()> {
+        Ok(())
+    }
+
+    fn scan(
+        &self,
+        _: String,
+        _: String,
+        _: usize,
+        _: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        Ok(HashMap::new())
+    }
+
+    fn scan_rows(
+        &self,
+        _: String,
+        _: String,
+        _: usize,
+        _: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        Ok(vec![])
+    }
+
+    fn delete(&self, _: String, _: String) -> DbResult<()> {
+        Ok(())
+    }
+
+    fn insert_batch(&self, _: String, _: Vec<(String, HashMap<String, Value>)>) -> DbResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workload::{CoreWorkload, CoreWorkloadConfig, Op, Workload};
+
+    #[test]
+    fn test_million_transactions() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(1000)
+            .fields(1)
+            .max_field_length(1)
+            .read_proportion(0.2)
+            .update_proportion(0.2)
+            .insert_proportion(0.15)
+            .scan_proportion(0.15)
+            .read_modify_write_proportion(0.15)
+            .delete_proportion(0.15)
+            .build()
+            .unwrap();
+        let w = CoreWorkload::new(config).unwrap();
+        w.load(NullDb).unwrap();
+        let mut ops = HashSet::new();
+        for _ in 0..1_000_000 {
+            ops.insert(w.transaction(NullDb).unwrap());
+        }
+        assert_eq!(ops.len(), 6);
+        assert!(!ops.contains(&Op::VerifyRead));
+    }
+}
@@ -0,0 +1,432 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use futures::{StreamExt, TryStreamExt};
+use object_store::{parse_url_opts, path::Path, ObjectStore, PutPayload};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{
+    decode, encode, into_columns, is_transient_io, not_found, project, AsyncDb, BoxFuture, DbError,
+    DbResult,
+};
+use crate::utils::Value;
+
+/// The store and the root path of the objects.
+type Store = (Arc<dyn ObjectStore>, Path);
+
+/// Configuration for the [`ObjectStoreDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ObjectStoreDbConfig {
+    /// The URL of the store and the root path of the objects, e.g. `s3://bucket/path`, `gs://bucket/path`,
+    /// `az://container/path`, `file:///tmp/path` or `memory:///`.
+    #[serde(default = "default::url")]
+    pub url: String,
+    /// The options of the store, e.g. `aws_access_key_id`, `aws_region` or `google_service_account`. The credentials
+    /// are not read from the environment, pass them here.
+    #[serde(default = "default::options")]
+    pub options: HashMap<String, String>,
+}
+
+/// An [`AsyncDb`] backed by an object store with the `object_store` crate, e.g. S3, GCS, Azure Blob Storage, the local
+/// file system or the memory.
+///
+/// Wrap it in a [`BlockingDb`](super::BlockingDb) to drive it from the client threads.
+///
+/// Each record is an object at `<root>/<table>/<key>`, and its body is the encoded fields. The layout of the fields in
+/// the body is not known without reading it, so reads always get the whole object and project the fields. Object stores
+/// can't update part of an object, so updates read the object, merge the fields and put it back, which is not atomic
+/// with the concurrent updates of the same record.
+///
+/// Scans list the objects of the table after the start key, with a request for each listed object. The order of the
+/// listed objects is up to the store, see [`Db::scan_is_ordered`](super::Db::scan_is_ordered).
+///
+/// Clones of an [`ObjectStoreDb`] share the same store, so the memory store is shared by the client threads. The store
+/// is created by the first `init()` call.
+#[derive(Debug, Clone)]
+pub struct ObjectStoreDb {
+    config: Arc<ObjectStoreDbConfig>,
+    store: Arc<Mutex<Option<Store>>>,
+}
+
+impl ObjectStoreDb {
+    /// Create an object store backend with the given config. The store is created by `init()`.
+    pub fn new(config: ObjectStoreDbConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            store: Arc::default(),
+        }
+    }
+
+    fn connect(&self) -> DbResult<()> {
+        let mut store = self.store.lock();
+        if store.is_none() {
+            let url = Url::parse(&self.config.url).map_err(anyhow::Error::from)?;
+            let (s, root) = parse_url_opts(&url, self.config.options.iter())?;
+            *store = Some((Arc::from(s), root));
+        }
+        Ok(())
+    }
+
+    fn store(&self) -> DbResult<Store> {
+        self.store
+            .lock()
+            .clone()
+            .ok_or_else(|| anyhow!("object store is not initialized").into())
+    }
+
+    /// The path of the directory of the table and the store.
+    fn table(&self, table: &str) -> DbResult<Store> {
+        let (store, root) = self.store()?;
+        Ok((store, root.child(table)))
+    }
+
+    /// Get the fields of the object, or `None` if it doesn't exist.
+    async fn get(
+        store: &dyn ObjectStore,
+        path: &Path,
+    ) -> DbResult<Option<HashMap<String, Vec<u8>>>> {
+        match store.get(path).await {
+            Ok(res) => Ok(Some(decode(&res.bytes().await?)?)),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn put(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> DbResult<()> {
+        let (store, dir) = self.table(&table)?;
+        let record = into_record(values)?;
+        store
+            .put(&dir.child(key), PutPayload::from(encode(&record)))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_fields(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let (store, dir) = self.table(&table)?;
+        let record = Self::get(store.as_ref(), &dir.child(key.as_str()))
+            .await?
+            .ok_or_else(|| not_found(&table, &key))?;
+        Ok(project(record, &fields)
+            .map(|(field, value)| (field, value.into()))
+            .collect())
+    }
+
+    async fn merge(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> DbResult<()> {
+        let (store, dir) = self.table(&table)?;
+        let path = dir.child(key.as_str());
+        let mut record = Self::get(store.as_ref(), &path)
+            .await?
+            .ok_or_else(|| not_found(&table, &key))?;
+        record.extend(into_record(values)?);
+        store.put(&path, PutPayload::from(encode(&record))).await?;
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let (store, dir) = self.table(&table)?;
+        let start = dir.child(start_key);
+        // The listing only returns the objects after the offset, get the start key on its own.
+        let mut rows = Vec::with_capacity(len);
+        if len > 0 {
+            rows.extend(Self::get(store.as_ref(), &start).await?);
+        }
+        let listed = store
+            .list_with_offset(Some(&dir), &start)
+            .take(len - rows.len())
+            .try_collect::<Vec<_>>()
+            .await?;
+        for meta in listed {
+            // The object may be deleted after it is listed.
+            rows.extend(Self::get(store.as_ref(), &meta.location).await?);
+        }
+        Ok(rows
+            .into_iter()
+            .map(|record| {
+                project(record, &fields)
+                    .map(|(field, value)| (field, value.into()))
+                    .collect()
+            })
+            .collect())
+    }
+
+    async fn remove(&self, table: String, key: String) -> DbResult<()> {
+        let (store, dir) = self.table(&table)?;
+        match store.delete(&dir.child(key)).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl AsyncDb for ObjectStoreDb {
+    type Config = ObjectStoreDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(std::future::ready(self.connect()))
+    }
+
+    fn cleanup(&self) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    fn insert(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.put(table, key, values))
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Value>>> {
+        Box::pin(self.get_fields(table, key, fields))
+    }
+
+    fn update(
+        &self,
+        table: String,
+        key: String,
+        values: HashMap<String, Value>,
+    ) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.merge(table, key, values))
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<HashMap<String, Vec<Value>>>> {
+        Box::pin(async move {
+            self.list(table, start_key, len, fields)
+                .await
+                .map(into_columns)
+        })
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> BoxFuture<'_, DbResult<Vec<HashMap<String, Value>>>> {
+        Box::pin(self.list(table, start_key, len, fields))
+    }
+
+    fn scan_is_ordered(&self) -> bool {
+        false
+    }
+
+    fn delete(&self, table: String, key: String) -> BoxFuture<'_, DbResult<()>> {
+        Box::pin(self.remove(table, key))
+    }
+}
+
+impl From<object_store::Error> for DbError {
+    /// The stores retry the requests on their own, the errors caused by the transient I/O errors, e.g. the refused
+    /// connections, are [`DbError::Transient`].
+    fn from(error: object_store::Error) -> Self {
+        let mut source = std::error::Error::source(&error);
+        while let Some(e) = source {
+            if e.downcast_ref::<std::io::Error>()
+                .map_or(false, is_transient_io)
+            {
+                return Self::Transient(error.into());
+            }
+            source = e.source();
+        }
+        Self::Other(error.into())
+    }
+}
+
+fn into_record(values: HashMap<String, Value>) -> anyhow::Result<HashMap<String, Vec<u8>>> {
+    values
+        .into_iter()
+        .map(|(field, value)| Ok((field, value.into_bytes()?)))
+        .collect()
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    use std::collections::HashMap;
+
+    pub fn url() -> String {
+        "memory:///".to_string()
+    }
+
+    pub fn options() -> HashMap<String, String> {
+        HashMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::db::{BlockingDb, Db, DbError};
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string().into()))
+            .collect()
+    }
+
+    fn bytes(cells: HashMap<String, Value>) -> HashMap<String, Vec<u8>> {
+        cells
+            .into_iter()
+            .map(|(field, value)| (field, value.into_bytes().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let db = BlockingDb::new(
+            ObjectStoreDb::new(ObjectStoreDbConfig {
+                url: "memory:///".to_string(),
+                options: HashMap::new(),
+            }),
+            runtime.handle().clone(),
+        );
+        db.init().unwrap();
+
+        for key in ["k2", "k1", "k3"] {
+            db.insert(
+                "t".to_string(),
+                key.to_string(),
+                values(&[("field0", key), ("field1", "b")]),
+            )
+            .unwrap();
+        }
+
+        let record = bytes(
+            db.read("t".to_string(), "k1".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record.len(), 2);
+        assert_eq!(record["field0"], b"k1");
+
+        // The update merges the fields into the object.
+        db.update(
+            "t".to_string(),
+            "k1".to_string(),
+            values(&[("field1", "x")]),
+        )
+        .unwrap();
+        let record = bytes(
+            db.read("t".to_string(), "k1".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record["field0"], b"k1");
+        assert_eq!(record["field1"], b"x");
+        let e = db
+            .update(
+                "t".to_string(),
+                "k4".to_string(),
+                values(&[("field1", "x")]),
+            )
+            .unwrap_err();
+        assert!(matches!(e, DbError::NotFound(_)));
+
+        // The scan includes the start key, and the listing order is up to the store.
+        let rows = db
+            .scan_rows(
+                "t".to_string(),
+                "k2".to_string(),
+                10,
+                HashSet::from(["field0".to_string()]),
+            )
+            .unwrap();
+        let keys = rows
+            .into_iter()
+            .map(|row| bytes(row).remove("field0").unwrap())
+            .sorted()
+            .collect_vec();
+        assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+        let columns = db
+            .scan("t".to_string(), "k1".to_string(), 2, HashSet::new())
+            .unwrap();
+        assert_eq!(columns["field0"].len(), 2);
+
+        db.delete("t".to_string(), "k1".to_string()).unwrap();
+        db.delete("t".to_string(), "k1".to_string()).unwrap();
+        let e = db
+            .read("t".to_string(), "k1".to_string(), HashSet::new())
+            .unwrap_err();
+        assert!(matches!(e, DbError::NotFound(_)));
+
+        db.cleanup().unwrap();
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let error = |kind: std::io::ErrorKind| object_store::Error::Generic {
+            store: "test",
+            source: Box::new(std::io::Error::from(kind)),
+        };
+        assert!(matches!(
+            DbError::from(error(std::io::ErrorKind::ConnectionRefused)),
+            DbError::Transient(_)
+        ));
+        assert!(matches!(
+            DbError::from(error(std::io::ErrorKind::PermissionDenied)),
+            DbError::Other(_)
+        ));
+    }
+}
@@ -0,0 +1,514 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use itertools::Itertools;
+use parking_lot::Mutex;
+use postgres::{error::SqlState, types::ToSql, Client, NoTls, Row, Statement};
+use serde::{Deserialize, Serialize};
+
+use super::{into_columns, is_transient_io, not_found, Db, DbError, DbResult};
+use crate::utils::Value;
+
+/// The name of the key column.
+const KEY_COLUMN: &str = "ycsb_key";
+
+/// Configuration for the [`PostgresDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PostgresDbConfig {
+    /// The connection string, e.g. `host=localhost user=postgres dbname=ycsb`, see [`postgres::Config`].
+    pub url: String,
+    /// The number of fields in a record, which must match the workload.
+    #[serde(default = "default::fields")]
+    pub fields: usize,
+    /// The prefix of the field names, which must match the workload.
+    #[serde(default = "default::field_name_prefix")]
+    pub field_name_prefix: String,
+    /// The max number of idle connections kept in the pool.
+    #[serde(default = "default::pool_size")]
+    pub pool_size: usize,
+}
+
+/// A pooled connection with its prepared statements.
+struct Connection {
+    client: Client,
+    /// The prepared statements of the connection, keyed by their SQL.
+    statements: HashMap<String, Statement>,
+}
+
+impl Connection {
+    /// Prepare the statement of `sql`, or take it from the cache of the connection.
+    fn prepare(&mut self, sql: &str) -> DbResult<Statement> {
+        if let Some(statement) = self.statements.get(sql) {
+            return Ok(statement.clone());
+        }
+        let statement = self.client.prepare(sql)?;
+        self.statements.insert(sql.to_string(), statement.clone());
+        Ok(statement)
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    /// The idle connections.
+    pool: Mutex<Vec<Connection>>,
+    /// The tables that are known to exist.
+    tables: Mutex<HashSet<String>>,
+}
+
+/// A [`Db`] backed by a PostgreSQL server.
+///
+/// Each table has a `ycsb_key TEXT PRIMARY KEY` column and a `BYTEA` column for each field, the schema is derived from
+/// the configured field count and field name prefix. The tables are created on first use.
+///
+/// The operations take a connection from a pool, which opens a new connection if there is no idle one, and keeps at
+/// most `pool_size` idle connections. The statements are prepared once per connection. Clones of an instance share its
+/// pool, so create one instance with [`PostgresDb::new`] and give each client thread a clone.
+#[derive(Clone)]
+pub struct PostgresDb {
+    config: Arc<PostgresDbConfig>,
+    columns: Arc<Vec<String>>,
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for PostgresDb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresDb")
+            .field("config", &self.config)
+            .field("idle_connections", &self.inner.pool.lock().len())
+            .finish()
+    }
+}
+
+impl PostgresDb {
+    /// Create a PostgreSQL backend with the given config. The connections are opened on demand.
+    pub fn new(config: PostgresDbConfig) -> Self {
+        let columns = (0..config.fields)
+            .map(|i| format!("{prefix}{i}", prefix = config.field_name_prefix))
+            .collect_vec();
+        Self {
+            config: Arc::new(config),
+            columns: Arc::new(columns),
+            inner: Arc::default(),
+        }
+    }
+
+    /// Take an idle connection from the pool, or open a new one.
+    fn connect(&self) -> DbResult<Connection> {
+        if let Some(conn) = self.inner.pool.lock().pop() {
+            return Ok(conn);
+        }
+        Ok(Connection {
+            client: Client::connect(&self.config.url, NoTls)?,
+            statements: HashMap::new(),
+        })
+    }
+
+    /// Return the connection to the pool, unless the pool is full or the connection is broken.
+    fn release(&self, conn: Connection) {
+        let mut pool = self.inner.pool.lock();
+        if pool.len() < self.config.pool_size && !conn.client.is_closed() {
+            pool.push(conn);
+        }
+    }
+
+    /// Run `f` with a pooled connection, after creating `table` if it is not known to exist.
+    fn with_conn<T>(
+        &self,
+        table: &str,
+        f: impl FnOnce(&mut Connection) -> DbResult<T>,
+    ) -> DbResult<T> {
+        let mut conn = self.connect()?;
+        let res = self
+            .create_table(&mut conn, table)
+            .and_then(|()| f(&mut conn));
+        self.release(conn);
+        res
+    }
+
+    fn create_table(&self, conn: &mut Connection, table: &str) -> DbResult<()> {
+        if self.inner.tables.lock().contains(table) {
+            return Ok(());
+        }
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| format!(", {} BYTEA", quote(column)))
+            .join("");
+        conn.client.batch_execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} ({KEY_COLUMN} TEXT PRIMARY KEY{columns})",
+            quote(table)
+        ))?;
+        self.inner.tables.lock().insert(table.to_string());
+        Ok(())
+    }
+
+    /// The columns to select, all the field columns if `fields` is empty.
+    fn projection(&self, fields: HashSet<String>) -> Vec<String> {
+        if fields.is_empty() {
+            self.columns.to_vec()
+        } else {
+            fields.into_iter().collect()
+        }
+    }
+}
+
+impl Db for PostgresDb {
+    type Config = PostgresDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> DbResult<()> {
+        // Open a connection to fail early if the server is unreachable.
+        let conn = self.connect()?;
+        self.release(conn);
+        Ok(())
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        self.inner.pool.lock().clear();
+        Ok(())
+    }
+
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        let (sql, values) = insert(&table, values)?;
+        self.with_conn(&table, |conn| {
+            let statement = conn.prepare(&sql)?;
+            conn.client.execute(&statement, &params(&key, &values))?;
+            Ok(())
+        })
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let columns = self.projection(fields);
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {KEY_COLUMN} = $1",
+            columns.iter().map(|column| quote(column)).join(", "),
+            quote(&table)
+        );
+        let row = self.with_conn(&table, |conn| {
+            let statement = conn.prepare(&sql)?;
+            Ok(conn.client.query_opt(&statement, &[&key])?)
+        })?;
+        let row = row.ok_or_else(|| not_found(&table, &key))?;
+        take_row(&row, &columns)
+    }
+
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        let (fields, values) = unzip(values)?;
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {KEY_COLUMN} = $1",
+            quote(&table),
+            fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| format!("{} = ${}", quote(field), i + 2))
+                .join(", ")
+        );
+        self.with_conn(&table, |conn| {
+            let statement = conn.prepare(&sql)?;
+            conn.client.execute(&statement, &params(&key, &values))?;
+            Ok(())
+        })
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        self.scan_rows(table, start_key, len, fields)
+            .map(into_columns)
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let columns = self.projection(fields);
+        let sql = format!(
+            "SELECT {} FROM {} WHERE {KEY_COLUMN} >= $1 ORDER BY {KEY_COLUMN} LIMIT $2",
+            columns.iter().map(|column| quote(column)).join(", "),
+            quote(&table)
+        );
+        let len = len.min(i64::MAX as usize) as i64;
+        let rows = self.with_conn(&table, |conn| {
+            let statement = conn.prepare(&sql)?;
+            Ok(conn.client.query(&statement, &[&start_key, &len])?)
+        })?;
+        rows.iter().map(|row| take_row(row, &columns)).collect()
+    }
+
+    fn delete(&self, table: String, key: String) -> DbResult<()> {
+        let sql = format!("DELETE FROM {} WHERE {KEY_COLUMN} = $1", quote(&table));
+        self.with_conn(&table, |conn| {
+            let statement = conn.prepare(&sql)?;
+            conn.client.execute(&statement, &[&key])?;
+            Ok(())
+        })
+    }
+
+    fn insert_batch(
+        &self,
+        table: String,
+        records: Vec<(String, HashMap<String, Value>)>,
+    ) -> DbResult<()> {
+        let records = records
+            .into_iter()
+            .map(|(key, values)| insert(&table, values).map(|(sql, values)| (sql, key, values)))
+            .collect::<DbResult<Vec<_>>>()?;
+        self.with_conn(&table, |conn| {
+            // Prepare the statements before the transaction borrows the connection.
+            let statements = records
+                .iter()
+                .map(|(sql, _, _)| conn.prepare(sql))
+                .collect::<DbResult<Vec<_>>>()?;
+            let mut txn = conn.client.transaction()?;
+            for (statement, (_, key, values)) in statements.iter().zip_eq(&records) {
+                txn.execute(statement, &params(key, values))?;
+            }
+            txn.commit()?;
+            Ok(())
+        })
+    }
+}
+
+/// Build the upsert statement of the fields of `values`, and return it with the values in the order of its
+/// parameters.
+fn insert(table: &str, values: HashMap<String, Value>) -> DbResult<(String, Vec<Vec<u8>>)> {
+    let (fields, values) = unzip(values)?;
+    let columns = fields
+        .iter()
+        .map(|field| format!(", {}", quote(field)))
+        .join("");
+    let placeholders = (0..fields.len()).map(|i| format!(", ${}", i + 2)).join("");
+    let conflict = if fields.is_empty() {
+        "NOTHING".to_string()
+    } else {
+        format!(
+            "UPDATE SET {}",
+            fields
+                .iter()
+                .map(|field| format!("{0} = EXCLUDED.{0}", quote(field)))
+                .join(", ")
+        )
+    };
+    let sql = format!(
+        "INSERT INTO {} ({KEY_COLUMN}{columns}) VALUES ($1{placeholders}) ON CONFLICT ({KEY_COLUMN}) DO {conflict}",
+        quote(table)
+    );
+    Ok((sql, values))
+}
+
+/// Split the field/value pairs into the fields and the bytes of the values.
+fn unzip(values: HashMap<String, Value>) -> DbResult<(Vec<String>, Vec<Vec<u8>>)> {
+    Ok(values
+        .into_iter()
+        .map(|(field, value)| Ok((field, value.into_bytes()?)))
+        .collect::<DbResult<Vec<_>>>()?
+        .into_iter()
+        .unzip())
+}
+
+/// The parameters of a statement, the key followed by the values.
+fn params<'a>(key: &'a String, values: &'a [Vec<u8>]) -> Vec<&'a (dyn ToSql + Sync)> {
+    std::iter::once(key as &(dyn ToSql + Sync))
+        .chain(values.iter().map(|value| value as &(dyn ToSql + Sync)))
+        .collect()
+}
+
+/// Take the non-null columns of the row.
+fn take_row(row: &Row, columns: &[String]) -> DbResult<HashMap<String, Value>> {
+    let mut record = HashMap::with_capacity(columns.len());
+    for (i, column) in columns.iter().enumerate() {
+        if let Some(value) = row.try_get::<_, Option<Vec<u8>>>(i)? {
+            record.insert(column.clone(), value.into());
+        }
+    }
+    Ok(record)
+}
+
+impl From<postgres::Error> for DbError {
+    /// Classify the serialization failures and the deadlocks as [`DbError::Conflict`], and the lost connections, the
+    /// connection exceptions, the overloaded or restarting server, the lock and statement timeouts as
+    /// [`DbError::Transient`].
+    fn from(error: postgres::Error) -> Self {
+        if let Some(code) = error.code() {
+            return match code {
+                &SqlState::T_R_SERIALIZATION_FAILURE | &SqlState::T_R_DEADLOCK_DETECTED => {
+                    Self::Conflict(error.into())
+                }
+                &SqlState::TOO_MANY_CONNECTIONS
+                | &SqlState::LOCK_NOT_AVAILABLE
+                | &SqlState::QUERY_CANCELED
+                | &SqlState::ADMIN_SHUTDOWN
+                | &SqlState::CANNOT_CONNECT_NOW => Self::Transient(error.into()),
+                // Class 08, the connection exceptions.
+                code if code.code().starts_with("08") => Self::Transient(error.into()),
+                _ => Self::Other(error.into()),
+            };
+        }
+        let io = std::error::Error::source(&error)
+            .and_then(|source| source.downcast_ref::<std::io::Error>())
+            .map_or(false, is_transient_io);
+        if error.is_closed() || io {
+            Self::Transient(error.into())
+        } else {
+            Self::Other(error.into())
+        }
+    }
+}
+
+/// Quote an identifier.
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn fields() -> usize {
+        10
+    }
+
+    pub fn field_name_prefix() -> String {
+        "field".to_string()
+    }
+
+    pub fn pool_size() -> usize {
+        16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbError;
+
+    /// The connection string of the test database, the test is skipped if it is not set.
+    const URL_ENV: &str = "YAY_TEST_POSTGRES_URL";
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string().into()))
+            .collect()
+    }
+
+    fn bytes(cells: HashMap<String, Value>) -> HashMap<String, Vec<u8>> {
+        cells
+            .into_iter()
+            .map(|(field, value)| (field, value.into_bytes().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let Ok(url) = std::env::var(URL_ENV) else {
+            eprintln!("{URL_ENV} is not set, skipped");
+            return;
+        };
+        let db = PostgresDb::from_config(PostgresDbConfig {
+            url,
+            fields: 2,
+            field_name_prefix: "field".to_string(),
+            pool_size: 2,
+        })
+        .unwrap();
+        db.init().unwrap();
+        let table = format!("yay_test_{}", std::process::id());
+
+        for key in ["k2", "k1", "k3"] {
+            db.insert(
+                table.clone(),
+                key.to_string(),
+                values(&[("field0", key), ("field1", "b")]),
+            )
+            .unwrap();
+        }
+
+        let record = bytes(
+            db.read(table.clone(), "k1".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record.len(), 2);
+        assert_eq!(record["field0"], b"k1");
+
+        db.update(table.clone(), "k1".to_string(), values(&[("field1", "x")]))
+            .unwrap();
+        let record = bytes(
+            db.read(
+                table.clone(),
+                "k1".to_string(),
+                HashSet::from(["field1".to_string()]),
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            record,
+            HashMap::from([("field1".to_string(), b"x".to_vec())])
+        );
+
+        let rows = db
+            .scan_rows(table.clone(), "k2".to_string(), 10, HashSet::new())
+            .unwrap();
+        let keys = rows
+            .into_iter()
+            .map(|row| bytes(row).remove("field0").unwrap())
+            .collect_vec();
+        assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+
+        for key in ["k1", "k2", "k3"] {
+            db.delete(table.clone(), key.to_string()).unwrap();
+        }
+        let e = db
+            .read(table.clone(), "k1".to_string(), HashSet::new())
+            .unwrap_err();
+        assert!(matches!(e, DbError::NotFound(_)));
+
+        db.cleanup().unwrap();
+    }
+
+    #[test]
+    fn test_unreachable_server() {
+        // Nothing listens on the port, so the connection is refused.
+        let db = PostgresDb::new(PostgresDbConfig {
+            url: "host=127.0.0.1 port=1 user=postgres connect_timeout=1".to_string(),
+            fields: 2,
+            field_name_prefix: "field".to_string(),
+            pool_size: 2,
+        });
+        let e = db.init().unwrap_err();
+        assert!(matches!(e, DbError::Transient(_)), "{e:?}");
+    }
+}
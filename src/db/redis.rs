@@ -0,0 +1,360 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use ::redis::{cmd, pipe, Client, Connection, ErrorKind, Pipeline, RedisError};
+use anyhow::anyhow;
+use itertools::Itertools;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use super::{into_columns, not_found, Db, DbError, DbResult};
+use crate::utils::Value;
+
+/// Configuration for the [`RedisDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedisDbConfig {
+    /// The connection URL of the Redis server, e.g. `redis://127.0.0.1:6379/0`.
+    #[serde(default = "default::url")]
+    pub url: String,
+    /// The number of connections shared by the clones of the db.
+    #[serde(default = "default::pool_size")]
+    pub pool_size: usize,
+}
+
+/// A [`Db`] backed by a Redis server.
+///
+/// Each record is stored as a Redis hash keyed by `{table}:{key}`, with a hash field for each record field.
+///
+/// Redis has no ordered iteration over keys, so the keys of each table are also indexed in a sorted set named after the
+/// table, with the same score for all members. Scans walk the index in lexicographical order with `ZRANGEBYLEX`, then
+/// fetch the records in a pipeline. The record and its index entry are written and deleted in a transaction.
+///
+/// Clones of a [`RedisDb`] share the same connection pool, so clone one instance for each client thread. The pool is
+/// connected by the first `init()` call and disconnected by the last `cleanup()` call.
+#[derive(Clone)]
+pub struct RedisDb {
+    config: RedisDbConfig,
+    pool: Arc<RwLock<Vec<Mutex<Connection>>>>,
+    next: Arc<AtomicUsize>,
+    refs: Arc<AtomicUsize>,
+}
+
+impl std::fmt::Debug for RedisDb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisDb")
+            .field("config", &self.config)
+            .field("connections", &self.pool.read().len())
+            .finish()
+    }
+}
+
+impl RedisDb {
+    /// Create a Redis backend with the given config.
+    pub fn new(config: RedisDbConfig) -> Self {
+        Self {
+            config,
+            pool: Arc::new(RwLock::new(vec![])),
+            next: Arc::new(AtomicUsize::new(0)),
+            refs: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Run `f` with one of the pooled connections, the connections are picked in a round-robin manner.
+    fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> DbResult<T>) -> DbResult<T> {
+        let pool = self.pool.read();
+        if pool.is_empty() {
+            return Err(anyhow!("redis is not initialized").into());
+        }
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % pool.len();
+        let mut conn = pool[index].lock();
+        f(&mut conn)
+    }
+
+    /// Queue the commands to write the fields of a record and index its key.
+    fn put(
+        pipe: &mut Pipeline,
+        table: &str,
+        key: &str,
+        values: HashMap<String, Value>,
+    ) -> DbResult<()> {
+        let values = values
+            .into_iter()
+            .map(|(field, value)| Ok((field, value.into_bytes()?)))
+            .collect::<DbResult<Vec<_>>>()?;
+        // `HSET` requires at least one field.
+        if !values.is_empty() {
+            pipe.hset_multiple(record_key(table, key), &values).ignore();
+        }
+        pipe.zadd(table, key, 0).ignore();
+        Ok(())
+    }
+
+    /// Queue the command to read the given fields of a record, or all of them if `fields` is empty.
+    fn get(pipe: &mut Pipeline, table: &str, key: &str, fields: &[&String]) {
+        if fields.is_empty() {
+            pipe.hgetall(record_key(table, key));
+        } else {
+            pipe.cmd("HMGET").arg(record_key(table, key)).arg(fields);
+        }
+    }
+}
+
+impl Db for RedisDb {
+    type Config = RedisDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> DbResult<()> {
+        let mut pool = self.pool.write();
+        if pool.is_empty() {
+            let client = Client::open(self.config.url.as_str())?;
+            *pool = (0..self.config.pool_size.max(1))
+                .map(|_| Ok(Mutex::new(client.get_connection()?)))
+                .collect::<DbResult<_>>()?;
+        }
+        self.refs.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        let mut pool = self.pool.write();
+        if self.refs.fetch_sub(1, Ordering::SeqCst) == 1 {
+            pool.clear();
+        }
+        Ok(())
+    }
+
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        let mut pipe = pipe();
+        pipe.atomic();
+        Self::put(&mut pipe, &table, &key, values)?;
+        self.with_conn(|conn| Ok(pipe.query(conn)?))
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let fields = fields.iter().collect_vec();
+        let mut pipe = pipe();
+        Self::get(&mut pipe, &table, &key, &fields);
+        let (reply,): (Reply,) = self.with_conn(|conn| Ok(pipe.query(conn)?))?;
+        Ok(into_record(reply, &fields)
+            .ok_or_else(|| not_found(&table, &key))?
+            .into_iter()
+            .map(|(field, value)| (field, value.into()))
+            .collect())
+    }
+
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        self.insert(table, key, values)
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        self.scan_rows(table, start_key, len, fields)
+            .map(into_columns)
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let fields = fields.iter().collect_vec();
+        self.with_conn(|conn| {
+            let keys: Vec<String> = cmd("ZRANGEBYLEX")
+                .arg(&table)
+                .arg(format!("[{start_key}"))
+                .arg("+")
+                .arg("LIMIT")
+                .arg(0)
+                .arg(len)
+                .query(conn)?;
+            if keys.is_empty() {
+                return Ok(vec![]);
+            }
+
+            let mut pipe = pipe();
+            for key in &keys {
+                Self::get(&mut pipe, &table, key, &fields);
+            }
+            let replies: Vec<Reply> = pipe.query(conn)?;
+
+            // A record deleted after its key is read from the index is skipped.
+            let result = replies
+                .into_iter()
+                .filter_map(|reply| into_record(reply, &fields))
+                .map(|record| {
+                    record
+                        .into_iter()
+                        .map(|(field, value)| (field, value.into()))
+                        .collect()
+                })
+                .collect();
+            Ok(result)
+        })
+    }
+
+    fn delete(&self, table: String, key: String) -> DbResult<()> {
+        let mut pipe = pipe();
+        pipe.atomic()
+            .del(record_key(&table, &key))
+            .ignore()
+            .zrem(&table, &key)
+            .ignore();
+        self.with_conn(|conn| Ok(pipe.query(conn)?))
+    }
+
+    fn insert_batch(
+        &self,
+        table: String,
+        records: Vec<(String, HashMap<String, Value>)>,
+    ) -> DbResult<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let mut pipe = pipe();
+        for (key, values) in records {
+            Self::put(&mut pipe, &table, &key, values)?;
+        }
+        self.with_conn(|conn| Ok(pipe.query(conn)?))
+    }
+
+    fn read_batch(
+        &self,
+        table: String,
+        keys: Vec<String>,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        let fields = fields.iter().collect_vec();
+        let mut pipe = pipe();
+        for key in &keys {
+            Self::get(&mut pipe, &table, key, &fields);
+        }
+        let replies: Vec<Reply> = self.with_conn(|conn| Ok(pipe.query(conn)?))?;
+        replies
+            .into_iter()
+            .zip_eq(keys)
+            .map(|(reply, key)| {
+                Ok(into_record(reply, &fields)
+                    .ok_or_else(|| not_found(&table, &key))?
+                    .into_iter()
+                    .map(|(field, value)| (field, value.into()))
+                    .collect())
+            })
+            .collect()
+    }
+}
+
+/// The reply of `HGETALL` or `HMGET`.
+///
+/// The replies are both flat arrays, `HGETALL` replies the field/value pairs, while `HMGET` replies the values of the
+/// requested fields in order.
+type Reply = Vec<Option<Vec<u8>>>;
+
+/// Pair the values of the reply with the fields, or `None` if the record doesn't exist.
+fn into_record(reply: Reply, fields: &[&String]) -> Option<HashMap<String, Vec<u8>>> {
+    let record: HashMap<String, Vec<u8>> = if fields.is_empty() {
+        reply
+            .into_iter()
+            .tuples()
+            .filter_map(|(field, value)| Some((String::from_utf8(field?).ok()?, value?)))
+            .collect()
+    } else {
+        fields
+            .iter()
+            .zip(reply)
+            .filter_map(|(field, value)| Some(((*field).clone(), value?)))
+            .collect()
+    };
+    if record.is_empty() {
+        return None;
+    }
+    Some(record)
+}
+
+impl From<RedisError> for DbError {
+    /// Classify the I/O errors, e.g. the timeouts and the dropped connections, and the errors of a server that is
+    /// loading its dataset or a cluster that is failing over as [`DbError::Transient`].
+    fn from(error: RedisError) -> Self {
+        match error.kind() {
+            ErrorKind::IoError
+            | ErrorKind::BusyLoadingError
+            | ErrorKind::TryAgain
+            | ErrorKind::ClusterDown
+            | ErrorKind::MasterDown => Self::Transient(error.into()),
+            _ => Self::Other(error.into()),
+        }
+    }
+}
+
+fn record_key(table: &str, key: &str) -> String {
+    format!("{table}:{key}")
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn url() -> String {
+        "redis://127.0.0.1:6379/".to_string()
+    }
+
+    pub fn pool_size() -> usize {
+        8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unreachable_server() {
+        // Nothing listens on the port, so the connection is refused.
+        let db = RedisDb::new(RedisDbConfig {
+            url: "redis://127.0.0.1:1/".to_string(),
+            pool_size: 1,
+        });
+        let e = db.init().unwrap_err();
+        assert!(matches!(e, DbError::Transient(_)), "{e:?}");
+
+        let e = RedisError::from((ErrorKind::BusyLoadingError, "loading"));
+        assert!(matches!(DbError::from(e), DbError::Transient(_)));
+        let e = RedisError::from((ErrorKind::TypeError, "wrong type"));
+        assert!(matches!(DbError::from(e), DbError::Other(_)));
+    }
+}
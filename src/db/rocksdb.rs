@@ -0,0 +1,370 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use ::rocksdb::{
+    BlockBasedOptions, Cache, Direction, Error, ErrorKind, IteratorMode, MergeOperands, Options, DB,
+};
+use anyhow::anyhow;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use super::{decode, encode, into_columns, not_found, project, Db, DbError, DbResult};
+use crate::utils::Value;
+
+/// Configuration for the [`RocksDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RocksDbConfig {
+    /// The data directory of the database.
+    pub path: PathBuf,
+    /// Enable the block cache or not.
+    #[serde(default = "default::block_cache")]
+    pub block_cache: bool,
+    /// Capacity of the block cache, in bytes.
+    ///
+    /// Only used if the block cache is enabled.
+    #[serde(default = "default::block_cache_capacity")]
+    pub block_cache_capacity: usize,
+}
+
+/// A [`Db`] backed by the embedded RocksDB engine.
+///
+/// Each record is stored as a single RocksDB entry keyed by `{table}:{key}`, with all its field/value pairs encoded
+/// into a length-prefixed blob. Updates write the new fields as merge operands, which the merge operator merges into the
+/// record, so the concurrent updates of the same record don't lose each other's fields.
+///
+/// Clones of a [`RocksDb`] share the same underlying database, so clone one instance for each client thread. The
+/// database is opened by the first `init()` call and flushed and closed by the last `cleanup()` call.
+#[derive(Debug, Clone)]
+pub struct RocksDb {
+    config: RocksDbConfig,
+    db: Arc<RwLock<Option<DB>>>,
+    refs: Arc<AtomicUsize>,
+}
+
+impl RocksDb {
+    /// Create a RocksDB backend with the given config.
+    pub fn new(config: RocksDbConfig) -> Self {
+        Self {
+            config,
+            db: Arc::new(RwLock::new(None)),
+            refs: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    fn options(&self) -> Options {
+        let mut block_opts = BlockBasedOptions::default();
+        if self.config.block_cache {
+            let cache = Cache::new_lru_cache(self.config.block_cache_capacity);
+            block_opts.set_block_cache(&cache);
+        } else {
+            block_opts.disable_cache();
+        }
+
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.set_block_based_table_factory(&block_opts);
+        opts.set_merge_operator_associative("merge_fields", merge_fields);
+        opts
+    }
+
+    fn with_db<T>(&self, f: impl FnOnce(&DB) -> DbResult<T>) -> DbResult<T> {
+        let db = self.db.read();
+        match db.as_ref() {
+            Some(db) => f(db),
+            None => Err(anyhow!("rocksdb is not initialized").into()),
+        }
+    }
+
+    fn get(&self, db: &DB, key: &[u8]) -> DbResult<Option<HashMap<String, Vec<u8>>>> {
+        Ok(db.get(key)?.map(|buf| decode(&buf)).transpose()?)
+    }
+}
+
+impl Db for RocksDb {
+    type Config = RocksDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> DbResult<()> {
+        let mut db = self.db.write();
+        if db.is_none() {
+            *db = Some(DB::open(&self.options(), &self.config.path)?);
+        }
+        self.refs.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        let mut db = self.db.write();
+        // A clone that is not initialized holds no reference.
+        let refs = self
+            .refs
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |refs| {
+                refs.checked_sub(1)
+            });
+        if refs == Ok(1) {
+            if let Some(db) = db.take() {
+                db.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        let record = values
+            .into_iter()
+            .map(|(field, value)| Ok((field, value.into_bytes()?)))
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+        self.with_db(|db| Ok(db.put(record_key(&table, &key), encode(&record))?))
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let record = self
+            .with_db(|db| self.get(db, &record_key(&table, &key)))?
+            .ok_or_else(|| not_found(&table, &key))?;
+        Ok(project(record, &fields)
+            .map(|(field, value)| (field, value.into()))
+            .collect())
+    }
+
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        let record = values
+            .into_iter()
+            .map(|(field, value)| Ok((field, value.into_bytes()?)))
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+        self.with_db(|db| Ok(db.merge(record_key(&table, &key), encode(&record))?))
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        self.scan_rows(table, start_key, len, fields)
+            .map(into_columns)
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let prefix = record_key(&table, "");
+        let start = record_key(&table, &start_key);
+        self.with_db(|db| {
+            let mut result = vec![];
+            let iter = db.iterator(IteratorMode::From(&start, Direction::Forward));
+            for item in iter.take(len) {
+                let (key, buf) = item?;
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                let record = project(decode(&buf)?, &fields)
+                    .map(|(field, value)| (field, value.into()))
+                    .collect();
+                result.push(record);
+            }
+            Ok(result)
+        })
+    }
+
+    fn delete(&self, table: String, key: String) -> DbResult<()> {
+        self.with_db(|db| Ok(db.delete(record_key(&table, &key))?))
+    }
+}
+
+impl From<Error> for DbError {
+    /// The timeouts and the busy or incomplete operations, which may succeed if they are retried, are
+    /// [`DbError::Transient`].
+    fn from(error: Error) -> Self {
+        match error.kind() {
+            ErrorKind::TimedOut | ErrorKind::Busy | ErrorKind::TryAgain | ErrorKind::Incomplete => {
+                Self::Transient(error.into())
+            }
+            _ => Self::Other(error.into()),
+        }
+    }
+}
+
+/// Merge the fields of the operands into the record in order, or into each other for a partial merge. A record that
+/// doesn't exist is created.
+fn merge_fields(_: &[u8], record: Option<&[u8]>, operands: &MergeOperands) -> Option<Vec<u8>> {
+    let mut record = record.map(decode).transpose().ok()?.unwrap_or_default();
+    for operand in operands {
+        record.extend(decode(operand).ok()?);
+    }
+    Some(encode(&record))
+}
+
+fn record_key(table: &str, key: &str) -> Vec<u8> {
+    format!("{table}:{key}").into_bytes()
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn block_cache() -> bool {
+        true
+    }
+
+    pub fn block_cache_capacity() -> usize {
+        8 << 20
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string().into()))
+            .collect()
+    }
+
+    fn bytes(cells: HashMap<String, Value>) -> HashMap<String, Vec<u8>> {
+        cells
+            .into_iter()
+            .map(|(field, value)| (field, value.into_bytes().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("yay-rocksdb-{}", std::process::id()));
+        let db = RocksDb::from_config(RocksDbConfig {
+            path: path.clone(),
+            block_cache: default::block_cache(),
+            block_cache_capacity: default::block_cache_capacity(),
+        })
+        .unwrap();
+        // The cleanup of a clone that is not initialized is a no-op.
+        db.clone().cleanup().unwrap();
+        db.init().unwrap();
+
+        for key in ["k2", "k1", "k3"] {
+            db.insert(
+                "t".to_string(),
+                key.to_string(),
+                values(&[("field0", key), ("field1", "b")]),
+            )
+            .unwrap();
+        }
+        // A record of the next table, which the scans of `t` must not reach.
+        db.insert(
+            "u".to_string(),
+            "k0".to_string(),
+            values(&[("field0", "u")]),
+        )
+        .unwrap();
+
+        let record = bytes(
+            db.read("t".to_string(), "k1".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record.len(), 2);
+        assert_eq!(record["field0"], b"k1");
+        assert_eq!(record["field1"], b"b");
+
+        db.update(
+            "t".to_string(),
+            "k1".to_string(),
+            values(&[("field1", "x")]),
+        )
+        .unwrap();
+        let record = bytes(
+            db.read(
+                "t".to_string(),
+                "k1".to_string(),
+                HashSet::from(["field1".to_string()]),
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            record,
+            HashMap::from([("field1".to_string(), b"x".to_vec())])
+        );
+
+        // The concurrent updates of different fields of the same record are all kept.
+        std::thread::scope(|s| {
+            for i in 0..8 {
+                let db = db.clone();
+                s.spawn(move || {
+                    let field = format!("field{}", i + 2);
+                    db.update(
+                        "t".to_string(),
+                        "k1".to_string(),
+                        values(&[(field.as_str(), "y")]),
+                    )
+                    .unwrap();
+                });
+            }
+        });
+        let record = bytes(
+            db.read("t".to_string(), "k1".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record.len(), 10);
+        assert_eq!(record["field0"], b"k1");
+        assert_eq!(record["field1"], b"x");
+
+        let rows = db
+            .scan_rows("t".to_string(), "k2".to_string(), 10, HashSet::new())
+            .unwrap();
+        let keys = rows
+            .into_iter()
+            .map(|row| bytes(row).remove("field0").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+
+        db.delete("t".to_string(), "k1".to_string()).unwrap();
+        let e = db
+            .read("t".to_string(), "k1".to_string(), HashSet::new())
+            .unwrap_err();
+        assert!(matches!(e, DbError::NotFound(_)));
+
+        // The records are flushed by the last cleanup, and found by the next phase.
+        db.cleanup().unwrap();
+        db.init().unwrap();
+        let record = bytes(
+            db.read("t".to_string(), "k2".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record["field0"], b"k2");
+        db.cleanup().unwrap();
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,313 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use ::sled::Tree;
+use anyhow::anyhow;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    decode, encode, into_columns, is_transient_io, not_found, project, Db, DbError, DbResult,
+};
+use crate::utils::Value;
+
+/// Configuration for the [`SledDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SledDbConfig {
+    /// The data directory of the database.
+    pub path: PathBuf,
+    /// Capacity of the page cache, in bytes.
+    #[serde(default = "default::cache_capacity")]
+    pub cache_capacity: u64,
+}
+
+/// A [`Db`] backed by the embedded sled engine.
+///
+/// Each table is a sled tree, and each record is stored as a single entry of the tree keyed by the record key, with all
+/// its field/value pairs encoded into a length-prefixed blob.
+///
+/// Clones of a [`SledDb`] share the same underlying database, so clone one instance for each client thread. The
+/// database is opened by the first `init()` call, flushed by the last `cleanup()` call, and closed when the last clone
+/// is dropped.
+#[derive(Debug, Clone)]
+pub struct SledDb {
+    config: SledDbConfig,
+    db: Arc<RwLock<Option<::sled::Db>>>,
+    refs: Arc<AtomicUsize>,
+}
+
+impl SledDb {
+    /// Create a sled backend with the given config.
+    pub fn new(config: SledDbConfig) -> Self {
+        Self {
+            config,
+            db: Arc::new(RwLock::new(None)),
+            refs: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Run `f` with the tree of `table`, which is created if it doesn't exist.
+    fn with_tree<T>(&self, table: &str, f: impl FnOnce(&Tree) -> DbResult<T>) -> DbResult<T> {
+        let db = self.db.read();
+        match db.as_ref() {
+            Some(db) => f(&db.open_tree(table)?),
+            None => Err(anyhow!("sled is not initialized").into()),
+        }
+    }
+}
+
+impl Db for SledDb {
+    type Config = SledDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> DbResult<()> {
+        let mut db = self.db.write();
+        if db.is_none() {
+            *db = Some(
+                ::sled::Config::new()
+                    .path(&self.config.path)
+                    .cache_capacity(self.config.cache_capacity)
+                    .open()?,
+            );
+        }
+        self.refs.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        // Keep the database open, the file lock of a closed database is released asynchronously, which fails the
+        // `init()` of the next phase.
+        let db = self.db.read();
+        if self.refs.fetch_sub(1, Ordering::SeqCst) == 1 {
+            if let Some(db) = db.as_ref() {
+                db.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        let record = values
+            .into_iter()
+            .map(|(field, value)| Ok((field, value.into_bytes()?)))
+            .collect::<DbResult<HashMap<_, _>>>()?;
+        self.with_tree(&table, |tree| {
+            tree.insert(key, encode(&record))?;
+            Ok(())
+        })
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let buf = self
+            .with_tree(&table, |tree| Ok(tree.get(&key)?))?
+            .ok_or_else(|| not_found(&table, &key))?;
+        Ok(project(decode(&buf)?, &fields)
+            .map(|(field, value)| (field, value.into()))
+            .collect())
+    }
+
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        let values = values
+            .into_iter()
+            .map(|(field, value)| Ok((field, value.into_bytes()?)))
+            .collect::<DbResult<Vec<_>>>()?;
+        self.with_tree(&table, |tree| {
+            // Merge the fields atomically, the closure may be called multiple times on conflicts.
+            let mut res: anyhow::Result<()> = Ok(());
+            tree.fetch_and_update(&key, |old| {
+                let mut record = match old.map(decode).transpose() {
+                    Ok(record) => record.unwrap_or_default(),
+                    Err(e) => {
+                        res = Err(e);
+                        return old.map(<[u8]>::to_vec);
+                    }
+                };
+                record.extend(values.iter().cloned());
+                Some(encode(&record))
+            })?;
+            Ok(res?)
+        })
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        self.scan_rows(table, start_key, len, fields)
+            .map(into_columns)
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        self.with_tree(&table, |tree| {
+            tree.range(start_key.as_bytes()..)
+                .take(len)
+                .map(|item| {
+                    let (_, buf) = item?;
+                    Ok(project(decode(&buf)?, &fields)
+                        .map(|(field, value)| (field, value.into()))
+                        .collect())
+                })
+                .collect()
+        })
+    }
+
+    fn delete(&self, table: String, key: String) -> DbResult<()> {
+        self.with_tree(&table, |tree| {
+            tree.remove(key)?;
+            Ok(())
+        })
+    }
+}
+
+impl From<::sled::Error> for DbError {
+    /// Classify the I/O errors that may go away, e.g. the interrupted ones, as [`DbError::Transient`].
+    fn from(error: ::sled::Error) -> Self {
+        match &error {
+            ::sled::Error::Io(io) if is_transient_io(io) => Self::Transient(error.into()),
+            _ => Self::Other(error.into()),
+        }
+    }
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn cache_capacity() -> u64 {
+        1 << 30
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbError;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string().into()))
+            .collect()
+    }
+
+    fn bytes(cells: HashMap<String, Value>) -> HashMap<String, Vec<u8>> {
+        cells
+            .into_iter()
+            .map(|(field, value)| (field, value.into_bytes().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let path = std::env::temp_dir().join(format!("yay-sled-{}", std::process::id()));
+        let db = SledDb::from_config(SledDbConfig {
+            path: path.clone(),
+            cache_capacity: default::cache_capacity(),
+        })
+        .unwrap();
+        let e = db
+            .read("t".to_string(), "k1".to_string(), HashSet::new())
+            .unwrap_err();
+        assert!(e.to_string().contains("not initialized"), "{e}");
+        db.init().unwrap();
+
+        for key in ["k2", "k1", "k3"] {
+            db.insert(
+                "t".to_string(),
+                key.to_string(),
+                values(&[("field0", key), ("field1", "b")]),
+            )
+            .unwrap();
+        }
+
+        let record = bytes(
+            db.read("t".to_string(), "k1".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record.len(), 2);
+        assert_eq!(record["field0"], b"k1");
+        assert_eq!(record["field1"], b"b");
+
+        db.update(
+            "t".to_string(),
+            "k1".to_string(),
+            values(&[("field1", "x")]),
+        )
+        .unwrap();
+        let record = bytes(
+            db.read(
+                "t".to_string(),
+                "k1".to_string(),
+                HashSet::from(["field1".to_string()]),
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            record,
+            HashMap::from([("field1".to_string(), b"x".to_vec())])
+        );
+
+        let rows = db
+            .scan_rows("t".to_string(), "k2".to_string(), 10, HashSet::new())
+            .unwrap();
+        let keys = rows
+            .into_iter()
+            .map(|row| bytes(row).remove("field0").unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+
+        db.delete("t".to_string(), "k1".to_string()).unwrap();
+        let e = db
+            .read("t".to_string(), "k1".to_string(), HashSet::new())
+            .unwrap_err();
+        assert!(matches!(e, DbError::NotFound(_)));
+
+        // The records are flushed by the last cleanup, and found by the next phase.
+        db.cleanup().unwrap();
+        db.init().unwrap();
+        let record = bytes(
+            db.read("t".to_string(), "k2".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record["field0"], b"k2");
+        db.cleanup().unwrap();
+
+        std::fs::remove_dir_all(path).unwrap();
+    }
+}
@@ -0,0 +1,434 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use itertools::Itertools;
+use parking_lot::Mutex;
+use rusqlite::{params_from_iter, types::Value as SqlValue, Connection, ErrorCode};
+use serde::{Deserialize, Serialize};
+
+use super::{into_columns, not_found, Db, DbError, DbResult};
+use crate::utils::Value;
+
+/// Configuration for the [`SqliteDb`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SqliteDbConfig {
+    /// The database file.
+    pub path: PathBuf,
+    /// The number of fields in a record, which must match the workload.
+    #[serde(default = "default::fields")]
+    pub fields: usize,
+    /// The prefix of the field names, which must match the workload.
+    #[serde(default = "default::field_name_prefix")]
+    pub field_name_prefix: String,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    conn: Option<Connection>,
+    /// The tables that are known to exist.
+    tables: HashSet<String>,
+}
+
+/// A [`Db`] backed by an embedded SQLite database.
+///
+/// Each table has a `key TEXT PRIMARY KEY` column and a `BLOB` column for each field, the schema is derived from the
+/// configured field count and field name prefix. The tables are created on first use.
+///
+/// Each [`SqliteDb`] instance owns its own connection, so create one instance for each client thread with
+/// [`SqliteDb::new`]. The connection is opened by `init()` and closed by `cleanup()`. Clones of an instance share its
+/// connection.
+#[derive(Debug, Clone)]
+pub struct SqliteDb {
+    config: SqliteDbConfig,
+    columns: Arc<Vec<String>>,
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl SqliteDb {
+    /// Create a SQLite backend with the given config.
+    pub fn new(config: SqliteDbConfig) -> Self {
+        let columns = (0..config.fields)
+            .map(|i| format!("{prefix}{i}", prefix = config.field_name_prefix))
+            .collect_vec();
+        Self {
+            config,
+            columns: Arc::new(columns),
+            inner: Arc::new(Mutex::new(Inner::default())),
+        }
+    }
+
+    /// Run `f` with the connection, after creating `table` if it is not known to exist.
+    fn with_conn<T>(
+        &self,
+        table: &str,
+        f: impl FnOnce(&mut Connection) -> DbResult<T>,
+    ) -> DbResult<T> {
+        let mut inner = self.inner.lock();
+        let Inner { conn, tables } = &mut *inner;
+        let conn = conn
+            .as_mut()
+            .ok_or_else(|| anyhow!("sqlite is not initialized"))?;
+        if !tables.contains(table) {
+            let columns = self
+                .columns
+                .iter()
+                .map(|column| format!("{} BLOB", quote(column)))
+                .join(", ");
+            conn.execute(
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS {} (key TEXT PRIMARY KEY, {columns})",
+                    quote(table)
+                ),
+                [],
+            )?;
+            tables.insert(table.to_string());
+        }
+        f(conn)
+    }
+
+    /// The columns to select, all the field columns if `fields` is empty.
+    fn projection(&self, fields: HashSet<String>) -> Vec<String> {
+        if fields.is_empty() {
+            self.columns.to_vec()
+        } else {
+            fields.into_iter().collect()
+        }
+    }
+}
+
+impl Db for SqliteDb {
+    type Config = SqliteDbConfig;
+
+    fn from_config(config: Self::Config) -> DbResult<Self> {
+        Ok(Self::new(config))
+    }
+
+    fn init(&self) -> DbResult<()> {
+        let mut inner = self.inner.lock();
+        if inner.conn.is_none() {
+            inner.conn = Some(Connection::open(&self.config.path)?);
+        }
+        Ok(())
+    }
+
+    fn cleanup(&self) -> DbResult<()> {
+        let mut inner = self.inner.lock();
+        if let Some(conn) = inner.conn.take() {
+            conn.close().map_err(|(_, e)| e)?;
+        }
+        inner.tables.clear();
+        Ok(())
+    }
+
+    fn insert(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        self.with_conn(&table, |conn| insert(conn, &table, key, values))
+    }
+
+    fn read(
+        &self,
+        table: String,
+        key: String,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Value>> {
+        let columns = self.projection(fields);
+        let sql = format!(
+            "SELECT {} FROM {} WHERE key = ?",
+            columns.iter().map(|column| quote(column)).join(", "),
+            quote(&table)
+        );
+        let row = self.with_conn(&table, |conn| {
+            let mut stmt = conn.prepare_cached(&sql)?;
+            let mut rows = stmt.query([&key])?;
+            rows.next()?.map(|row| take_row(row, &columns)).transpose()
+        })?;
+        Ok(row
+            .ok_or_else(|| not_found(&table, &key))?
+            .into_iter()
+            .map(|(field, value)| (field, value.into()))
+            .collect())
+    }
+
+    fn update(&self, table: String, key: String, values: HashMap<String, Value>) -> DbResult<()> {
+        if values.is_empty() {
+            return Ok(());
+        }
+        let (fields, values): (Vec<_>, Vec<_>) = values
+            .into_iter()
+            .map(|(field, value)| Ok((field, SqlValue::Blob(value.into_bytes()?))))
+            .collect::<DbResult<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let sql = format!(
+            "UPDATE {} SET {} WHERE key = ?",
+            quote(&table),
+            fields
+                .iter()
+                .map(|field| format!("{} = ?", quote(field)))
+                .join(", ")
+        );
+        self.with_conn(&table, |conn| {
+            let mut stmt = conn.prepare_cached(&sql)?;
+            stmt.execute(params_from_iter(
+                values.into_iter().chain([SqlValue::Text(key.clone())]),
+            ))?;
+            Ok(())
+        })
+    }
+
+    fn scan(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<HashMap<String, Vec<Value>>> {
+        self.scan_rows(table, start_key, len, fields)
+            .map(into_columns)
+    }
+
+    fn scan_rows(
+        &self,
+        table: String,
+        start_key: String,
+        len: usize,
+        fields: HashSet<String>,
+    ) -> DbResult<Vec<HashMap<String, Value>>> {
+        let columns = self.projection(fields);
+        let sql = format!(
+            "SELECT {} FROM {} WHERE key >= ? ORDER BY key LIMIT ?",
+            columns.iter().map(|column| quote(column)).join(", "),
+            quote(&table)
+        );
+        self.with_conn(&table, |conn| {
+            let mut stmt = conn.prepare_cached(&sql)?;
+            let mut rows = stmt.query(params_from_iter([
+                SqlValue::Text(start_key),
+                SqlValue::Integer(len.min(i64::MAX as usize) as i64),
+            ]))?;
+            let mut result = vec![];
+            while let Some(row) = rows.next()? {
+                let record = take_row(row, &columns)?
+                    .into_iter()
+                    .map(|(field, value)| (field, value.into()))
+                    .collect();
+                result.push(record);
+            }
+            Ok(result)
+        })
+    }
+
+    fn delete(&self, table: String, key: String) -> DbResult<()> {
+        let sql = format!("DELETE FROM {} WHERE key = ?", quote(&table));
+        self.with_conn(&table, |conn| {
+            conn.prepare_cached(&sql)?.execute([&key])?;
+            Ok(())
+        })
+    }
+
+    fn insert_batch(
+        &self,
+        table: String,
+        records: Vec<(String, HashMap<String, Value>)>,
+    ) -> DbResult<()> {
+        self.with_conn(&table, |conn| {
+            let txn = conn.transaction()?;
+            for (key, values) in records {
+                insert(&txn, &table, key, values)?;
+            }
+            txn.commit()?;
+            Ok(())
+        })
+    }
+}
+
+fn insert(
+    conn: &Connection,
+    table: &str,
+    key: String,
+    values: HashMap<String, Value>,
+) -> DbResult<()> {
+    let (fields, values): (Vec<_>, Vec<_>) = values
+        .into_iter()
+        .map(|(field, value)| Ok((field, SqlValue::Blob(value.into_bytes()?))))
+        .collect::<DbResult<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    let sql = format!(
+        "INSERT OR REPLACE INTO {} (key{}) VALUES (?{})",
+        quote(table),
+        fields
+            .iter()
+            .map(|field| format!(", {}", quote(field)))
+            .join(""),
+        ", ?".repeat(fields.len())
+    );
+    let mut stmt = conn.prepare_cached(&sql)?;
+    stmt.execute(params_from_iter(
+        [SqlValue::Text(key)].into_iter().chain(values),
+    ))?;
+    Ok(())
+}
+
+/// Take the non-null columns of the row.
+fn take_row(row: &rusqlite::Row<'_>, columns: &[String]) -> DbResult<Vec<(String, Vec<u8>)>> {
+    let mut record = vec![];
+    for (i, column) in columns.iter().enumerate() {
+        if let Some(value) = row.get::<_, Option<Vec<u8>>>(i)? {
+            record.push((column.clone(), value));
+        }
+    }
+    Ok(record)
+}
+
+impl From<rusqlite::Error> for DbError {
+    /// Classify the busy and locked database errors, which are raised when a concurrent transaction holds the lock, as
+    /// [`DbError::Transient`].
+    fn from(error: rusqlite::Error) -> Self {
+        match error.sqlite_error_code() {
+            Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked) => {
+                Self::Transient(error.into())
+            }
+            _ => Self::Other(error.into()),
+        }
+    }
+}
+
+/// Quote an identifier.
+fn quote(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn fields() -> usize {
+        10
+    }
+
+    pub fn field_name_prefix() -> String {
+        "field".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbError;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string().into()))
+            .collect()
+    }
+
+    fn bytes(cells: HashMap<String, Value>) -> HashMap<String, Vec<u8>> {
+        cells
+            .into_iter()
+            .map(|(field, value)| (field, value.into_bytes().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let db = SqliteDb::from_config(SqliteDbConfig {
+            path: ":memory:".into(),
+            fields: 2,
+            field_name_prefix: "field".to_string(),
+        })
+        .unwrap();
+        db.init().unwrap();
+
+        for key in ["k2", "k1", "k3"] {
+            db.insert(
+                "t".to_string(),
+                key.to_string(),
+                values(&[("field0", key), ("field1", "b")]),
+            )
+            .unwrap();
+        }
+
+        let record = bytes(
+            db.read("t".to_string(), "k1".to_string(), HashSet::new())
+                .unwrap(),
+        );
+        assert_eq!(record.len(), 2);
+        assert_eq!(record["field0"], b"k1");
+        assert_eq!(record["field1"], b"b");
+
+        db.update(
+            "t".to_string(),
+            "k1".to_string(),
+            values(&[("field1", "x")]),
+        )
+        .unwrap();
+        let record = bytes(
+            db.read(
+                "t".to_string(),
+                "k1".to_string(),
+                HashSet::from(["field1".to_string()]),
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            record,
+            HashMap::from([("field1".to_string(), b"x".to_vec())])
+        );
+
+        let rows = db
+            .scan_rows("t".to_string(), "k2".to_string(), 10, HashSet::new())
+            .unwrap();
+        let keys = rows
+            .into_iter()
+            .map(|row| bytes(row).remove("field0").unwrap())
+            .collect_vec();
+        assert_eq!(keys, vec![b"k2".to_vec(), b"k3".to_vec()]);
+
+        db.delete("t".to_string(), "k1".to_string()).unwrap();
+        let e = db
+            .read("t".to_string(), "k1".to_string(), HashSet::new())
+            .unwrap_err();
+        assert!(matches!(e, DbError::NotFound(_)));
+
+        db.cleanup().unwrap();
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let error = |code| rusqlite::Error::SqliteFailure(rusqlite::ffi::Error::new(code), None);
+        assert!(matches!(
+            DbError::from(error(rusqlite::ffi::SQLITE_BUSY)),
+            DbError::Transient(_)
+        ));
+        assert!(matches!(
+            DbError::from(error(rusqlite::ffi::SQLITE_LOCKED)),
+            DbError::Transient(_)
+        ));
+        assert!(matches!(
+            DbError::from(error(rusqlite::ffi::SQLITE_CONSTRAINT)),
+            DbError::Other(_)
+        ));
+        assert!(matches!(
+            DbError::from(rusqlite::Error::QueryReturnedNoRows),
+            DbError::Other(_)
+        ));
+    }
+}
@@ -28,25 +28,87 @@ macro_rules! acknowledged {
     ($( {$atype:ty, $type:ty, $name:ident}, )*) => {
         paste! {
             $(
-                // FIXME(MrCroxx): Take care of the memory order.
                 /// Generates a sequence of numeric value in an atomic manner.
+                ///
+                /// Each `next()` call returns a distinct value even under concurrent calls. `last()` only advances
+                /// after all the values up to it are acknowledged, and the advanced limit is published under a lock, so
+                /// `last()` observes the acknowledgements that happen before it.
+                ///
+                /// At most `window_size` values can be in flight between the last acknowledged value and the newly
+                /// acknowledged one. When the window is exhausted, `acknowledge()` blocks until the previous values are
+                /// acknowledged.
+                ///
+                /// The values below `start` are regarded as acknowledged, so `last()` is `start - 1` before any value is
+                /// acknowledged. Use `last_acknowledged()` to tell whether a value is acknowledged since the start.
+                /// Acknowledging a value below the limit of the acknowledged values again, e.g. a value below `start`,
+                /// does nothing.
                 #[derive(Debug)]
                 pub struct [<Acknowledged $name Counter>] {
                     counter: $atype,
                     windows: Vec<AtomicBool>,
+                    window_mask: usize,
+                    start: $type,
+                    /// The first value that is not acknowledged yet. All the values from `start` below it are
+                    /// acknowledged.
                     limit: RwLock<$type>
                 }
 
                 impl [<Acknowledged $name Counter>] {
-                    const WINDOW_SIZE: usize = 1 << 20;
-                    const WINDOW_MASK: usize = Self::WINDOW_SIZE - 1;
+                    /// Default size of the acknowledgement window.
+                    pub const DEFAULT_WINDOW_SIZE: usize = 1 << 20;
 
                     /// Create a counter that starts at `start`.
                     pub fn new(start: $type) -> Self {
+                        Self::with_window_size(start, Self::DEFAULT_WINDOW_SIZE)
+                    }
+
+                    /// Create a counter that starts at `start`, with the given acknowledgement window size.
+                    ///
+                    /// The window size is rounded up to the next power of two.
+                    pub fn with_window_size(start: $type, window_size: usize) -> Self {
+                        let window_size = window_size.next_power_of_two();
                         Self {
                             counter: $atype::new(start),
-                            windows: (0..Self::WINDOW_SIZE).map(|_| AtomicBool::new(false)).collect_vec(),
-                            limit: RwLock::new(start - 1),
+                            windows: (0..window_size).map(|_| AtomicBool::new(false)).collect_vec(),
+                            window_mask: window_size - 1,
+                            start,
+                            limit: RwLock::new(start),
+                        }
+                    }
+
+                    /// Get the last acknowledged value, or `None` if no value is acknowledged since the start.
+                    ///
+                    /// Unlike `last()`, it never underflows when the counter starts at the minimum value of its type.
+                    pub fn last_acknowledged(&self) -> Option<$type> {
+                        let limit = *self.limit.read();
+                        (limit != self.start).then(|| limit - 1)
+                    }
+
+                    /// Advance the limit over the consecutive acknowledged values, if no one else is advancing it.
+                    fn advance(&self) {
+                        loop {
+                            let Some(mut limit) = self.limit.try_write() else {
+                                return;
+                            };
+                            // Scan at most a window of slots, ending at the slot of the last acknowledged value.
+                            let stop = (*limit as usize).wrapping_sub(1) & self.window_mask;
+                            let mut index = *limit;
+                            while index as usize & self.window_mask != stop {
+                                let slot = index as usize & self.window_mask;
+                                if !self.windows[slot].load(Ordering::SeqCst) {
+                                    break;
+                                }
+                                self.windows[slot].store(false, Ordering::SeqCst);
+                                index += 1;
+                            }
+                            *limit = index;
+                            drop(limit);
+
+                            // A value acknowledged while the lock is held may have failed to advance the limit, check
+                            // it again after the lock is released.
+                            if !self.windows[index as usize & self.window_mask].load(Ordering::SeqCst) {
+                                return;
+                            }
                         }
                     }
                 }
@@ -55,36 +117,44 @@ macro_rules! acknowledged {
                     type Output = $type;
 
                     fn next(&self) -> Self::Output {
-                        self.counter.fetch_add(1, Ordering::Relaxed)
+                        self.counter.fetch_add(1, Ordering::AcqRel)
                     }
                 }
 
                 impl Counter for [<Acknowledged $name Counter>] {
+                    /// # Panics
+                    ///
+                    /// Panics if no value is acknowledged and the counter starts at the minimum value of its type, see
+                    /// `last_acknowledged()`.
                     fn last(&self) -> Self::Output {
-                        *self.limit.read()
+                        self.limit
+                            .read()
+                            .checked_sub(1)
+                            .expect("no value is acknowledged below the start of the counter")
                     }
                 }
 
                 impl AcknowledgedCounter for [<Acknowledged $name Counter>] {
                     fn acknowledge(&self, val: Self::Output) {
-                        let slot = val as usize & Self::WINDOW_MASK;
-                        if self.windows[slot].fetch_or(true, Ordering::SeqCst) {
-                            panic!("Too many unacknowledged insertion keys.");
-                        }
-
-                        if let Some(mut limit) = self.limit.try_write() {
-                            let stop = *limit as usize & Self::WINDOW_MASK;
-                            let mut index = *limit + 1;
-                            while index as usize & Self::WINDOW_MASK != stop {
-                                let slot = index as usize & Self::WINDOW_MASK;
-                                if !self.windows[slot].load(Ordering::SeqCst) {
-                                    break;
-                                }
-                                self.windows[slot].store(false, Ordering::SeqCst);
-                                index += 1;
+                        loop {
+                            let limit = *self.limit.read();
+                            if val < limit {
+                                // Already acknowledged, and its slot may be taken by a value a window ahead.
+                                return;
+                            }
+                            if (val as usize).wrapping_sub(limit as usize) < self.windows.len() {
+                                break;
                             }
-                            *limit = index - 1;
+                            // The slot of the value is still taken by an unacknowledged value a window ahead, wait
+                            // until the limit catches up.
+                            self.advance();
+                            std::thread::yield_now();
                         }
+
+                        let slot = val as usize & self.window_mask;
+                        self.windows[slot].store(true, Ordering::SeqCst);
+
+                        self.advance();
                     }
                 }
             )*
@@ -110,3 +180,78 @@ macro_rules! for_all_numeric_types {
 }
 
 for_all_numeric_types! { acknowledged }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn test_acknowledge_below_limit() {
+        let counter = AcknowledgedUsizeCounter::new(10);
+        // Below the start.
+        counter.acknowledge(5);
+        assert_eq!(counter.last_acknowledged(), None);
+
+        assert_eq!(counter.next(), 10);
+        counter.acknowledge(10);
+        counter.acknowledge(10);
+        assert_eq!(counter.last(), 10);
+        assert_eq!(counter.next(), 11);
+        counter.acknowledge(11);
+        assert_eq!(counter.last(), 11);
+    }
+
+    #[test]
+    fn test_more_than_a_window_in_flight() {
+        const THREADS: usize = 4;
+        let total = AcknowledgedUsizeCounter::DEFAULT_WINDOW_SIZE + 1000;
+
+        let counter = Arc::new(AcknowledgedUsizeCounter::new(0));
+        // Hold the first value, so all the others stay in flight.
+        let held = counter.next();
+        let handles = (0..THREADS)
+            .map(|_| {
+                let counter = counter.clone();
+                thread::spawn(move || {
+                    for _ in 0..total / THREADS {
+                        let val = counter.next();
+                        counter.acknowledge(val);
+                    }
+                })
+            })
+            .collect_vec();
+
+        // The values a window ahead of the held one wait for it.
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(counter.last_acknowledged(), None);
+        assert!(!handles.iter().all(|handle| handle.is_finished()));
+
+        counter.acknowledge(held);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(counter.last(), total / THREADS * THREADS);
+    }
+
+    #[test]
+    fn test_start_at_zero() {
+        let counter = AcknowledgedUsizeCounter::new(0);
+        assert_eq!(counter.last_acknowledged(), None);
+
+        assert_eq!(counter.next(), 0);
+        assert_eq!(counter.next(), 1);
+        // Out of order, nothing is acknowledged until 0 is.
+        counter.acknowledge(1);
+        assert_eq!(counter.last_acknowledged(), None);
+        counter.acknowledge(0);
+        assert_eq!(counter.last_acknowledged(), Some(1));
+        assert_eq!(counter.last(), 1);
+
+        let counter = AcknowledgedI8Counter::new(i8::MIN);
+        assert_eq!(counter.last_acknowledged(), None);
+        counter.acknowledge(counter.next());
+        assert_eq!(counter.last(), i8::MIN);
+    }
+}
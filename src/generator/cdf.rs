@@ -0,0 +1,215 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng};
+
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
+
+/// The tolerance of the last cumulative probability from 1.0, for the rounding errors of the measured tables.
+const TOLERANCE: f64 = 1e-6;
+
+/// Generates a distribution given by an explicit cumulative distribution function (CDF) table, e.g. a key popularity
+/// curve or a size distribution measured from production.
+///
+/// Each entry of the table is a value and the probability of generating it or any of the values before it. Entry `i` is
+/// generated with the probability `p[i] - p[i - 1]`, which is found by a binary search of a uniform draw.
+#[derive(Debug)]
+pub struct CdfGenerator {
+    values: Vec<usize>,
+    cumulative: Vec<f64>,
+    rng: Option<Mutex<StdRng>>,
+}
+
+impl CdfGenerator {
+    /// Create a generator from the entries of a CDF table, each of which is a value and its cumulative probability.
+    ///
+    /// Returns an error if the table is empty, any probability is not in `[0.0, 1.0]`, the probabilities decrease, or
+    /// the last probability is not 1.0.
+    pub fn new(table: Vec<(usize, f64)>) -> Result<Self> {
+        let mut prev = 0.0;
+        for &(value, p) in &table {
+            if !(0.0..=1.0).contains(&p) {
+                return Err(anyhow!(
+                    "invalid cumulative probability {p} of value {value}"
+                ));
+            }
+            if p < prev {
+                return Err(anyhow!(
+                    "cumulative probability {p} of value {value} is less than the previous one {prev}"
+                ));
+            }
+            prev = p;
+        }
+        match table.last() {
+            None => return Err(anyhow!("cdf table must have at least one entry")),
+            Some(&(_, p)) if (1.0 - p).abs() > TOLERANCE => {
+                return Err(anyhow!(
+                    "the last cumulative probability of the cdf table is {p}, not 1.0"
+                ))
+            }
+            Some(_) => {}
+        }
+        let (values, cumulative) = table.into_iter().unzip();
+        Ok(Self {
+            values,
+            cumulative,
+            rng: None,
+        })
+    }
+
+    /// Create a generator from a CDF file, each line of which is `<value> <cumulative probability>`.
+    ///
+    /// ```plain
+    /// 100   0.5
+    /// 1000  0.9
+    /// 10000 1.0
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| anyhow!("failed to open cdf file {}: {e}", path.display()))?;
+
+        let mut table = vec![];
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [value, p] => table.push((value.parse()?, p.parse()?)),
+                _ => {
+                    return Err(anyhow!(
+                        "malformed line in cdf file {}: {line}",
+                        path.display()
+                    ))
+                }
+            }
+        }
+
+        Self::new(table)
+    }
+
+    /// Iterate over the values with the probabilities of generating them, in the order of the table.
+    fn probabilities(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.values
+            .iter()
+            .zip(&self.cumulative)
+            .scan(0.0, |prev, (&value, &p)| {
+                let probability = p - *prev;
+                *prev = p;
+                Some((value, probability))
+            })
+    }
+}
+
+impl Seedable for CdfGenerator {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = Some(Mutex::new(rng));
+        self
+    }
+}
+
+impl Generator for CdfGenerator {
+    type Output = usize;
+
+    fn next(&self) -> Self::Output {
+        let target = match &self.rng {
+            Some(rng) => rng.lock().gen::<f64>(),
+            None => workload_rng().gen::<f64>(),
+        };
+        // The last probability may be slightly less than 1.0, take the last value for the targets beyond it.
+        let index = self
+            .cumulative
+            .partition_point(|&p| p <= target)
+            .min(self.values.len() - 1);
+        self.values[index]
+    }
+}
+
+impl NumberGenerator for CdfGenerator {
+    fn mean(&self) -> f64 {
+        self.probabilities()
+            .map(|(value, probability)| value as f64 * probability)
+            .sum()
+    }
+
+    fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.probabilities()
+            .map(|(value, probability)| (value as f64 - mean).powi(2) * probability)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_cdf() {
+        // The probability of 20 is 0, so the steps are at 10 and 30.
+        let generator = CdfGenerator::new(vec![(10, 0.5), (20, 0.5), (30, 1.0)]).unwrap();
+        assert_eq!(generator.mean(), 20.0);
+        assert_eq!(generator.variance(), 100.0);
+
+        let mut tens = 0;
+        for _ in 0..10_000 {
+            match generator.next() {
+                10 => tens += 1,
+                30 => {}
+                x => panic!("unexpected value {x}"),
+            }
+        }
+        assert!((4500..5500).contains(&tens), "{tens}");
+
+        let generator = CdfGenerator::new(vec![(7, 1.0)]).unwrap();
+        assert_eq!(generator.next(), 7);
+        assert_eq!(generator.variance(), 0.0);
+    }
+
+    #[test]
+    fn test_malformed_cdf() {
+        let e = CdfGenerator::new(vec![(10, 0.6), (20, 0.4), (30, 1.0)]).unwrap_err();
+        assert!(e.to_string().contains("less than the previous one"), "{e}");
+
+        assert!(CdfGenerator::new(vec![]).is_err());
+        assert!(CdfGenerator::new(vec![(10, 0.5), (20, 0.9)]).is_err());
+        assert!(CdfGenerator::new(vec![(10, -0.1), (20, 1.0)]).is_err());
+        assert!(CdfGenerator::new(vec![(10, f64::NAN), (20, 1.0)]).is_err());
+        // Within the tolerance of the rounding errors.
+        assert!(CdfGenerator::new(vec![(10, 0.5), (20, 1.0 - 1e-9)]).is_ok());
+    }
+
+    #[test]
+    fn test_from_file() {
+        let path = std::env::temp_dir().join(format!("yay-cdf-{}", std::process::id()));
+        std::fs::write(&path, "100   0.5\n\n1000  0.9\n10000 1.0\n").unwrap();
+        let generator = CdfGenerator::from_file(&path).unwrap();
+        assert_eq!(generator.values, vec![100, 1000, 10000]);
+        assert!((generator.mean() - 1450.0).abs() < 1e-9);
+
+        std::fs::write(&path, "100 0.5 1\n").unwrap();
+        assert!(CdfGenerator::from_file(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -16,6 +16,35 @@ use super::{Generator, NumberGenerator};
 
 use paste::paste;
 
+/// A tritival generator that always returns a clone of the same value of any type.
+///
+/// Use the numeric constant generators, e.g. [`ConstantUsizeGenerator`], where a [`NumberGenerator`] is required.
+#[derive(Debug, Clone)]
+pub struct ConstantGenerator<T> {
+    val: T,
+}
+
+impl<T> ConstantGenerator<T> {
+    /// Creates a tritival generator that always returns a clone of the same value.
+    pub fn new(val: T) -> Self {
+        Self { val }
+    }
+}
+
+impl<T> Generator for ConstantGenerator<T>
+where
+    T: Clone + Send + Sync,
+{
+    type Output = T;
+
+    fn next(&self) -> Self::Output {
+        self.val.clone()
+    }
+}
+
+/// A tritival generator that always returns the same string.
+pub type ConstantStringGenerator = ConstantGenerator<String>;
+
 macro_rules! constant {
     ($( {$type:ty, $name:ident}, )*) => {
         paste! {
@@ -41,12 +70,20 @@ macro_rules! constant {
                     fn next(&self) -> Self::Output {
                         self.val
                     }
+
+                    fn next_batch(&self, n: usize, out: &mut Vec<Self::Output>) {
+                        out.resize(out.len() + n, self.val);
+                    }
                 }
 
                 impl NumberGenerator for [<Constant $name Generator>] {
                     fn mean(&self) -> f64 {
                         self.val as f64
                     }
+
+                    fn variance(&self) -> f64 {
+                        0.0
+                    }
                 }
             )*
         }
@@ -71,3 +108,25 @@ macro_rules! for_all_numeric_types {
 }
 
 for_all_numeric_types! { constant }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_string() {
+        let generator = ConstantGenerator::new("foo".to_string());
+        assert_eq!(generator.next(), "foo");
+        assert_eq!(generator.next(), "foo");
+
+        let generator: ConstantStringGenerator = ConstantGenerator::new("bar".to_string());
+        let mut out = vec![];
+        generator.next_batch(3, &mut out);
+        assert_eq!(out, vec!["bar"; 3]);
+
+        let generator = ConstantUsizeGenerator::new(7);
+        assert_eq!(generator.next(), 7);
+        assert_eq!(generator.mean(), 7.0);
+        assert_eq!(generator.variance(), 0.0);
+    }
+}
@@ -25,10 +25,14 @@ macro_rules! counter {
     ($( {$atype:ty, $type:ty, $name:ident}, )*) => {
         paste! {
             $(
-                // FIXME(MrCroxx): Take care of the memory order.
                 /// Generates a sequence of numeric value in an atomic manner.
+                ///
+                /// Each `next()` call returns a distinct value even under concurrent calls. `next()` releases the
+                /// advanced counter and `last()` acquires it, so `last()` observes at least the values generated by the
+                /// `next()` calls that happen before it.
                 #[derive(Debug)]
                 pub struct [<$name Counter>] {
+                    start: $type,
                     counter: $atype,
                 }
 
@@ -36,22 +40,38 @@ macro_rules! counter {
                     /// Create a counter that starts at `start`.
                     pub fn new(start: $type) -> Self {
                         Self {
+                            start,
                             counter: $atype::new(start),
                         }
                     }
+
+                    /// Rewind the counter, so the next generated value is `start` again.
+                    ///
+                    /// It is safe to call between benchmark phases, but not concurrently with `next()`.
+                    pub fn reset(&self) {
+                        self.set(self.start);
+                    }
+
+                    /// Jump the counter, so the next generated value is `value`. A value below `start` is clamped to
+                    /// `start`.
+                    ///
+                    /// It is safe to call between benchmark phases, but not concurrently with `next()`.
+                    pub fn set(&self, value: $type) {
+                        self.counter.store(value.max(self.start), Ordering::Release);
+                    }
                 }
 
                 impl Generator for [<$name Counter>] {
                     type Output = $type;
 
                     fn next(&self) -> Self::Output {
-                        self.counter.fetch_add(1, Ordering::Relaxed)
+                        self.counter.fetch_add(1, Ordering::AcqRel)
                     }
                 }
 
                 impl Counter for [<$name Counter>] {
                     fn last(&self) -> Self::Output {
-                        self.counter.load(Ordering::Relaxed) - 1
+                        self.counter.load(Ordering::Acquire) - 1
                     }
                 }
             )*
@@ -77,3 +97,50 @@ macro_rules! for_all_numeric_types {
 }
 
 for_all_numeric_types! { counter }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_and_set() {
+        let counter = UsizeCounter::new(10);
+        assert_eq!(counter.next(), 10);
+        assert_eq!(counter.next(), 11);
+
+        counter.reset();
+        assert_eq!(counter.next(), 10);
+
+        counter.set(100);
+        assert_eq!(counter.next(), 100);
+        assert_eq!(counter.last(), 100);
+
+        // Clamped to the start.
+        counter.set(0);
+        assert_eq!(counter.next(), 10);
+
+        let counter = I8Counter::new(-5);
+        counter.set(i8::MIN);
+        assert_eq!(counter.next(), -5);
+    }
+
+    #[test]
+    fn test_concurrent_next() {
+        const THREADS: usize = 8;
+        const CALLS: usize = 10_000;
+
+        let counter = UsizeCounter::new(10);
+        let mut values = std::thread::scope(|s| {
+            let handles = (0..THREADS)
+                .map(|_| s.spawn(|| (0..CALLS).map(|_| counter.next()).collect::<Vec<_>>()))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(counter.last(), 10 + THREADS * CALLS - 1);
+        values.sort_unstable();
+        assert!(values.into_iter().eq(10..10 + THREADS * CALLS));
+    }
+}
@@ -12,9 +12,12 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use rand::{thread_rng, Rng};
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng};
 
-use super::Generator;
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
 
 /// Choice of the generated value of [`DiscreteGenerator`].
 pub struct Choice<T> {
@@ -27,32 +30,224 @@ pub struct Choice<T> {
 /// Generates a distribution by choosing from a discrete set of values.
 pub struct DiscreteGenerator<T> {
     choices: Vec<Choice<T>>,
-    sum: f64,
+    /// Accumulated weights of the choices, the last one is the sum of all weights.
+    cumulative: Vec<f64>,
+    rng: Option<Mutex<StdRng>>,
 }
 
 impl<T> DiscreteGenerator<T> {
     /// Create a generator that generates a distribution by choosing from a discrete set of values.
-    pub fn new(choices: Vec<Choice<T>>) -> Self {
-        let sum = choices.iter().map(|choice| choice.weight).sum();
-        Self { choices, sum }
+    ///
+    /// Returns an error if any weight is negative or not finite, or if there is no choice with a positive weight, in
+    /// which case there is nothing to choose from.
+    pub fn new(choices: Vec<Choice<T>>) -> Result<Self> {
+        if let Some(choice) = choices
+            .iter()
+            .find(|choice| !choice.weight.is_finite() || choice.weight < 0.0)
+        {
+            return Err(anyhow!("invalid choice weight: {}", choice.weight));
+        }
+        let cumulative = choices
+            .iter()
+            .scan(0.0, |acc, choice| {
+                *acc += choice.weight;
+                Some(*acc)
+            })
+            .collect::<Vec<_>>();
+        if cumulative.last().copied().unwrap_or_default() <= 0.0 {
+            return Err(anyhow!("no choice with a positive weight"));
+        }
+        Ok(Self {
+            choices,
+            cumulative,
+            rng: None,
+        })
+    }
+
+    /// Get the sum of the weights of all the choices, which is always positive.
+    pub fn weight_sum(&self) -> f64 {
+        self.cumulative.last().copied().unwrap_or_default()
+    }
+
+    /// Iterate over the values of the choices with their normalized probabilities, `weight / weight_sum`, in the order
+    /// of the choices.
+    pub fn probabilities(&self) -> impl Iterator<Item = (&T, f64)> {
+        let sum = self.weight_sum();
+        self.choices
+            .iter()
+            .map(move |choice| (&choice.val, choice.weight / sum))
+    }
+}
+
+impl<T> Seedable for DiscreteGenerator<T> {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = Some(Mutex::new(rng));
+        self
     }
 }
 
 impl<T> Generator for DiscreteGenerator<T>
 where
-    T: Clone,
+    T: Clone + Send + Sync,
 {
     type Output = T;
 
     fn next(&self) -> Self::Output {
-        let target = thread_rng().gen_range(0.0..self.sum);
-        let mut acc = 0.0;
-        for choice in self.choices.iter() {
-            acc += choice.weight;
-            if target < acc {
-                return choice.val.clone();
+        let sum = *self.cumulative.last().unwrap();
+        let target = match &self.rng {
+            Some(rng) => rng.lock().gen_range(0.0..sum),
+            None => workload_rng().gen_range(0.0..sum),
+        };
+        // The target is always less than the sum, so there must be a choice with a larger accumulated weight.
+        let index = self.cumulative.partition_point(|&acc| acc <= target);
+        self.choices[index].val.clone()
+    }
+}
+
+macro_rules! discrete {
+    ($( {$type:ty, $name:ident}, )*) => {
+        $(
+            impl NumberGenerator for DiscreteGenerator<$type> {
+                fn mean(&self) -> f64 {
+                    let sum = *self.cumulative.last().unwrap();
+                    self.choices
+                        .iter()
+                        .map(|choice| choice.val as f64 * choice.weight)
+                        .sum::<f64>()
+                        / sum
+                }
             }
+        )*
+    };
+}
+
+macro_rules! for_all_numeric_types {
+    ($macro:ident) => {
+        $macro! {
+            {u8, U8},
+            {u16, U16},
+            {u32, U32},
+            {u64, U64},
+            {usize, Usize},
+            {i8, I8},
+            {i16, I16},
+            {i32, I32},
+            {i64, I64},
+            {isize, Isize},
+            {f32, F32},
+            {f64, F64},
         }
-        unreachable!()
+    };
+}
+
+for_all_numeric_types! { discrete }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_choices() {
+        assert!(DiscreteGenerator::<usize>::new(vec![]).is_err());
+        assert!(DiscreteGenerator::new(vec![Choice {
+            val: 1,
+            weight: 0.0
+        }])
+        .is_err());
+        assert!(DiscreteGenerator::new(vec![Choice {
+            val: 1,
+            weight: -1.0
+        }])
+        .is_err());
+    }
+
+    #[test]
+    fn test_binary_search_matches_linear_search() {
+        use rand::SeedableRng;
+
+        let weights = [0.05, 0.3, 0.0, 0.15, 0.4, 0.1];
+        let choices = weights
+            .iter()
+            .enumerate()
+            .map(|(val, &weight)| Choice { val, weight })
+            .collect();
+        let generator = DiscreteGenerator::new(choices)
+            .unwrap()
+            .with_rng(StdRng::seed_from_u64(42));
+
+        // Draw the same targets, and choose the first choice whose weight covers the target one by one.
+        let mut rng = StdRng::seed_from_u64(42);
+        let sum = weights.iter().sum::<f64>();
+        let mut counts = [0; 6];
+        for _ in 0..100_000 {
+            let mut target = rng.gen_range(0.0..sum);
+            let expected = weights
+                .iter()
+                .position(|&weight| {
+                    if target < weight {
+                        return true;
+                    }
+                    target -= weight;
+                    false
+                })
+                .unwrap();
+            let val = generator.next();
+            assert_eq!(val, expected);
+            counts[val] += 1;
+        }
+        assert_eq!(counts[2], 0);
+    }
+
+    #[test]
+    fn test_mean() {
+        let generator = DiscreteGenerator::new(vec![
+            Choice {
+                val: 10usize,
+                weight: 0.25,
+            },
+            Choice {
+                val: 20,
+                weight: 0.75,
+            },
+        ])
+        .unwrap();
+        assert!((generator.mean() - 17.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let generator = DiscreteGenerator::new(vec![
+            Choice {
+                val: "read",
+                weight: 0.95,
+            },
+            Choice {
+                val: "update",
+                weight: 0.05,
+            },
+            Choice {
+                val: "scan",
+                weight: 0.0,
+            },
+            Choice {
+                val: "insert",
+                weight: 1.0,
+            },
+        ])
+        .unwrap();
+        assert_eq!(generator.weight_sum(), 2.0);
+
+        let probabilities = generator.probabilities().collect::<Vec<_>>();
+        assert_eq!(
+            probabilities,
+            vec![
+                (&"read", 0.475),
+                (&"update", 0.025),
+                (&"scan", 0.0),
+                (&"insert", 0.5)
+            ]
+        );
+        let sum = probabilities.iter().map(|(_, p)| p).sum::<f64>();
+        assert!((sum - 1.0).abs() < 1e-12, "{sum}");
     }
 }
@@ -0,0 +1,139 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::Arc;
+
+use super::{
+    scrambled::ScrambledZipfianU64Generator, uniform::UniformU64Generator, Counter, Generator,
+    NumberGenerator, Seedable,
+};
+
+use rand::rngs::StdRng;
+
+/// A generator of the values in a range whose upper bound grows with a basis counter.
+///
+/// The upper bound is read on every `next()` call. It starts at the given upper bound, and advances as much as the last
+/// value of the basis counter has advanced since the generator was created. So the values inserted after the generator
+/// was created are chosen too.
+///
+/// The inner generator draws from a large value space, and its values are folded into the current range. It decides
+/// the distribution, e.g. [`DynamicRangeGenerator::uniform`] and [`DynamicRangeGenerator::scrambled_zipfian`].
+#[derive(Debug)]
+pub struct DynamicRangeGenerator<C, G> {
+    basis: Arc<C>,
+    /// The last value of the basis counter when the generator was created.
+    base: usize,
+    lower_bound: usize,
+    upper_bound: usize,
+    inner: G,
+}
+
+impl<C, G> DynamicRangeGenerator<C, G>
+where
+    C: Counter<Output = usize>,
+    G: Generator<Output = u64>,
+{
+    /// Create a generator of the values between `lower_bound` and `upper_bound` (inclusive), whose upper bound grows
+    /// with the `basis` counter, and whose values are the values of `inner` folded into the range.
+    pub fn new(basis: Arc<C>, lower_bound: usize, upper_bound: usize, inner: G) -> Self {
+        debug_assert!(
+            lower_bound <= upper_bound,
+            "lower bound {lower_bound} is larger than upper bound {upper_bound}"
+        );
+        let base = basis.last();
+        Self {
+            basis,
+            base,
+            lower_bound,
+            upper_bound,
+            inner,
+        }
+    }
+
+    /// Get the current upper bound (inclusive) of the generated values.
+    pub fn upper_bound(&self) -> usize {
+        let grown = self.basis.last().saturating_sub(self.base);
+        self.upper_bound.saturating_add(grown)
+    }
+}
+
+impl<C> DynamicRangeGenerator<C, UniformU64Generator>
+where
+    C: Counter<Output = usize>,
+{
+    /// Create a generator that chooses the values of the growing range uniformly.
+    pub fn uniform(basis: Arc<C>, lower_bound: usize, upper_bound: usize) -> Self {
+        Self::new(
+            basis,
+            lower_bound,
+            upper_bound,
+            UniformU64Generator::new(0, u64::MAX),
+        )
+    }
+}
+
+impl<C> DynamicRangeGenerator<C, ScrambledZipfianU64Generator>
+where
+    C: Counter<Output = usize>,
+{
+    /// Create a generator that chooses the values of the growing range following a scrambled zipfian distribution.
+    ///
+    /// The popular values are scattered throughout the range, like [`ScrambledZipfianU64Generator`].
+    pub fn scrambled_zipfian(basis: Arc<C>, lower_bound: usize, upper_bound: usize) -> Self {
+        Self::new(
+            basis,
+            lower_bound,
+            upper_bound,
+            ScrambledZipfianU64Generator::new(0, u64::MAX - 1),
+        )
+    }
+}
+
+impl<C, G> Seedable for DynamicRangeGenerator<C, G>
+where
+    G: Seedable,
+{
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.inner = self.inner.with_rng(rng);
+        self
+    }
+}
+
+impl<C, G> Generator for DynamicRangeGenerator<C, G>
+where
+    C: Counter<Output = usize>,
+    G: Generator<Output = u64>,
+{
+    type Output = usize;
+
+    fn next(&self) -> Self::Output {
+        let span = (self.upper_bound() - self.lower_bound) as u64;
+        let val = match span.checked_add(1) {
+            Some(items) => self.inner.next() % items,
+            None => self.inner.next(),
+        };
+        self.lower_bound + val as usize
+    }
+}
+
+impl<C, G> NumberGenerator for DynamicRangeGenerator<C, G>
+where
+    C: Counter<Output = usize>,
+    G: Generator<Output = u64>,
+{
+    /// The mean of the current range.
+    fn mean(&self) -> f64 {
+        (self.lower_bound as f64 + self.upper_bound() as f64) / 2.0
+    }
+}
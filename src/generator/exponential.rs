@@ -0,0 +1,107 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
+
+use paste::paste;
+
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng};
+
+macro_rules! exponential {
+    ($( {$type:ty, $name:ident}, )*) => {
+        paste! {
+            $(
+                /// A generator of an exponential distribution. It produces a sequence of time intervals according to an
+                /// exponential distribution.
+                ///
+                /// Smaller intervals are more frequent than larger ones, and there is no bound on the length of an
+                /// interval.
+                #[derive(Debug)]
+                pub struct [<Exponential $name Generator>] {
+                    /// The exponential constant to use.
+                    gamma: f64,
+                    rng: Option<Mutex<StdRng>>,
+                }
+
+                impl [<Exponential $name Generator>] {
+                    /// Create an exponential generator with `percentile` percent of the values in `[0, range)`.
+                    pub fn new(percentile: f64, range: f64) -> Self {
+                        Self {
+                            gamma: -(1.0 - percentile / 100.0).ln() / range,
+                            rng: None,
+                        }
+                    }
+
+                    /// Create an exponential generator with a mean arrival rate of `mean`.
+                    pub fn with_mean(mean: f64) -> Self {
+                        Self {
+                            gamma: 1.0 / mean,
+                            rng: None,
+                        }
+                    }
+                }
+
+                impl Seedable for [<Exponential $name Generator>] {
+                    fn with_rng(mut self, rng: StdRng) -> Self {
+                        self.rng = Some(Mutex::new(rng));
+                        self
+                    }
+                }
+
+                impl Generator for [<Exponential $name Generator>] {
+                    type Output = $type;
+
+                    fn next(&self) -> Self::Output {
+                        let u: f64 = match &self.rng {
+                            Some(rng) => rng.lock().gen(),
+                            None => workload_rng().gen(),
+                        };
+                        (-(1.0 - u).ln() / self.gamma) as $type
+                    }
+                }
+
+                impl NumberGenerator for [<Exponential $name Generator>] {
+                    fn mean(&self) -> f64 {
+                        1.0 / self.gamma
+                    }
+
+                    fn variance(&self) -> f64 {
+                        1.0 / (self.gamma * self.gamma)
+                    }
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! for_all_numeric_types {
+    ($macro:ident) => {
+        $macro! {
+            {u8, U8},
+            {u16, U16},
+            {u32, U32},
+            {u64, U64},
+            {usize, Usize},
+            {i8, I8},
+            {i16, I16},
+            {i32, I32},
+            {i64, I64},
+            {isize, Isize},
+        }
+    };
+}
+
+for_all_numeric_types! { exponential }
@@ -0,0 +1,86 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::utils::{CorpusBytes, Value};
+
+/// Generates record values by slicing a corpus file of real-world data, so the values compress and deduplicate like
+/// real data.
+///
+/// The corpus is read into memory once and shared by all the generated values. Each value continues from where the
+/// previous one ends, and wraps around to the beginning of the corpus at its end.
+#[derive(Debug)]
+pub struct FileValueGenerator {
+    data: Arc<[u8]>,
+    offset: AtomicUsize,
+}
+
+impl FileValueGenerator {
+    /// Create a value generator from the corpus file at `path`.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .map_err(|e| anyhow!("failed to read corpus file {}: {e}", path.display()))?;
+        Self::from_bytes(data)
+    }
+
+    /// Create a value generator from an in-memory corpus.
+    pub fn from_bytes(data: impl Into<Arc<[u8]>>) -> Result<Self> {
+        let data = data.into();
+        if data.is_empty() {
+            return Err(anyhow!("corpus must not be empty"));
+        }
+        Ok(Self {
+            data,
+            offset: AtomicUsize::new(0),
+        })
+    }
+
+    /// Generate a value of `size` bytes.
+    pub fn next_value(&self, size: usize) -> Value {
+        let offset = self.offset.fetch_add(size, Ordering::Relaxed) % self.data.len();
+        CorpusBytes::new(self.data.clone(), offset, size).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus_file() {
+        let path = std::env::temp_dir().join(format!("yay-corpus-{}", std::process::id()));
+        std::fs::write(&path, b"abcdefghij").unwrap();
+        let generator = FileValueGenerator::new(&path);
+        std::fs::remove_file(&path).unwrap();
+        let generator = generator.unwrap();
+
+        // Wraps around at the end of the corpus.
+        for expected in [&b"abcd"[..], b"efgh", b"ijab", b"cdef"] {
+            assert_eq!(generator.next_value(4).into_bytes().unwrap(), expected);
+        }
+
+        assert!(FileValueGenerator::new(&path).is_err());
+        assert!(FileValueGenerator::from_bytes(vec![]).is_err());
+    }
+}
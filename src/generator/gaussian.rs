@@ -0,0 +1,201 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
+
+use anyhow::{anyhow, Result};
+use paste::paste;
+
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Normal};
+
+/// A generator of a gaussian (normal) distribution with the given mean and standard deviation.
+#[derive(Debug)]
+pub struct GaussianGenerator {
+    normal: Normal<f64>,
+    rng: Option<Mutex<StdRng>>,
+}
+
+impl GaussianGenerator {
+    /// Create a gaussian generator with the given mean and standard deviation.
+    pub fn new(mean: f64, std_dev: f64) -> Result<Self> {
+        let normal = Normal::new(mean, std_dev)
+            .map_err(|e| anyhow!("invalid gaussian distribution: {e}"))?;
+        Ok(Self { normal, rng: None })
+    }
+}
+
+impl Seedable for GaussianGenerator {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = Some(Mutex::new(rng));
+        self
+    }
+}
+
+impl Generator for GaussianGenerator {
+    type Output = f64;
+
+    fn next(&self) -> Self::Output {
+        match &self.rng {
+            Some(rng) => self.normal.sample(&mut *rng.lock()),
+            None => self.normal.sample(&mut workload_rng()),
+        }
+    }
+}
+
+impl NumberGenerator for GaussianGenerator {
+    fn mean(&self) -> f64 {
+        self.normal.mean()
+    }
+
+    fn variance(&self) -> f64 {
+        self.normal.std_dev().powi(2)
+    }
+}
+
+macro_rules! gaussian {
+    ($( {$type:ty, $name:ident}, )*) => {
+        paste! {
+            $(
+                /// A generator of a gaussian (normal) distribution with the given mean and standard deviation.
+                ///
+                /// The generated values are rounded to the nearest integer, and clamped to
+                /// `[lower_bound, upper_bound]`. Clamping piles the probability out of the bounds onto the bounds.
+                #[derive(Debug)]
+                pub struct [<Gaussian $name Generator>] {
+                    normal: Normal<f64>,
+                    lower_bound: $type,
+                    upper_bound: $type,
+                    rng: Option<Mutex<StdRng>>,
+                }
+
+                impl [<Gaussian $name Generator>] {
+                    /// Create a gaussian generator with the given mean and standard deviation, whose values are clamped
+                    /// to `[lower_bound, upper_bound]`.
+                    pub fn new(mean: f64, std_dev: f64, lower_bound: $type, upper_bound: $type) -> Result<Self> {
+                        if lower_bound > upper_bound {
+                            return Err(anyhow!(
+                                "lower bound {lower_bound} is larger than upper bound {upper_bound}"
+                            ));
+                        }
+                        let normal = Normal::new(mean, std_dev).map_err(|e| anyhow!("invalid gaussian distribution: {e}"))?;
+                        Ok(Self {
+                            normal,
+                            lower_bound,
+                            upper_bound,
+                            rng: None,
+                        })
+                    }
+                }
+
+                impl Seedable for [<Gaussian $name Generator>] {
+                    fn with_rng(mut self, rng: StdRng) -> Self {
+                        self.rng = Some(Mutex::new(rng));
+                        self
+                    }
+                }
+
+                impl Generator for [<Gaussian $name Generator>] {
+                    type Output = $type;
+
+                    fn next(&self) -> Self::Output {
+                        let val = match &self.rng {
+                            Some(rng) => self.normal.sample(&mut *rng.lock()),
+                            None => self.normal.sample(&mut workload_rng()),
+                        }
+                        .round();
+                        val.clamp(self.lower_bound as f64, self.upper_bound as f64) as $type
+                    }
+                }
+
+                impl NumberGenerator for [<Gaussian $name Generator>] {
+                    fn mean(&self) -> f64 {
+                        self.normal.mean()
+                    }
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! for_all_numeric_types {
+    ($macro:ident) => {
+        $macro! {
+            {u8, U8},
+            {u16, U16},
+            {u32, U32},
+            {u64, U64},
+            {usize, Usize},
+            {i8, I8},
+            {i16, I16},
+            {i32, I32},
+            {i64, I64},
+            {isize, Isize},
+        }
+    };
+}
+
+for_all_numeric_types! { gaussian }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_mean_and_variance() {
+        let generator = GaussianGenerator::new(100.0, 10.0)
+            .unwrap()
+            .with_rng(StdRng::seed_from_u64(42));
+        assert_eq!(generator.mean(), 100.0);
+        assert_eq!(generator.variance(), 100.0);
+        let n = 100_000;
+        let vals = (0..n).map(|_| generator.next()).collect::<Vec<_>>();
+        let mean = vals.iter().sum::<f64>() / n as f64;
+        let variance = vals.iter().map(|val| (val - mean).powi(2)).sum::<f64>() / n as f64;
+        assert!((mean - 100.0).abs() < 0.2, "{mean}");
+        assert!((variance - 100.0).abs() < 2.0, "{variance}");
+
+        assert!(GaussianGenerator::new(0.0, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_clamp() {
+        let generator = GaussianUsizeGenerator::new(100.0, 10.0, 0, usize::MAX)
+            .unwrap()
+            .with_rng(StdRng::seed_from_u64(42));
+        assert_eq!(generator.mean(), 100.0);
+        let n = 100_000;
+        let mean = (0..n).map(|_| generator.next() as f64).sum::<f64>() / n as f64;
+        assert!((mean - 100.0).abs() < 0.2, "{mean}");
+
+        // The values out of the bounds are piled onto the bounds.
+        let generator = GaussianI32Generator::new(0.0, 100.0, -10, 10)
+            .unwrap()
+            .with_rng(StdRng::seed_from_u64(42));
+        let vals = (0..1000).map(|_| generator.next()).collect::<Vec<_>>();
+        assert!(vals.iter().all(|val| (-10..=10).contains(val)));
+        assert!(vals.contains(&-10) && vals.contains(&10));
+
+        // A single point.
+        let generator = GaussianU8Generator::new(0.0, 1.0, 7, 7).unwrap();
+        assert!((0..100).all(|_| generator.next() == 7));
+
+        assert!(GaussianUsizeGenerator::new(100.0, 10.0, 10, 0).is_err());
+        assert!(GaussianI64Generator::new(0.0, 1.0, 1, -1).is_err());
+    }
+}
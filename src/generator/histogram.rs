@@ -0,0 +1,200 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng};
+
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
+
+/// The max number of buckets of a histogram file, which bounds the memory taken by a sparse file.
+const MAX_BUCKETS: usize = 1 << 20;
+
+/// Generate a distribution by choosing from a histogram of buckets.
+///
+/// Bucket `i` stands for values of `i * block_size`, and is chosen with the probability of its count over the total
+/// count of all buckets.
+#[derive(Debug)]
+pub struct HistogramGenerator {
+    block_size: usize,
+    /// Cumulative counts of the buckets.
+    cumulative: Vec<u64>,
+    mean: f64,
+    rng: Option<Mutex<StdRng>>,
+}
+
+impl HistogramGenerator {
+    /// Create a histogram generator with the given block size and the count of each bucket.
+    pub fn new(block_size: usize, buckets: Vec<u64>) -> Result<Self> {
+        let mut cumulative = Vec::with_capacity(buckets.len());
+        let mut area = 0;
+        let mut weighted_area = 0.0;
+        for (i, count) in buckets.into_iter().enumerate() {
+            area += count;
+            weighted_area += i as f64 * count as f64;
+            cumulative.push(area);
+        }
+        if area == 0 {
+            return Err(anyhow!("histogram must have at least one non-empty bucket"));
+        }
+
+        let mean = block_size as f64 * weighted_area / area as f64;
+
+        Ok(Self {
+            block_size,
+            cumulative,
+            mean,
+            rng: None,
+        })
+    }
+
+    /// Create a histogram generator from a histogram file.
+    ///
+    /// The first line of the file must be `BlockSize <block size>`, and each of the following lines is
+    /// `<bucket> <count>`. Buckets not listed in the file are empty. The buckets must be less than 2^20.
+    ///
+    /// ```plain
+    /// BlockSize   8
+    /// 0   100
+    /// 1   50
+    /// 4   20
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| anyhow!("failed to open histogram file {}: {e}", path.display()))?;
+        let mut lines = BufReader::new(file).lines();
+
+        let line = lines
+            .next()
+            .ok_or_else(|| anyhow!("empty histogram file {}", path.display()))??;
+        let block_size = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+            ["BlockSize", block_size] => block_size.parse()?,
+            _ => {
+                return Err(anyhow!(
+                    "first line of histogram file {} is not the block size: {line}",
+                    path.display()
+                ))
+            }
+        };
+
+        let mut buckets = vec![];
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let parsed = match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [bucket, count] => bucket.parse::<usize>().ok().zip(count.parse::<u64>().ok()),
+                _ => None,
+            };
+            let Some((bucket, count)) = parsed.filter(|(bucket, _)| *bucket < MAX_BUCKETS) else {
+                return Err(anyhow!(
+                    "malformed line in histogram file {}: {line}",
+                    path.display()
+                ));
+            };
+            if buckets.len() <= bucket {
+                buckets.resize(bucket + 1, 0);
+            }
+            buckets[bucket] = count;
+        }
+
+        Self::new(block_size, buckets)
+    }
+}
+
+impl Seedable for HistogramGenerator {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = Some(Mutex::new(rng));
+        self
+    }
+}
+
+impl Generator for HistogramGenerator {
+    type Output = usize;
+
+    fn next(&self) -> Self::Output {
+        let area = *self.cumulative.last().unwrap();
+        let target = match &self.rng {
+            Some(rng) => rng.lock().gen_range(0..area),
+            None => workload_rng().gen_range(0..area),
+        };
+        let bucket = self.cumulative.partition_point(|&acc| acc <= target);
+        bucket * self.block_size
+    }
+}
+
+impl NumberGenerator for HistogramGenerator {
+    fn mean(&self) -> f64 {
+        self.mean
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    /// Write the histogram file of the test `name`, and create a generator from it.
+    fn from_contents(name: &str, contents: &str) -> Result<HistogramGenerator> {
+        let path =
+            std::env::temp_dir().join(format!("yay-histogram-{name}-{}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let res = HistogramGenerator::from_file(&path);
+        std::fs::remove_file(path).unwrap();
+        res
+    }
+
+    #[test]
+    fn test_from_file() {
+        let generator = from_contents("ok", "BlockSize   8\n0   100\n1   50\n\n4   20\n")
+            .unwrap()
+            .with_rng(StdRng::seed_from_u64(42));
+        assert_eq!(generator.mean(), 8.0 * (50.0 + 4.0 * 20.0) / 170.0);
+        let n = 100_000;
+        let vals = (0..n).map(|_| generator.next()).collect::<Vec<_>>();
+        assert!(vals.iter().all(|val| [0, 8, 32].contains(val)));
+        let mean = vals.iter().sum::<usize>() as f64 / n as f64;
+        assert!((mean - generator.mean()).abs() < 0.1, "{mean}");
+        let zeros = vals.iter().filter(|val| **val == 0).count() as f64 / n as f64;
+        assert!((zeros - 100.0 / 170.0).abs() < 0.01, "{zeros}");
+    }
+
+    #[test]
+    fn test_malformed_file() {
+        for (name, contents) in [
+            ("empty", ""),
+            ("no-block-size", "0 100\n"),
+            ("bad-block-size", "BlockSize x\n0 100\n"),
+            ("extra-column", "BlockSize 8\n0 100 1\n"),
+            ("bad-bucket", "BlockSize 8\nx 100\n"),
+            ("bad-count", "BlockSize 8\n0 -1\n"),
+            ("huge-bucket", "BlockSize 8\n18446744073709551615 1\n"),
+            ("no-count", "BlockSize 8\n0 0\n"),
+        ] {
+            assert!(from_contents(name, contents).is_err(), "{name}");
+        }
+        let e = from_contents("huge", "BlockSize 8\n18446744073709551615 1\n").unwrap_err();
+        assert!(e.to_string().contains("malformed line"), "{e}");
+    }
+}
@@ -0,0 +1,324 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
+
+use paste::paste;
+
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng, RngCore};
+
+macro_rules! hotspot {
+    ($( {$type:ty, $name:ident}, )*) => {
+        paste! {
+            $(
+                /// Generate integers resembling a hotspot distribution where x% of operations access y% of data items.
+                ///
+                /// The parameters specify the bounds for the numbers, the percentage of the interval which comprises the
+                /// hot set and the percentage of operations that access the hot set. Numbers of the hot set are always
+                /// smaller than any number in the cold set. Elements from the hot set and the cold set are chosen using
+                /// a uniform distribution.
+                #[derive(Debug)]
+                pub struct [<Hotspot $name Generator>] {
+                    lower_bound: $type,
+                    hot_interval: u64,
+                    cold_interval: u64,
+                    hot_op_fraction: f64,
+                    rng: Option<Mutex<StdRng>>,
+                }
+
+                impl [<Hotspot $name Generator>] {
+                    /// Create a generator for hotspot distributions.
+                    ///
+                    /// * `lower_bound` - Lower bound of the distribution.
+                    /// * `upper_bound` - Upper bound of the distribution.
+                    /// * `hot_set_fraction` - Percentage of data item.
+                    /// * `hot_op_fraction` - Percentage of operations accessing the hot set.
+                    ///
+                    /// Fractions out of `[0.0, 1.0]` are clamped.
+                    pub fn new(lower_bound: $type, upper_bound: $type, hot_set_fraction: f64, hot_op_fraction: f64) -> Self {
+                        let hot_set_fraction = hot_set_fraction.clamp(0.0, 1.0);
+                        let hot_op_fraction = hot_op_fraction.clamp(0.0, 1.0);
+
+                        let interval = (upper_bound - lower_bound) as u64 + 1;
+                        let hot_interval = (interval as f64 * hot_set_fraction) as u64;
+                        let cold_interval = interval - hot_interval;
+
+                        Self {
+                            lower_bound,
+                            hot_interval,
+                            cold_interval,
+                            hot_op_fraction,
+                            rng: None,
+                        }
+                    }
+
+                    fn sample(&self, rng: &mut impl RngCore) -> $type {
+                        let hot = self.cold_interval == 0
+                            || (self.hot_interval > 0 && rng.gen::<f64>() < self.hot_op_fraction);
+                        let offset = if hot {
+                            rng.gen_range(0..self.hot_interval)
+                        } else {
+                            self.hot_interval + rng.gen_range(0..self.cold_interval)
+                        };
+                        self.lower_bound + offset as $type
+                    }
+                }
+
+                impl Seedable for [<Hotspot $name Generator>] {
+                    fn with_rng(mut self, rng: StdRng) -> Self {
+                        self.rng = Some(Mutex::new(rng));
+                        self
+                    }
+                }
+
+                impl Generator for [<Hotspot $name Generator>] {
+                    type Output = $type;
+
+                    fn next(&self) -> Self::Output {
+                        match &self.rng {
+                            Some(rng) => self.sample(&mut *rng.lock()),
+                            None => self.sample(&mut workload_rng()),
+                        }
+                    }
+                }
+
+                impl NumberGenerator for [<Hotspot $name Generator>] {
+                    fn mean(&self) -> f64 {
+                        let lower_bound = self.lower_bound as f64;
+                        let hot_interval = self.hot_interval as f64;
+                        let cold_interval = self.cold_interval as f64;
+                        // Weight by the probabilities `sample` actually draws with, which ignore `hot_op_fraction`
+                        // if either set is empty.
+                        let hot_fraction = if self.cold_interval == 0 {
+                            1.0
+                        } else if self.hot_interval == 0 {
+                            0.0
+                        } else {
+                            self.hot_op_fraction
+                        };
+                        let hot_mean = lower_bound + (hot_interval - 1.0) / 2.0;
+                        let cold_mean = lower_bound + hot_interval + (cold_interval - 1.0) / 2.0;
+                        hot_fraction * hot_mean + (1.0 - hot_fraction) * cold_mean
+                    }
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! for_all_numeric_types {
+    ($macro:ident) => {
+        $macro! {
+            {u8, U8},
+            {u16, U16},
+            {u32, U32},
+            {u64, U64},
+            {usize, Usize},
+            {i8, I8},
+            {i16, I16},
+            {i32, I32},
+            {i64, I64},
+            {isize, Isize},
+        }
+    };
+}
+
+for_all_numeric_types! { hotspot }
+
+/// When the hot set of [`ShiftingHotspotGenerator`] moves on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftInterval {
+    /// Move the hot set every given number of generated values.
+    Operations(usize),
+    /// Move the hot set every given duration since the generator is created.
+    Time(Duration),
+}
+
+/// Generates a hotspot distribution like [`HotspotUsizeGenerator`], whose hot set slides across the interval over time,
+/// to model the temporal locality shifts, e.g. today's data is hot and last week's is cold.
+///
+/// The values are generated in phases. In phase `p`, the values of [`HotspotUsizeGenerator`] are rotated by `p` times
+/// the size of the hot set within the bounds, so the hot set of each phase follows the previous one, and wraps around to
+/// the lower bound after the upper bound. The phase advances every [`ShiftInterval`].
+///
+/// The phase is kept in a shared atomic counter, see [`ShiftingHotspotGenerator::phase`].
+#[derive(Debug)]
+pub struct ShiftingHotspotGenerator {
+    hotspot: HotspotUsizeGenerator,
+    lower_bound: usize,
+    interval: usize,
+    /// The distance the hot set moves in each phase, which is the size of the hot set.
+    shift: usize,
+    every: ShiftInterval,
+    /// The number of generated values.
+    operations: AtomicUsize,
+    start: Instant,
+    phase: Arc<AtomicUsize>,
+}
+
+impl ShiftingHotspotGenerator {
+    /// Create a generator for hotspot distributions whose hot set moves every `every`.
+    ///
+    /// * `lower_bound` - Lower bound of the distribution.
+    /// * `upper_bound` - Upper bound of the distribution.
+    /// * `hot_set_fraction` - Percentage of data item.
+    /// * `hot_op_fraction` - Percentage of operations accessing the hot set.
+    /// * `every` - When the hot set moves on.
+    ///
+    /// Fractions out of `[0.0, 1.0]` are clamped. A zero interval never moves the hot set.
+    pub fn new(
+        lower_bound: usize,
+        upper_bound: usize,
+        hot_set_fraction: f64,
+        hot_op_fraction: f64,
+        every: ShiftInterval,
+    ) -> Self {
+        let interval = upper_bound - lower_bound + 1;
+        let shift = (interval as f64 * hot_set_fraction.clamp(0.0, 1.0)) as usize;
+        Self {
+            hotspot: HotspotUsizeGenerator::new(0, interval - 1, hot_set_fraction, hot_op_fraction),
+            lower_bound,
+            interval,
+            shift,
+            every,
+            operations: AtomicUsize::new(0),
+            start: Instant::now(),
+            phase: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Get the shared counter of the current phase, e.g. to report when the hot set moves.
+    pub fn phase(&self) -> &Arc<AtomicUsize> {
+        &self.phase
+    }
+
+    /// The phase of the value generated after `operations` values.
+    fn current_phase(&self, operations: usize) -> usize {
+        match self.every {
+            ShiftInterval::Operations(0) => 0,
+            ShiftInterval::Operations(n) => operations / n,
+            ShiftInterval::Time(interval) if interval.is_zero() => 0,
+            ShiftInterval::Time(interval) => {
+                (self.start.elapsed().as_nanos() / interval.as_nanos()) as usize
+            }
+        }
+    }
+}
+
+impl Generator for ShiftingHotspotGenerator {
+    type Output = usize;
+
+    fn next(&self) -> Self::Output {
+        let operations = self.operations.fetch_add(1, Ordering::Relaxed);
+        let phase = self.current_phase(operations);
+        // The phase never goes back, even if the threads race to update it.
+        let phase = self.phase.fetch_max(phase, Ordering::Relaxed).max(phase);
+        let interval = self.interval as u128;
+        let rotation = phase as u128 * self.shift as u128 % interval;
+        self.lower_bound + ((self.hotspot.next() as u128 + rotation) % interval) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    /// The mean of `n` values of the generator.
+    fn sampled_mean(generator: &impl Generator<Output = u64>, n: usize) -> f64 {
+        (0..n).map(|_| generator.next() as f64).sum::<f64>() / n as f64
+    }
+
+    #[test]
+    fn test_mean() {
+        let generator =
+            HotspotU64Generator::new(100, 199, 0.2, 0.8).with_rng(StdRng::seed_from_u64(42));
+        // 0.8 * 109.5 + 0.2 * 159.5
+        assert!((generator.mean() - 119.5).abs() < 1e-9);
+        assert!((sampled_mean(&generator, 100_000) - generator.mean()).abs() < 1.0);
+
+        // All the values are cold without a hot set, whatever the fraction of the hot operations.
+        let generator =
+            HotspotU64Generator::new(100, 199, 0.0, 0.8).with_rng(StdRng::seed_from_u64(42));
+        assert!((generator.mean() - 149.5).abs() < 1e-9);
+        assert!((sampled_mean(&generator, 100_000) - generator.mean()).abs() < 1.0);
+
+        // All the values are hot without a cold set.
+        let generator =
+            HotspotU64Generator::new(100, 199, 1.0, 0.2).with_rng(StdRng::seed_from_u64(42));
+        assert!((generator.mean() - 149.5).abs() < 1e-9);
+        assert!((sampled_mean(&generator, 100_000) - generator.mean()).abs() < 1.0);
+
+        let generator = HotspotU64Generator::new(7, 7, 0.0, 0.8);
+        assert_eq!(generator.mean(), 7.0);
+        assert_eq!(generator.next(), 7);
+    }
+
+    /// The fraction of `n` values of the generator that fall in `range`.
+    fn hot_fraction(
+        generator: &ShiftingHotspotGenerator,
+        n: usize,
+        range: std::ops::Range<usize>,
+    ) -> f64 {
+        (0..n).filter(|_| range.contains(&generator.next())).count() as f64 / n as f64
+    }
+
+    #[test]
+    fn test_shifting_hotspot() {
+        // 90% of the operations go to 10% of the keys, and the hot set moves every 1000 operations.
+        let generator =
+            ShiftingHotspotGenerator::new(1000, 1999, 0.1, 0.9, ShiftInterval::Operations(1000));
+        assert!(hot_fraction(&generator, 1000, 1000..1100) > 0.8);
+        assert_eq!(generator.phase().load(Ordering::Relaxed), 0);
+
+        assert!(hot_fraction(&generator, 1000, 1100..1200) > 0.8);
+        assert_eq!(generator.phase().load(Ordering::Relaxed), 1);
+
+        // The hot set wraps around to the lower bound after the upper bound.
+        for _ in 0..8000 {
+            assert!((1000..2000).contains(&generator.next()));
+        }
+        assert!(hot_fraction(&generator, 1000, 1000..1100) > 0.8);
+        assert_eq!(generator.phase().load(Ordering::Relaxed), 10);
+
+        let generator = ShiftingHotspotGenerator::new(
+            0,
+            999,
+            0.1,
+            0.9,
+            ShiftInterval::Time(Duration::from_millis(50)),
+        );
+        generator.next();
+        std::thread::sleep(Duration::from_millis(60));
+        generator.next();
+        assert!(generator.phase().load(Ordering::Relaxed) >= 1);
+
+        // A zero interval never moves the hot set.
+        let generator =
+            ShiftingHotspotGenerator::new(0, 999, 0.1, 0.9, ShiftInterval::Operations(0));
+        assert!(hot_fraction(&generator, 5000, 0..100) > 0.8);
+        assert_eq!(generator.phase().load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,119 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use rand::rngs::StdRng;
+
+use super::{zipfian::ZipfianUsizeGenerator, Counter, Generator, NumberGenerator, Seedable};
+
+/// Generate a popularity distribution of items, skewed to favor recent items significantly more than older items.
+///
+/// The most recent item is the last value of the basis counter, which is read on every `next()` call.
+#[derive(Debug)]
+pub struct SkewedLatestGenerator<C> {
+    basis: Arc<C>,
+    zipfian: ZipfianUsizeGenerator,
+}
+
+impl<C> SkewedLatestGenerator<C>
+where
+    C: Counter<Output = usize>,
+{
+    /// Create a generator that favors the recent values of the `basis` counter.
+    ///
+    /// The distance from the last value of the basis counter follows a zipfian distribution over `[0, items)`.
+    ///
+    /// Returns an error if `items` is 0.
+    pub fn new(basis: Arc<C>, items: usize) -> Result<Self> {
+        if items == 0 {
+            return Err(anyhow!(
+                "the skewed latest generator needs at least one item"
+            ));
+        }
+        Ok(Self {
+            basis,
+            zipfian: ZipfianUsizeGenerator::new(0, items - 1),
+        })
+    }
+}
+
+impl<C> Seedable for SkewedLatestGenerator<C> {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.zipfian = self.zipfian.with_rng(rng);
+        self
+    }
+}
+
+impl<C> Generator for SkewedLatestGenerator<C>
+where
+    C: Counter<Output = usize>,
+{
+    type Output = usize;
+
+    fn next(&self) -> Self::Output {
+        loop {
+            let max = self.basis.last();
+            if let Some(val) = max.checked_sub(self.zipfian.next()) {
+                return val;
+            }
+        }
+    }
+}
+
+impl<C> NumberGenerator for SkewedLatestGenerator<C>
+where
+    C: Counter<Output = usize>,
+{
+    fn mean(&self) -> f64 {
+        self.basis.last() as f64 - self.zipfian.mean()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{acknowledge::AcknowledgedUsizeCounter, AcknowledgedCounter};
+
+    #[test]
+    fn test_latest() {
+        let counter = Arc::new(AcknowledgedUsizeCounter::new(0));
+        for _ in 0..1000 {
+            let val = counter.next();
+            counter.acknowledge(val);
+        }
+        let generator = SkewedLatestGenerator::new(counter.clone(), 1000).unwrap();
+        let n = 10_000;
+        let recent = (0..n).filter(|_| generator.next() >= 990).count();
+        assert!(recent > n / 3, "{recent}");
+
+        // The basis is read on every draw.
+        for _ in 0..1000 {
+            let val = counter.next();
+            counter.acknowledge(val);
+        }
+        let recent = (0..n).filter(|_| generator.next() >= 1990).count();
+        assert!(recent > n / 3, "{recent}");
+        assert!((0..n).all(|_| generator.next() < 2000));
+    }
+
+    #[test]
+    fn test_items() {
+        let counter = Arc::new(AcknowledgedUsizeCounter::new(0));
+        assert!(SkewedLatestGenerator::new(counter.clone(), 0).is_err());
+        // The unbounded item count doesn't take forever to build.
+        assert!(SkewedLatestGenerator::new(counter, usize::MAX).is_ok());
+    }
+}
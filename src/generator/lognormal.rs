@@ -0,0 +1,122 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
+
+use anyhow::{anyhow, Result};
+
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, LogNormal};
+
+/// A generator of a log-normal distribution, whose logarithm is normally distributed with mean `mu` and standard
+/// deviation `sigma`.
+///
+/// The distribution has a heavy right tail, which models the sizes of objects and blobs better than the uniform and
+/// zipfian distributions. The generated values are rounded to the nearest integer, and clamped to
+/// `[lower_bound, upper_bound]`.
+#[derive(Debug)]
+pub struct LogNormalGenerator {
+    mu: f64,
+    sigma: f64,
+    log_normal: LogNormal<f64>,
+    lower_bound: usize,
+    upper_bound: usize,
+    rng: Option<Mutex<StdRng>>,
+}
+
+impl LogNormalGenerator {
+    /// Create a log-normal generator with the mean `mu` and standard deviation `sigma` of the underlying normal
+    /// distribution, whose values are clamped to `[lower_bound, upper_bound]`.
+    pub fn new(mu: f64, sigma: f64, lower_bound: usize, upper_bound: usize) -> Result<Self> {
+        if lower_bound > upper_bound {
+            return Err(anyhow!(
+                "lower bound {lower_bound} is larger than upper bound {upper_bound}"
+            ));
+        }
+        let log_normal = LogNormal::new(mu, sigma)
+            .map_err(|e| anyhow!("invalid log-normal distribution: {e}"))?;
+        Ok(Self {
+            mu,
+            sigma,
+            log_normal,
+            lower_bound,
+            upper_bound,
+            rng: None,
+        })
+    }
+}
+
+impl Seedable for LogNormalGenerator {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = Some(Mutex::new(rng));
+        self
+    }
+}
+
+impl Generator for LogNormalGenerator {
+    type Output = usize;
+
+    fn next(&self) -> Self::Output {
+        let val = match &self.rng {
+            Some(rng) => self.log_normal.sample(&mut *rng.lock()),
+            None => self.log_normal.sample(&mut workload_rng()),
+        }
+        .round();
+        val.clamp(self.lower_bound as f64, self.upper_bound as f64) as usize
+    }
+}
+
+impl NumberGenerator for LogNormalGenerator {
+    /// The mean of the unclamped distribution.
+    fn mean(&self) -> f64 {
+        (self.mu + self.sigma * self.sigma / 2.0).exp()
+    }
+
+    /// The variance of the unclamped distribution.
+    fn variance(&self) -> f64 {
+        let sigma2 = self.sigma * self.sigma;
+        (sigma2.exp() - 1.0) * (2.0 * self.mu + sigma2).exp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_mean() {
+        let generator = LogNormalGenerator::new(5.0, 0.5, 0, usize::MAX)
+            .unwrap()
+            .with_rng(StdRng::seed_from_u64(42));
+        let n = 100_000;
+        let mean = (0..n).map(|_| generator.next() as f64).sum::<f64>() / n as f64;
+        assert!(
+            (mean - generator.mean()).abs() / generator.mean() < 0.02,
+            "{mean}"
+        );
+
+        // The values are clamped to the bounds.
+        let generator = LogNormalGenerator::new(5.0, 2.0, 10, 1000).unwrap();
+        assert!((0..1000)
+            .map(|_| generator.next())
+            .all(|val| (10..=1000).contains(&val)));
+
+        assert!(LogNormalGenerator::new(5.0, 0.5, 1, 0).is_err());
+        assert!(LogNormalGenerator::new(5.0, f64::NAN, 0, 1).is_err());
+    }
+}
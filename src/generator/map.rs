@@ -0,0 +1,82 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::fmt::Debug;
+
+use super::Generator;
+
+/// A generator that maps the values of the inner generator with a closure.
+///
+/// It is created by [`Generator::map`]. It only implements [`Generator`] but not
+/// [`NumberGenerator`](super::NumberGenerator), because the mean of the mapped values can't be derived from the mean
+/// of the inner generator in general.
+#[derive(Clone)]
+pub struct Map<G, F> {
+    generator: G,
+    f: F,
+}
+
+impl<G, F> Map<G, F> {
+    pub(super) fn new(generator: G, f: F) -> Self {
+        Self { generator, f }
+    }
+
+    /// Get the inner generator.
+    pub fn inner(&self) -> &G {
+        &self.generator
+    }
+}
+
+impl<G, F> Debug for Map<G, F>
+where
+    G: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Map")
+            .field("generator", &self.generator)
+            .finish()
+    }
+}
+
+impl<G, F, U> Generator for Map<G, F>
+where
+    G: Generator,
+    F: Fn(G::Output) -> U + Send + Sync,
+{
+    type Output = U;
+
+    fn next(&self) -> Self::Output {
+        (self.f)(self.generator.next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{constant::ConstantUsizeGenerator, uniform::UniformUsizeGenerator};
+
+    #[test]
+    fn test_map() {
+        let generator = ConstantUsizeGenerator::new(2).map(|v| v * 10);
+        assert_eq!(generator.next(), 20);
+        assert_eq!(generator.inner().next(), 2);
+
+        let generator = UniformUsizeGenerator::new(0, 9).map(|v| format!("user{v}"));
+        for _ in 0..100 {
+            let key = generator.next();
+            let v = key.strip_prefix("user").unwrap().parse::<usize>().unwrap();
+            assert!(v <= 9);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use super::{Generator, NumberGenerator, Seedable};
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rand::{rngs::StdRng, thread_rng, Rng};
+
+use crate::utils::fnvhash64;
+
+/// Rounds of the Feistel network.
+const ROUNDS: usize = 4;
+
+/// Generates each value from start to end (included) exactly once per cycle, in a pseudo-random order.
+///
+/// Unlike [`SequentialUsizeGenerator`](super::sequential::SequentialUsizeGenerator), the order is scrambled, and unlike
+/// [`UniformUsizeGenerator`](super::uniform::UniformUsizeGenerator), no value repeats until all the values of the range
+/// are generated. After that, the same order repeats.
+///
+/// The order is a bijection of the offsets in the range, which is a Feistel network over the smallest domain of an even
+/// number of bits that covers the range. The offsets that are mapped out of the range are mapped again until they fall
+/// in it, which takes less than 4 mappings on average. So the range is never materialized.
+#[derive(Debug)]
+pub struct PermutationUsizeGenerator {
+    start: usize,
+    /// The largest offset from `start`, which is `end - start`.
+    max: u64,
+    /// Bits of each half of the Feistel network domain.
+    half_bits: u32,
+    keys: [u64; ROUNDS],
+    /// Index of the next value in the permutation.
+    index: AtomicU64,
+}
+
+impl PermutationUsizeGenerator {
+    /// Create a new permutation generator of the values from start to end (included), with a random order.
+    ///
+    /// Use [`Seedable::with_rng`] for a reproducible order.
+    pub fn new(start: usize, end: usize) -> Self {
+        debug_assert!(start <= end, "start {start} is larger than end {end}");
+        let max = (end - start) as u64;
+        let bits = u64::BITS - max.leading_zeros();
+        Self {
+            start,
+            max,
+            half_bits: ((bits + 1) / 2).max(1),
+            keys: thread_rng().gen(),
+            index: AtomicU64::new(0),
+        }
+    }
+
+    /// Rewind the generator, so the permutation starts over.
+    ///
+    /// It is safe to call between benchmark phases, but not concurrently with `next()`.
+    pub fn reset(&self) {
+        self.index.store(0, Ordering::Release);
+    }
+
+    /// Map the offset with the Feistel network, the result is a bijection of the domain.
+    fn feistel(&self, offset: u64) -> u64 {
+        let mask = (1u64 << self.half_bits) - 1;
+        let mut left = offset >> self.half_bits;
+        let mut right = offset & mask;
+        for key in self.keys {
+            (left, right) = (right, left ^ (fnvhash64(right ^ key) & mask));
+        }
+        (left << self.half_bits) | right
+    }
+}
+
+impl Seedable for PermutationUsizeGenerator {
+    fn with_rng(mut self, mut rng: StdRng) -> Self {
+        self.keys = rng.gen();
+        self
+    }
+}
+
+impl Generator for PermutationUsizeGenerator {
+    type Output = usize;
+
+    fn next(&self) -> Self::Output {
+        let index = self.index.fetch_add(1, Ordering::Relaxed);
+        // The range covers all the `u64` values if `max + 1` overflows.
+        let mut offset = index.checked_rem(self.max.wrapping_add(1)).unwrap_or(index);
+        // Cycle walking: the bijection of the domain restricted to the range is a bijection of the range.
+        loop {
+            offset = self.feistel(offset);
+            if offset <= self.max {
+                return self.start + offset as usize;
+            }
+        }
+    }
+}
+
+impl NumberGenerator for PermutationUsizeGenerator {
+    fn mean(&self) -> f64 {
+        self.start as f64 + self.max as f64 / 2.0
+    }
+
+    /// The permutation visits each value in `[start, end]` once per cycle, so the variance is the one of the discrete
+    /// uniform distribution.
+    fn variance(&self) -> f64 {
+        let n = self.max as f64 + 1.0;
+        (n * n - 1.0) / 12.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_each_value_once() {
+        for (start, end) in [(0, 0), (10, 10), (5, 104), (0, 1000)] {
+            let generator = PermutationUsizeGenerator::new(start, end);
+            let mut values = (start..=end).map(|_| generator.next()).collect::<Vec<_>>();
+            let order = values.clone();
+            values.sort();
+            assert_eq!(values, (start..=end).collect::<Vec<_>>());
+
+            // The same order repeats.
+            let cycle = (start..=end).map(|_| generator.next()).collect::<Vec<_>>();
+            assert_eq!(cycle, order);
+            generator.reset();
+            assert_eq!(generator.next(), order[0]);
+        }
+
+        // The order is scrambled, and reproducible with a seed.
+        let order = |seed| {
+            let generator =
+                PermutationUsizeGenerator::new(0, 99).with_rng(StdRng::seed_from_u64(seed));
+            (0..100).map(|_| generator.next()).collect::<Vec<_>>()
+        };
+        assert_ne!(order(42), (0..100).collect::<Vec<_>>());
+        assert_eq!(order(42), order(42));
+    }
+}
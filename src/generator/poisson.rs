@@ -0,0 +1,115 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::time::Duration;
+
+use super::{Generator, Seedable};
+use crate::utils::workload_rng;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Exp};
+
+/// Max generated interval, in seconds.
+const MAX_INTERVAL_SECS: f64 = u32::MAX as f64;
+
+/// Generates the inter-arrival intervals of a Poisson arrival process with the given mean rate.
+///
+/// The intervals follow an exponential distribution, sleeping for each generated interval before issuing an operation
+/// produces an open-loop load whose arrivals are independent of the latencies of the operations.
+#[derive(Debug)]
+pub struct PoissonGenerator {
+    rate: f64,
+    exp: Exp<f64>,
+    rng: Option<Mutex<StdRng>>,
+}
+
+impl PoissonGenerator {
+    /// Create a generator of the arrivals with the mean rate of `rate` arrivals per second.
+    pub fn new(rate: f64) -> Result<Self> {
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(anyhow!("invalid arrival rate: {rate}"));
+        }
+        let exp = Exp::new(rate).map_err(|e| anyhow!("invalid exponential distribution: {e}"))?;
+        Ok(Self {
+            rate,
+            exp,
+            rng: None,
+        })
+    }
+
+    /// Create a generator of the arrivals with the given mean interval.
+    pub fn with_mean_interval(interval: Duration) -> Result<Self> {
+        Self::new(1.0 / interval.as_secs_f64())
+    }
+
+    /// Get the mean rate of the arrivals, in arrivals per second.
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Get the mean interval between the arrivals.
+    pub fn mean_interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.rate)
+    }
+}
+
+impl Seedable for PoissonGenerator {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = Some(Mutex::new(rng));
+        self
+    }
+}
+
+impl Generator for PoissonGenerator {
+    type Output = Duration;
+
+    fn next(&self) -> Self::Output {
+        let secs = match &self.rng {
+            Some(rng) => self.exp.sample(&mut *rng.lock()),
+            None => self.exp.sample(&mut workload_rng()),
+        };
+        // The tail of the exponential distribution is unbounded, saturate the extremely rare huge intervals.
+        Duration::from_secs_f64(secs.min(MAX_INTERVAL_SECS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_mean_interval() {
+        const DRAWS: u32 = 100_000;
+
+        let generator = PoissonGenerator::new(1000.0)
+            .unwrap()
+            .with_rng(StdRng::seed_from_u64(0));
+        assert_eq!(generator.mean_interval(), Duration::from_millis(1));
+        let mean = (0..DRAWS).map(|_| generator.next()).sum::<Duration>() / DRAWS;
+        // The standard error of the mean is 1/rate/sqrt(draws), about 0.3% of the mean.
+        let error = (mean.as_secs_f64() - 1.0 / 1000.0).abs() * 1000.0;
+        assert!(error < 0.02, "{mean:?}");
+
+        let generator = PoissonGenerator::with_mean_interval(Duration::from_millis(10)).unwrap();
+        assert!((generator.rate() - 100.0).abs() < 1e-9);
+
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(PoissonGenerator::new(rate).is_err());
+        }
+    }
+}
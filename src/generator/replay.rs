@@ -0,0 +1,156 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+
+use super::Generator;
+
+/// A generator that records the values of the inner generator, so the sequence can be replayed later with a
+/// [`ReplayGenerator`], e.g. against a different backend.
+///
+/// The values are recorded in the order they are returned, the concurrent calls of `next()` are serialized to keep
+/// the order. It is meant for debugging, not for measuring.
+#[derive(Debug)]
+pub struct RecordingGenerator<G>
+where
+    G: Generator,
+{
+    generator: G,
+    values: Mutex<Vec<G::Output>>,
+}
+
+impl<G> RecordingGenerator<G>
+where
+    G: Generator,
+    G::Output: Clone,
+{
+    /// Create a generator that records the values of `generator`.
+    pub fn new(generator: G) -> Self {
+        Self {
+            generator,
+            values: Mutex::new(vec![]),
+        }
+    }
+
+    /// Get the inner generator.
+    pub fn inner(&self) -> &G {
+        &self.generator
+    }
+
+    /// Get a copy of the values recorded so far.
+    pub fn values(&self) -> Vec<G::Output> {
+        self.values.lock().clone()
+    }
+
+    /// Take the values recorded so far, and restart the recording.
+    pub fn take(&self) -> Vec<G::Output> {
+        std::mem::take(&mut *self.values.lock())
+    }
+
+    /// Create a generator that replays the values recorded so far.
+    ///
+    /// Returns an error if no value is recorded yet.
+    pub fn replay(&self) -> Result<ReplayGenerator<G::Output>> {
+        ReplayGenerator::new(self.values())
+    }
+}
+
+impl<G> Generator for RecordingGenerator<G>
+where
+    G: Generator,
+    G::Output: Clone + Send,
+{
+    type Output = G::Output;
+
+    fn next(&self) -> Self::Output {
+        let mut values = self.values.lock();
+        let value = self.generator.next();
+        values.push(value.clone());
+        value
+    }
+}
+
+/// A generator that replays a captured sequence of values, e.g. the values recorded by a [`RecordingGenerator`].
+///
+/// After the last value, the sequence starts over.
+#[derive(Debug)]
+pub struct ReplayGenerator<T> {
+    values: Vec<T>,
+    /// Index of the next value.
+    index: AtomicUsize,
+}
+
+impl<T> ReplayGenerator<T> {
+    /// Create a generator that replays `values` in order.
+    ///
+    /// Returns an error if `values` is empty, in which case there is nothing to replay.
+    pub fn new(values: Vec<T>) -> Result<Self> {
+        if values.is_empty() {
+            return Err(anyhow!("no value to replay"));
+        }
+        Ok(Self {
+            values,
+            index: AtomicUsize::new(0),
+        })
+    }
+
+    /// Rewind the generator, so the sequence starts over.
+    ///
+    /// It is safe to call between benchmark phases, but not concurrently with `next()`.
+    pub fn reset(&self) {
+        self.index.store(0, Ordering::Release);
+    }
+}
+
+impl<T> Generator for ReplayGenerator<T>
+where
+    T: Clone + Send + Sync,
+{
+    type Output = T;
+
+    fn next(&self) -> Self::Output {
+        let index = self.index.fetch_add(1, Ordering::Relaxed);
+        self.values[index % self.values.len()].clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::uniform::UniformUsizeGenerator;
+
+    #[test]
+    fn test_replay() {
+        let recording = RecordingGenerator::new(UniformUsizeGenerator::new(0, 1_000_000));
+        assert!(recording.replay().is_err());
+
+        let drawn = (0..100).map(|_| recording.next()).collect::<Vec<_>>();
+        assert_eq!(recording.values(), drawn);
+
+        let replay = recording.replay().unwrap();
+        let replayed = (0..100).map(|_| replay.next()).collect::<Vec<_>>();
+        assert_eq!(replayed, drawn);
+        // The sequence starts over.
+        assert_eq!(replay.next(), drawn[0]);
+        replay.next();
+        replay.reset();
+        assert_eq!(replay.next(), drawn[0]);
+
+        assert_eq!(recording.take(), drawn);
+        assert!(recording.values().is_empty());
+    }
+}
@@ -0,0 +1,116 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use super::{
+    zipfian::{ZipfianU64Generator, ZIPFIAN_CONSTANT},
+    Generator, NumberGenerator, Seedable,
+};
+
+use paste::paste;
+use rand::rngs::StdRng;
+
+use crate::utils::fnvhash64;
+
+/// Item count of the underlying zipfian generator.
+const ITEM_COUNT: u64 = 10_000_000_000;
+/// Precomputed zeta constant of [`ITEM_COUNT`] items with the default zipfian constant.
+const ZETAN: f64 = 26.46902820178302;
+
+macro_rules! scrambled {
+    ($( {$type:ty, $name:ident}, )*) => {
+        paste! {
+            $(
+                /// A generator of a zipfian distribution. It produces a sequence of items, such that some items are more
+                /// popular than others, according to a zipfian distribution.
+                ///
+                /// Unlike the zipfian generator, popular items are scattered throughout the item space instead of
+                /// clustered together. The items are drawn from a zipfian distribution over a large item count, and then
+                /// hashed with [`fnvhash64`] and folded into `[lower_bound, upper_bound]`.
+                #[derive(Debug)]
+                pub struct [<ScrambledZipfian $name Generator>] {
+                    lower_bound: $type,
+                    upper_bound: $type,
+                    items: u64,
+                    zipfian: ZipfianU64Generator,
+                }
+
+                impl [<ScrambledZipfian $name Generator>] {
+                    /// Create a scrambled zipfian generator for items between `lower_bound` and `upper_bound` (inclusive)
+                    /// with the default zipfian constant.
+                    pub fn new(lower_bound: $type, upper_bound: $type) -> Self {
+                        Self::with_constant(lower_bound, upper_bound, ZIPFIAN_CONSTANT)
+                    }
+
+                    /// Create a scrambled zipfian generator for items between `lower_bound` and `upper_bound` (inclusive)
+                    /// with the given zipfian constant.
+                    pub fn with_constant(lower_bound: $type, upper_bound: $type, zipfian_constant: f64) -> Self {
+                        let items = (upper_bound - lower_bound) as u64 + 1;
+                        let zipfian = if zipfian_constant == ZIPFIAN_CONSTANT {
+                            ZipfianU64Generator::with_zetan(0, ITEM_COUNT - 1, zipfian_constant, ZETAN)
+                        } else {
+                            ZipfianU64Generator::with_constant(0, items - 1, zipfian_constant)
+                        };
+                        Self {
+                            lower_bound,
+                            upper_bound,
+                            items,
+                            zipfian,
+                        }
+                    }
+                }
+
+                impl Seedable for [<ScrambledZipfian $name Generator>] {
+                    fn with_rng(mut self, rng: StdRng) -> Self {
+                        self.zipfian = self.zipfian.with_rng(rng);
+                        self
+                    }
+                }
+
+                impl Generator for [<ScrambledZipfian $name Generator>] {
+                    type Output = $type;
+
+                    fn next(&self) -> Self::Output {
+                        let val = self.zipfian.next();
+                        self.lower_bound + (fnvhash64(val) % self.items) as $type
+                    }
+                }
+
+                impl NumberGenerator for [<ScrambledZipfian $name Generator>] {
+                    fn mean(&self) -> f64 {
+                        (self.lower_bound as f64 + self.upper_bound as f64) / 2.0
+                    }
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! for_all_numeric_types {
+    ($macro:ident) => {
+        $macro! {
+            {u8, U8},
+            {u16, U16},
+            {u32, U32},
+            {u64, U64},
+            {usize, Usize},
+            {i8, I8},
+            {i16, I16},
+            {i32, I32},
+            {i64, I64},
+            {isize, Isize},
+        }
+    };
+}
+
+for_all_numeric_types! { scrambled }
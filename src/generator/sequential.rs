@@ -30,7 +30,8 @@ macro_rules! sequential {
                 pub struct [<Sequential $name Generator>] {
                     start: $type,
                     end: $type,
-                    val: $atype,
+                    /// Offset of the next value from `start`.
+                    offset: $atype,
                 }
 
                 impl [<Sequential $name Generator>] {
@@ -39,17 +40,33 @@ macro_rules! sequential {
                         Self {
                             start,
                             end,
-                            val: $atype::new(start),
+                            offset: $atype::new(0),
                         }
                     }
+
+                    /// Rewind the generator, so the next generated value is `start` again.
+                    ///
+                    /// It is safe to call between benchmark phases, but not concurrently with `next()`.
+                    pub fn reset(&self) {
+                        self.set(self.start);
+                    }
+
+                    /// Jump the generator, so the next generated value is `value`. A value out of `[start, end]` is
+                    /// clamped to the range.
+                    ///
+                    /// It is safe to call between benchmark phases, but not concurrently with `next()`.
+                    pub fn set(&self, value: $type) {
+                        let value = value.clamp(self.start, self.end);
+                        self.offset.store(value - self.start, Ordering::Release);
+                    }
                 }
 
                 impl Generator for [<Sequential $name Generator>] {
                     type Output = $type;
 
                     fn next(&self) -> Self::Output {
-                        let val = self.val.fetch_add(1, Ordering::Relaxed);
-                        self.start + (val % (self.end - self.start + 1))
+                        let offset = self.offset.fetch_add(1, Ordering::Relaxed);
+                        self.start + (offset % (self.end - self.start + 1))
                     }
 
                 }
@@ -58,6 +75,13 @@ macro_rules! sequential {
                     fn mean(&self) -> f64 {
                         (self.start as f64 + self.end as f64) / 2.0
                     }
+
+                    /// The sequence visits each value in `[start, end]` once per cycle, so the variance is the one of
+                    /// the discrete uniform distribution.
+                    fn variance(&self) -> f64 {
+                        let n = self.end as f64 - self.start as f64 + 1.0;
+                        (n * n - 1.0) / 12.0
+                    }
                 }
             )*
         }
@@ -82,3 +106,42 @@ macro_rules! for_all_numeric_types {
 }
 
 for_all_numeric_types! { sequential }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_and_set() {
+        let generator = SequentialUsizeGenerator::new(10, 20);
+        assert_eq!(generator.next(), 10);
+        assert_eq!(generator.next(), 11);
+
+        generator.reset();
+        assert_eq!(generator.next(), 10);
+
+        generator.set(15);
+        assert_eq!(generator.next(), 15);
+
+        // Clamped to the range.
+        generator.set(0);
+        assert_eq!(generator.next(), 10);
+        generator.set(100);
+        assert_eq!(generator.next(), 20);
+        assert_eq!(generator.next(), 10);
+
+        let generator = SequentialI8Generator::new(-5, 5);
+        generator.set(i8::MIN);
+        assert_eq!(generator.next(), -5);
+    }
+
+    #[test]
+    fn test_variance() {
+        let generator = SequentialUsizeGenerator::new(1, 4);
+        let samples = (0..4).map(|_| generator.next() as f64).collect::<Vec<_>>();
+        let mean = samples.iter().sum::<f64>() / 4.0;
+        let variance = samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 4.0;
+        assert_eq!(mean, generator.mean());
+        assert_eq!(variance, generator.variance());
+    }
+}
@@ -12,11 +12,14 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use super::{Generator, NumberGenerator};
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
 
+use anyhow::{anyhow, Result};
 use paste::paste;
 
-use rand::{thread_rng, Rng};
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng};
 
 macro_rules! uniform {
     ($( {$type:ty, $name:ident}, )*) => {
@@ -27,32 +30,89 @@ macro_rules! uniform {
                 pub struct [<Uniform $name Generator>] {
                     lower_bound: $type,
                     upper_bound: $type,
+                    rng: Option<Mutex<StdRng>>,
                 }
 
                 impl [<Uniform $name Generator>] {
                     /// Creates a generator that will return numerics uniformly randomly from the interval
                     /// [lower_bound,upper_bound] inclusive (that is, lower_bound and upper_bound are possible values).
+                    ///
+                    /// `lower_bound` must not be larger than `upper_bound`, use `try_new()` if the bounds are not
+                    /// trusted.
                     pub fn new(lower_bound: $type, upper_bound: $type) -> Self {
+                        debug_assert!(
+                            lower_bound <= upper_bound,
+                            "lower bound {lower_bound} is larger than upper bound {upper_bound}"
+                        );
                         Self {
                             lower_bound,
                             upper_bound,
+                            rng: None,
                         }
                     }
+
+                    /// Like `new()`, but returns an error if `lower_bound` is larger than `upper_bound`.
+                    pub fn try_new(lower_bound: $type, upper_bound: $type) -> Result<Self> {
+                        if lower_bound > upper_bound {
+                            return Err(anyhow!("lower bound {lower_bound} is larger than upper bound {upper_bound}"));
+                        }
+                        Ok(Self::new(lower_bound, upper_bound))
+                    }
+
+                    /// Get the lower bound (inclusive) of the generated values.
+                    pub fn lower_bound(&self) -> $type {
+                        self.lower_bound
+                    }
+
+                    /// Get the upper bound (inclusive) of the generated values.
+                    pub fn upper_bound(&self) -> $type {
+                        self.upper_bound
+                    }
+                }
+
+                impl Seedable for [<Uniform $name Generator>] {
+                    fn with_rng(mut self, rng: StdRng) -> Self {
+                        self.rng = Some(Mutex::new(rng));
+                        self
+                    }
                 }
 
                 impl Generator for [<Uniform $name Generator>] {
                     type Output = $type;
 
                     fn next(&self) -> Self::Output {
-                        thread_rng().gen_range(self.lower_bound..=self.upper_bound)
+                        match &self.rng {
+                            Some(rng) => rng.lock().gen_range(self.lower_bound..=self.upper_bound),
+                            None => workload_rng().gen_range(self.lower_bound..=self.upper_bound),
+                        }
                     }
 
+                    fn next_batch(&self, n: usize, out: &mut Vec<Self::Output>) {
+                        // Lock once for the batch, and sample the same way as `next()`.
+                        let range = self.lower_bound..=self.upper_bound;
+                        match &self.rng {
+                            Some(rng) => {
+                                let mut rng = rng.lock();
+                                out.extend((0..n).map(|_| rng.gen_range(range.clone())));
+                            }
+                            None => {
+                                let mut rng = workload_rng();
+                                out.extend((0..n).map(|_| rng.gen_range(range.clone())));
+                            }
+                        }
+                    }
                 }
 
                 impl NumberGenerator for [<Uniform $name Generator>] {
                     fn mean(&self) -> f64 {
                         (self.lower_bound as f64 + self.upper_bound as f64) / 2.0
                     }
+
+                    fn variance(&self) -> f64 {
+                        // The variance of the discrete uniform distribution over `n` values is `(n^2 - 1) / 12`.
+                        let n = self.upper_bound as f64 - self.lower_bound as f64 + 1.0;
+                        (n * n - 1.0) / 12.0
+                    }
                 }
             )*
         }
@@ -77,3 +137,57 @@ macro_rules! for_all_numeric_types {
 }
 
 for_all_numeric_types! { uniform }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let generator = UniformUsizeGenerator::try_new(3, 7).unwrap();
+        assert_eq!(generator.lower_bound(), 3);
+        assert_eq!(generator.upper_bound(), 7);
+        assert!((0..1000)
+            .map(|_| generator.next())
+            .all(|val| (3..=7).contains(&val)));
+
+        // A single value.
+        let generator = UniformUsizeGenerator::try_new(0, 0).unwrap();
+        assert_eq!(generator.next(), 0);
+
+        assert!(UniformUsizeGenerator::try_new(1, 0).is_err());
+        assert!(UniformI32Generator::try_new(0, -1).is_err());
+    }
+
+    #[test]
+    fn test_next_batch() {
+        let batch = UniformUsizeGenerator::new(0, 1000).with_rng(StdRng::seed_from_u64(42));
+        let single = UniformUsizeGenerator::new(0, 1000).with_rng(StdRng::seed_from_u64(42));
+
+        let mut out = vec![];
+        batch.next_batch(100, &mut out);
+        batch.next_batch(100, &mut out);
+        assert_eq!(out.len(), 200);
+        assert_eq!(out, (0..200).map(|_| single.next()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_variance() {
+        let generator = UniformUsizeGenerator::new(10, 29).with_rng(StdRng::seed_from_u64(42));
+        assert_eq!(generator.variance(), (20.0 * 20.0 - 1.0) / 12.0);
+        assert_eq!(generator.stddev(), generator.variance().sqrt());
+
+        // Monte-Carlo estimate of the variance.
+        let n = 100_000;
+        let samples = (0..n).map(|_| generator.next() as f64).collect::<Vec<_>>();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+        assert!((mean - generator.mean()).abs() < 0.1, "{mean}");
+        assert!(
+            (variance - generator.variance()).abs() / generator.variance() < 0.02,
+            "{variance}"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::fmt::Write;
+
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, RngCore};
+
+use super::{Generator, Seedable};
+use crate::utils::workload_rng;
+
+/// Generates random version 4 UUIDs as keys, in the hyphenated lowercase form, e.g.
+/// `936da01f-9abd-4d9d-80c7-02af85c822a8`.
+///
+/// The keys are not ordered, and there is no way to tell which keys are generated without keeping them.
+#[derive(Debug, Default)]
+pub struct UuidKeyGenerator {
+    rng: Option<Mutex<StdRng>>,
+}
+
+impl UuidKeyGenerator {
+    /// Create a generator of random UUID keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Seedable for UuidKeyGenerator {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = Some(Mutex::new(rng));
+        self
+    }
+}
+
+impl Generator for UuidKeyGenerator {
+    type Output = String;
+
+    fn next(&self) -> Self::Output {
+        let mut bytes = [0u8; 16];
+        match &self.rng {
+            Some(rng) => rng.lock().fill_bytes(&mut bytes),
+            None => workload_rng().fill_bytes(&mut bytes),
+        }
+        // Set the version to 4 (random) and the variant to RFC 4122.
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        let mut key = String::with_capacity(36);
+        for (i, byte) in bytes.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                key.push('-');
+            }
+            write!(&mut key, "{byte:02x}").unwrap();
+        }
+        key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_uuid_key() {
+        let generator = UuidKeyGenerator::new();
+        let keys = (0..1000).map(|_| generator.next()).collect::<HashSet<_>>();
+        assert_eq!(keys.len(), 1000);
+        for key in &keys {
+            assert_eq!(key.len(), 36);
+            let parts = key.split('-').map(str::len).collect::<Vec<_>>();
+            assert_eq!(parts, vec![8, 4, 4, 4, 12]);
+            assert_eq!(&key[14..15], "4");
+            assert!(matches!(&key[19..20], "8" | "9" | "a" | "b"), "{key}");
+        }
+
+        // Seeded generators generate the same keys.
+        let a = UuidKeyGenerator::new().with_rng(StdRng::seed_from_u64(42));
+        let b = UuidKeyGenerator::new().with_rng(StdRng::seed_from_u64(42));
+        assert_eq!(a.next(), b.next());
+    }
+}
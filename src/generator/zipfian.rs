@@ -0,0 +1,294 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use super::{Generator, NumberGenerator, Seedable};
+use crate::utils::workload_rng;
+
+use paste::paste;
+
+use parking_lot::Mutex;
+use rand::{rngs::StdRng, Rng};
+
+/// Default zipfian constant.
+pub const ZIPFIAN_CONSTANT: f64 = 0.99;
+
+/// The item count up to which the zeta constant is summed term by term.
+const EXACT_ZETA_ITEMS: u64 = 1 << 20;
+
+/// Compute the zeta constant needed for the distribution.
+///
+/// Returns the zeta value and the sum of `i * i^-theta`, which is used to compute the mean.
+///
+/// The first [`EXACT_ZETA_ITEMS`] terms are summed exactly, and the rest of the sums are approximated with the
+/// Euler-Maclaurin formula, whose relative error is far below `f64` precision at that point. So it takes constant time
+/// for any item count, e.g. for the unbounded key space of `u64::MAX` items.
+fn zeta(n: u64, theta: f64) -> (f64, f64) {
+    let mut zeta = 0.0;
+    let mut weighted = 0.0;
+    for i in 1..=n.min(EXACT_ZETA_ITEMS) {
+        let p = 1.0 / (i as f64).powf(theta);
+        zeta += p;
+        weighted += i as f64 * p;
+    }
+    if n > EXACT_ZETA_ITEMS {
+        zeta += power_sum_tail(EXACT_ZETA_ITEMS as f64, n as f64, -theta);
+        weighted += power_sum_tail(EXACT_ZETA_ITEMS as f64, n as f64, 1.0 - theta);
+    }
+    (zeta, weighted)
+}
+
+/// Approximate the sum of `i^s` for `i` in `(m, n]` with the first terms of the Euler-Maclaurin formula.
+fn power_sum_tail(m: f64, n: f64, s: f64) -> f64 {
+    let integral = if (s + 1.0).abs() < f64::EPSILON {
+        n.ln() - m.ln()
+    } else {
+        (n.powf(s + 1.0) - m.powf(s + 1.0)) / (s + 1.0)
+    };
+    integral + (n.powf(s) - m.powf(s)) / 2.0 + s * (n.powf(s - 1.0) - m.powf(s - 1.0)) / 12.0
+}
+
+macro_rules! zipfian {
+    ($( {$type:ty, $unsigned:ty, $name:ident}, )*) => {
+        paste! {
+            $(
+                /// A generator of a zipfian distribution. It produces a sequence of items, such that some items are more
+                /// popular than others, according to a zipfian distribution.
+                ///
+                /// Popular items are clustered together, e.g. item 0 is the most popular, item 1 the second most popular,
+                /// and so on.
+                ///
+                /// Implements the algorithm from "Quickly Generating Billion-Record Synthetic Databases",
+                /// Jim Gray et al, SIGMOD 1994.
+                #[derive(Debug)]
+                pub struct [<Zipfian $name Generator>] {
+                    lower_bound: $type,
+                    items: u64,
+                    theta: f64,
+                    alpha: f64,
+                    zetan: f64,
+                    eta: f64,
+                    mean: f64,
+                    rng: Option<Mutex<StdRng>>,
+                }
+
+                impl [<Zipfian $name Generator>] {
+                    /// Create a zipfian generator for items between `lower_bound` and `upper_bound` (inclusive) with
+                    /// the default zipfian constant.
+                    pub fn new(lower_bound: $type, upper_bound: $type) -> Self {
+                        Self::with_constant(lower_bound, upper_bound, ZIPFIAN_CONSTANT)
+                    }
+
+                    /// Create a zipfian generator for items between `lower_bound` and `upper_bound` (inclusive) with
+                    /// the given zipfian constant.
+                    pub fn with_constant(lower_bound: $type, upper_bound: $type, zipfian_constant: f64) -> Self {
+                        let items = Self::items(lower_bound, upper_bound);
+                        let (zetan, weighted) = zeta(items, zipfian_constant);
+                        let mean = lower_bound as f64 + weighted / zetan - 1.0;
+                        Self::build(lower_bound, items, zipfian_constant, zetan, mean)
+                    }
+
+                    /// Create a zipfian generator for items between `lower_bound` and `upper_bound` (inclusive) with
+                    /// the given zipfian constant and a precomputed zeta constant.
+                    ///
+                    /// Skips computing the zeta constant, e.g. to reuse a constant shared by many generators. The mean
+                    /// is approximated with the continuous form of the distribution.
+                    pub fn with_zetan(lower_bound: $type, upper_bound: $type, zipfian_constant: f64, zetan: f64) -> Self {
+                        let items = Self::items(lower_bound, upper_bound);
+                        let weighted = ((items as f64).powf(2.0 - zipfian_constant) - 1.0) / (2.0 - zipfian_constant);
+                        let mean = lower_bound as f64 + weighted / zetan - 1.0;
+                        Self::build(lower_bound, items, zipfian_constant, zetan, mean)
+                    }
+
+                    /// The item count between `lower_bound` and `upper_bound` (inclusive).
+                    ///
+                    /// Panics if `lower_bound` is larger than `upper_bound`, or if there are more than `u64::MAX`
+                    /// items, i.e. the bounds span the full range of a 64-bit type.
+                    fn items(lower_bound: $type, upper_bound: $type) -> u64 {
+                        assert!(
+                            lower_bound <= upper_bound,
+                            "lower bound {lower_bound} is larger than upper bound {upper_bound}"
+                        );
+                        let span = upper_bound.wrapping_sub(lower_bound) as $unsigned as u64;
+                        assert!(
+                            span < u64::MAX,
+                            "the range from {lower_bound} to {upper_bound} has more than u64::MAX items"
+                        );
+                        span + 1
+                    }
+
+                    fn build(lower_bound: $type, items: u64, theta: f64, zetan: f64, mean: f64) -> Self {
+                        let (zeta2theta, _) = zeta(2, theta);
+                        let alpha = 1.0 / (1.0 - theta);
+                        let eta = (1.0 - (2.0 / items as f64).powf(1.0 - theta)) / (1.0 - zeta2theta / zetan);
+
+                        Self {
+                            lower_bound,
+                            items,
+                            theta,
+                            alpha,
+                            zetan,
+                            eta,
+                            mean,
+                            rng: None,
+                        }
+                    }
+                }
+
+                impl Seedable for [<Zipfian $name Generator>] {
+                    fn with_rng(mut self, rng: StdRng) -> Self {
+                        self.rng = Some(Mutex::new(rng));
+                        self
+                    }
+                }
+
+                impl Generator for [<Zipfian $name Generator>] {
+                    type Output = $type;
+
+                    fn next(&self) -> Self::Output {
+                        match &self.rng {
+                            Some(rng) => self.sample(rng.lock().gen()),
+                            None => self.sample(workload_rng().gen()),
+                        }
+                    }
+
+                    fn next_batch(&self, n: usize, out: &mut Vec<Self::Output>) {
+                        // Lock once for the batch, and sample the same way as `next()`.
+                        match &self.rng {
+                            Some(rng) => {
+                                let mut rng = rng.lock();
+                                out.extend((0..n).map(|_| self.sample(rng.gen())));
+                            }
+                            None => {
+                                let mut rng = workload_rng();
+                                out.extend((0..n).map(|_| self.sample(rng.gen())));
+                            }
+                        }
+                    }
+                }
+
+                impl [<Zipfian $name Generator>] {
+                    /// Map a uniform random value in `[0, 1)` to the zipfian distributed value.
+                    fn sample(&self, u: f64) -> $type {
+                        let uz = u * self.zetan;
+
+                        if uz < 1.0 {
+                            return self.lower_bound;
+                        }
+
+                        if uz < 1.0 + 0.5f64.powf(self.theta) {
+                            return self.lower_bound + 1;
+                        }
+
+                        let offset = (self.items as f64 * (self.eta * u - self.eta + 1.0).powf(self.alpha)) as u64;
+                        // The offset may not fit in a signed type, but the sum is within the bounds.
+                        self.lower_bound.wrapping_add(offset.min(self.items - 1) as $type)
+                    }
+                }
+
+                impl NumberGenerator for [<Zipfian $name Generator>] {
+                    fn mean(&self) -> f64 {
+                        self.mean
+                    }
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! for_all_numeric_types {
+    ($macro:ident) => {
+        $macro! {
+            {u8, u8, U8},
+            {u16, u16, U16},
+            {u32, u32, U32},
+            {u64, u64, U64},
+            {usize, usize, Usize},
+            {i8, u8, I8},
+            {i16, u16, I16},
+            {i32, u32, I32},
+            {i64, u64, I64},
+            {isize, usize, Isize},
+        }
+    };
+}
+
+for_all_numeric_types! { zipfian }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn test_zeta_approximation() {
+        // The precomputed zeta constant of the scrambled zipfian generator, summed term by term.
+        let (zetan, _) = zeta(10_000_000_000, ZIPFIAN_CONSTANT);
+        assert!((zetan - 26.46902820178302).abs() < 1e-9, "{zetan}");
+
+        // Just above the exact part, both sums agree with the exact ones.
+        let n = EXACT_ZETA_ITEMS + 1000;
+        let (zetan, weighted) = zeta(n, ZIPFIAN_CONSTANT);
+        let (exact_zetan, exact_weighted) = (1..=n).fold((0.0, 0.0), |(z, w), i| {
+            let p = 1.0 / (i as f64).powf(ZIPFIAN_CONSTANT);
+            (z + p, w + i as f64 * p)
+        });
+        assert!((zetan - exact_zetan).abs() / exact_zetan < 1e-12);
+        assert!((weighted - exact_weighted).abs() / exact_weighted < 1e-12);
+    }
+
+    #[test]
+    fn test_huge_range() {
+        let generator = ZipfianUsizeGenerator::new(0, usize::MAX - 1);
+        let n = 10_000;
+        let low = (0..n).filter(|_| generator.next() < 1_000).count();
+        // About 13% of the draws from `u64::MAX` items fall on the first thousand items, versus none if uniform.
+        assert!(low > n / 10, "{low}");
+    }
+
+    #[test]
+    fn test_full_range() {
+        // 256 items, the most popular one at the lower bound.
+        let generator =
+            ZipfianI8Generator::new(i8::MIN, i8::MAX).with_rng(StdRng::seed_from_u64(42));
+        let values = (0..10_000).map(|_| generator.next()).collect::<Vec<_>>();
+        assert!(values.iter().filter(|&&v| v == i8::MIN).count() > 1000);
+        assert!(values.iter().any(|&v| v > 0));
+
+        // `u64::MAX` items, the most that fit.
+        let generator =
+            ZipfianI64Generator::new(i64::MIN, i64::MAX - 1).with_rng(StdRng::seed_from_u64(42));
+        let low = (0..10_000)
+            .filter(|_| generator.next() < i64::MIN + 1_000)
+            .count();
+        assert!(low > 1_000, "{low}");
+    }
+
+    #[test]
+    #[should_panic(expected = "more than u64::MAX items")]
+    fn test_too_many_items() {
+        ZipfianI64Generator::new(i64::MIN, i64::MAX);
+    }
+
+    #[test]
+    fn test_next_batch() {
+        let batch = ZipfianUsizeGenerator::new(0, 1000).with_rng(StdRng::seed_from_u64(42));
+        let single = ZipfianUsizeGenerator::new(0, 1000).with_rng(StdRng::seed_from_u64(42));
+
+        let mut out = vec![];
+        batch.next_batch(100, &mut out);
+        batch.next_batch(100, &mut out);
+        assert_eq!(out, (0..200).map(|_| single.next()).collect::<Vec<_>>());
+    }
+}
@@ -16,10 +16,20 @@
 
 #![warn(missing_docs)]
 
+/// Multi-threaded benchmark runner.
+pub mod client;
 /// Database traits.
 pub mod db;
 /// Generators and counters.
 pub mod generator;
+/// Rate limiters.
+pub mod limiter;
+/// Latency measurements.
+pub mod measurement;
+/// Java-style properties files.
+pub mod properties;
+/// Status reporting.
+pub mod status;
 /// Utilities.
 pub mod utils;
 /// Benchmark wordloads.
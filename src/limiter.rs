@@ -0,0 +1,144 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens, negative if the tokens are reserved by the waiting callers.
+    tokens: f64,
+    /// The last time the tokens are refilled.
+    refilled: Instant,
+    /// The intended time of the next token. It advances by one interval for each token taken, no matter how late the
+    /// callers are, so it falls behind when the callers can't keep up with the rate.
+    scheduled: Instant,
+}
+
+/// A token bucket rate limiter, which is used to pace the operations to a target throughput.
+///
+/// Each [`RateLimiter::acquire`] call takes a token. If no token is available, the caller reserves the next token and
+/// sleeps until it is refilled, so the pace is kept even if the sleeps oversleep.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Tokens refilled per second, or `None` if unlimited.
+    rate: Option<f64>,
+    /// Max tokens that can be accumulated while idle.
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter that allows `rate` operations per second.
+    ///
+    /// The limiter is unlimited if `rate` is not positive.
+    pub fn new(rate: f64) -> Self {
+        Self::with_burst(rate, 1.0)
+    }
+
+    /// Create a rate limiter that allows `rate` operations per second, with at most `burst` operations that can be
+    /// issued at once after idle.
+    ///
+    /// The limiter is unlimited if `rate` is not positive. `burst` is at least 1.
+    pub fn with_burst(rate: f64, burst: f64) -> Self {
+        let rate = if rate.is_finite() && rate > 0.0 {
+            Some(rate)
+        } else {
+            None
+        };
+        let burst = burst.max(1.0);
+        Self {
+            rate,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled: Instant::now(),
+                scheduled: Instant::now(),
+            }),
+        }
+    }
+
+    /// Create a rate limiter that never blocks.
+    pub fn unlimited() -> Self {
+        Self::new(0.0)
+    }
+
+    /// Returns `true` if the limiter never blocks.
+    pub fn is_unlimited(&self) -> bool {
+        self.rate.is_none()
+    }
+
+    /// Take a token, blocks until the token is available.
+    ///
+    /// Returns the intended start time of the paced operation, as if the operations were issued exactly at the rate
+    /// since the limiter is created. It is earlier than the time the call returns if the callers fall behind the rate,
+    /// e.g. the operations are slower than the rate, which is the queueing delay that YCSB measures as the intended
+    /// latency. The time is now if the limiter is unlimited.
+    pub fn acquire(&self) -> Instant {
+        let Some(rate) = self.rate else {
+            return Instant::now();
+        };
+
+        let (scheduled, wait) = {
+            let mut bucket = self.bucket.lock();
+            let now = Instant::now();
+            let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+            bucket.tokens = (bucket.tokens + elapsed * rate).min(self.burst);
+            bucket.refilled = now;
+            bucket.tokens -= 1.0;
+            let scheduled = bucket.scheduled;
+            bucket.scheduled += Duration::from_secs_f64(1.0 / rate);
+            if bucket.tokens >= 0.0 {
+                return scheduled;
+            }
+            (scheduled, Duration::from_secs_f64(-bucket.tokens / rate))
+        };
+
+        std::thread::sleep(wait);
+        scheduled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate() {
+        const RATE: f64 = 500.0;
+        let duration = Duration::from_secs(1);
+
+        let limiter = RateLimiter::new(RATE);
+        let start = Instant::now();
+        let mut operations = 0;
+        while start.elapsed() < duration {
+            limiter.acquire();
+            operations += 1;
+        }
+        let rate = operations as f64 / start.elapsed().as_secs_f64();
+        assert!((rate - RATE).abs() < RATE * 0.05, "{rate}");
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let limiter = RateLimiter::new(0.0);
+        assert!(limiter.is_unlimited());
+        let start = Instant::now();
+        for _ in 0..1_000_000 {
+            limiter.acquire();
+        }
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
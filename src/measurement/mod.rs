@@ -0,0 +1,331 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use hdrhistogram::Histogram;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+
+use crate::workload::Op;
+
+/// Significant figures of the latency histograms.
+pub(crate) const SIGNIFICANT_FIGURES: u8 = 3;
+
+/// Collects the latencies of the operations, in microseconds.
+///
+/// Each operation is recorded into its own histogram, so the threads measuring different operations don't contend
+/// with each other. The database operations are also recorded into an overall histogram across all of them.
+///
+/// If the operations are paced by a rate limiter, their intended latencies are recorded separately, which are measured
+/// from the intended start time of the schedule instead of the actual start time, see
+/// [`RateLimiter::acquire`](crate::limiter::RateLimiter::acquire).
+#[derive(Debug)]
+pub struct Measurements {
+    histograms: RwLock<BTreeMap<Op, Mutex<Histogram<u64>>>>,
+    /// The latencies of all the database operations, which excludes the read-modify-writes made of them.
+    overall: Mutex<Histogram<u64>>,
+    intended: RwLock<BTreeMap<Op, Mutex<Histogram<u64>>>>,
+    /// When the measurements are created or last reset.
+    created: Mutex<Instant>,
+}
+
+impl Default for Measurements {
+    fn default() -> Self {
+        Self {
+            histograms: RwLock::default(),
+            overall: Mutex::new(new_histogram()),
+            intended: RwLock::default(),
+            created: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl Measurements {
+    /// Create an empty measurements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the latency of an operation.
+    pub fn measure(&self, op: Op, latency: Duration) {
+        let us = micros(latency);
+        if op != Op::ReadModifyWrite {
+            record(&mut self.overall.lock(), us);
+        }
+        record_op(&self.histograms, op, us);
+    }
+
+    /// Record the intended latency of an operation paced by a rate limiter, which is measured from its intended start
+    /// time.
+    pub fn measure_intended(&self, op: Op, latency: Duration) {
+        record_op(&self.intended, op, micros(latency));
+    }
+
+    /// Discard the latencies recorded so far, and restart the runtime.
+    pub fn reset(&self) {
+        self.histograms.write().clear();
+        self.overall.lock().reset();
+        self.intended.write().clear();
+        *self.created.lock() = Instant::now();
+    }
+
+    /// Summarize the latencies recorded so far. The runtime of the report is the elapsed time since the measurements
+    /// are created or last reset.
+    pub fn report(&self) -> Report {
+        let runtime = self.created.lock().elapsed();
+        let operations = report_ops(&self.histograms);
+        let overall = {
+            let overall = self.overall.lock();
+            (!overall.is_empty()).then(|| OperationReport::new(&overall))
+        };
+        let total_operations = operations
+            .iter()
+            .filter(|(op, _)| **op != Op::ReadModifyWrite)
+            .map(|(_, report)| report.count)
+            .sum::<u64>();
+        Report {
+            runtime_ms: runtime.as_millis().min(u64::MAX as u128) as u64,
+            total_operations,
+            throughput: total_operations as f64 / runtime.as_secs_f64().max(f64::EPSILON),
+            operations,
+            overall,
+            intended: report_ops(&self.intended),
+        }
+    }
+}
+
+fn new_histogram() -> Histogram<u64> {
+    Histogram::new(SIGNIFICANT_FIGURES).expect("valid significant figures")
+}
+
+fn micros(latency: Duration) -> u64 {
+    latency.as_micros().min(u64::MAX as u128) as u64
+}
+
+/// Record the value into the histogram of the operation, which is created on the first record.
+fn record_op(histograms: &RwLock<BTreeMap<Op, Mutex<Histogram<u64>>>>, op: Op, value: u64) {
+    if let Some(histogram) = histograms.read().get(&op) {
+        record(&mut histogram.lock(), value);
+        return;
+    }
+
+    let mut histograms = histograms.write();
+    let histogram = histograms
+        .entry(op)
+        .or_insert_with(|| Mutex::new(new_histogram()))
+        .get_mut();
+    record(histogram, value);
+}
+
+fn report_ops(
+    histograms: &RwLock<BTreeMap<Op, Mutex<Histogram<u64>>>>,
+) -> BTreeMap<Op, OperationReport> {
+    histograms
+        .read()
+        .iter()
+        .map(|(op, histogram)| (op.clone(), OperationReport::new(&histogram.lock())))
+        .collect()
+}
+
+/// Record the value, resizing the histogram if needed. Only the values that can't be tracked even after resizing are
+/// clamped.
+pub(crate) fn record(histogram: &mut Histogram<u64>, value: u64) {
+    if histogram.record(value).is_err() {
+        histogram.saturating_record(value);
+    }
+}
+
+/// A copy of a histogram taken at some point, which is not affected by the values recorded afterwards.
+#[derive(Debug, Clone)]
+pub struct HistogramSnapshot {
+    histogram: Histogram<u64>,
+}
+
+impl HistogramSnapshot {
+    pub(crate) fn new(histogram: Histogram<u64>) -> Self {
+        Self { histogram }
+    }
+
+    /// Get the underlying histogram, e.g. to iterate over its buckets.
+    pub fn histogram(&self) -> &Histogram<u64> {
+        &self.histogram
+    }
+
+    /// Count of the recorded values.
+    pub fn count(&self) -> u64 {
+        self.histogram.len()
+    }
+
+    /// Min recorded value.
+    pub fn min(&self) -> u64 {
+        self.histogram.min()
+    }
+
+    /// Max recorded value.
+    pub fn max(&self) -> u64 {
+        self.histogram.max()
+    }
+
+    /// Mean of the recorded values.
+    pub fn mean(&self) -> f64 {
+        self.histogram.mean()
+    }
+
+    /// The recorded value at the given quantile, from 0.0 to 1.0.
+    pub fn value_at_quantile(&self, quantile: f64) -> u64 {
+        self.histogram.value_at_quantile(quantile)
+    }
+}
+
+/// Latency summary of an operation. All latencies are in microseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OperationReport {
+    /// Count of the measured operations.
+    pub count: u64,
+    /// Min latency.
+    pub min: u64,
+    /// Max latency.
+    pub max: u64,
+    /// Mean latency.
+    pub mean: f64,
+    /// 50th percentile latency.
+    pub p50: u64,
+    /// 95th percentile latency.
+    pub p95: u64,
+    /// 99th percentile latency.
+    pub p99: u64,
+    /// 99.9th percentile latency.
+    pub p999: u64,
+}
+
+impl OperationReport {
+    fn new(histogram: &Histogram<u64>) -> Self {
+        Self {
+            count: histogram.len(),
+            min: histogram.min(),
+            max: histogram.max(),
+            mean: histogram.mean(),
+            p50: histogram.value_at_quantile(0.5),
+            p95: histogram.value_at_quantile(0.95),
+            p99: histogram.value_at_quantile(0.99),
+            p999: histogram.value_at_quantile(0.999),
+        }
+    }
+}
+
+/// Latency summary of all the measured operations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    /// Elapsed time of the measurements, in milliseconds.
+    pub runtime_ms: u64,
+    /// Count of the measured database operations. A read-modify-write counts as its read and its update.
+    pub total_operations: u64,
+    /// Aggregate throughput of the measured database operations, in operations per second.
+    pub throughput: f64,
+    /// Latency summary of each operation, only the measured operations are included.
+    pub operations: BTreeMap<Op, OperationReport>,
+    /// Latency summary of all the measured database operations, or `None` if no operation is measured.
+    #[serde(default)]
+    pub overall: Option<OperationReport>,
+    /// Intended latency summary of each operation, measured from the intended start time of the schedule of the rate
+    /// limiter. Empty if the operations are not paced.
+    #[serde(default)]
+    pub intended: BTreeMap<Op, OperationReport>,
+}
+
+impl Report {
+    /// Serialize the report as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("serialize report")
+    }
+
+    /// Write the latency summary as CSV, with a header row and one row for each operation, followed by the `Overall`
+    /// row and the `Intended-{operation}` rows if any.
+    pub fn to_csv(&self, mut writer: impl Write) -> Result<()> {
+        writeln!(writer, "operation,count,min,max,mean,p50,p95,p99,p999")?;
+        let rows = self
+            .operations
+            .iter()
+            .map(|(op, report)| (format!("{op:?}"), report))
+            .chain(
+                self.overall
+                    .iter()
+                    .map(|report| ("Overall".to_string(), report)),
+            )
+            .chain(
+                self.intended
+                    .iter()
+                    .map(|(op, report)| (format!("Intended-{op:?}"), report)),
+            );
+        for (op, report) in rows {
+            writeln!(
+                writer,
+                "{op},{},{},{},{:.3},{},{},{},{}",
+                report.count,
+                report.min,
+                report.max,
+                report.mean,
+                report.p50,
+                report.p95,
+                report.p99,
+                report.p999
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_and_csv() {
+        let measurements = Measurements::new();
+        for i in 1..=100 {
+            measurements.measure(Op::Read, Duration::from_micros(i));
+        }
+        measurements.measure(Op::Update, Duration::from_micros(50));
+        measurements.measure_intended(Op::Read, Duration::from_micros(200));
+        let report = measurements.report();
+        assert_eq!(report.total_operations, 101);
+        assert_eq!(report.operations[&Op::Read].count, 100);
+        assert_eq!(report.overall.as_ref().unwrap().count, 101);
+
+        // The throughput may not round-trip to the last bit through JSON.
+        let json = report.to_json();
+        let mut parsed = serde_json::from_str::<Report>(&json).unwrap();
+        assert!((parsed.throughput - report.throughput).abs() <= report.throughput * 1e-9);
+        parsed.throughput = report.throughput;
+        assert_eq!(parsed, report);
+
+        let mut csv = vec![];
+        report.to_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "operation,count,min,max,mean,p50,p95,p99,p999");
+        // Read, Update, Overall and Intended-Read.
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("Read,100,1,100,"));
+        assert!(lines[3].starts_with("Overall,101,"));
+        assert!(lines[4].starts_with("Intended-Read,1,"));
+        assert!(lines.iter().all(|line| line.split(',').count() == 9));
+    }
+}
@@ -0,0 +1,139 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader, Read},
+};
+
+use anyhow::{anyhow, Result};
+use serde::{
+    de::{value::MapDeserializer, DeserializeOwned, IntoDeserializer, Visitor},
+    forward_to_deserialize_any, Deserializer,
+};
+
+type Error = serde::de::value::Error;
+
+/// Parse the `key=value` lines of a Java-style properties file.
+///
+/// Blank lines and the comment lines starting with `#` or `!` are ignored. The key and the value are separated by the
+/// first `=` or `:`, and trimmed. If a key appears more than once, the last value wins. Line continuations and escapes
+/// are not supported.
+pub fn parse(reader: impl Read) -> Result<BTreeMap<String, String>> {
+    let mut properties = BTreeMap::new();
+    for (index, line) in BufReader::new(reader).lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once(['=', ':'])
+            .ok_or_else(|| anyhow!("invalid property at line {}: {line}", index + 1))?;
+        properties.insert(key.trim().to_string(), value.trim().to_string());
+    }
+    Ok(properties)
+}
+
+/// Deserialize a config from a Java-style properties file, see [`parse`].
+///
+/// The values are parsed into the types of the config fields. The keys that don't match any field are ignored, unless
+/// the config denies unknown fields.
+pub fn from_reader<T: DeserializeOwned>(reader: impl Read) -> Result<T> {
+    let properties = parse(reader)?;
+    let deserializer = MapDeserializer::<_, Error>::new(
+        properties
+            .iter()
+            .map(|(key, value)| (key.as_str(), PropertyValue(value))),
+    );
+    Ok(T::deserialize(deserializer)?)
+}
+
+/// A property value, which is parsed into the type the visitor asks for.
+struct PropertyValue<'a>(&'a str);
+
+impl<'a> IntoDeserializer<'a, Error> for PropertyValue<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($( {$method:ident, $visit:ident}, )*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let value = self
+                    .0
+                    .parse()
+                    .map_err(|e| serde::de::Error::custom(format!("invalid value {:?}: {e}", self.0)))?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for PropertyValue<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    deserialize_parsed! {
+        {deserialize_bool, visit_bool},
+        {deserialize_i8, visit_i8},
+        {deserialize_i16, visit_i16},
+        {deserialize_i32, visit_i32},
+        {deserialize_i64, visit_i64},
+        {deserialize_u8, visit_u8},
+        {deserialize_u16, visit_u16},
+        {deserialize_u32, visit_u32},
+        {deserialize_u64, visit_u64},
+        {deserialize_f32, visit_f32},
+        {deserialize_f64, visit_f64},
+    }
+
+    /// An empty value is `None`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.0.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 char str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
@@ -0,0 +1,260 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use crate::measurement::Measurements;
+
+/// Reports the status of a running benchmark periodically on a background thread.
+///
+/// Each tick logs the total operations, the throughput since the last tick, and the p95/p99 latencies of each
+/// operation so far with `tracing::info!`.
+///
+/// The thread is stopped by [`StatusReporter::stop`], or when the reporter is dropped.
+#[derive(Debug)]
+pub struct StatusReporter {
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl StatusReporter {
+    /// Spawn a background thread that reports the status every `interval`.
+    ///
+    /// * `measurements` - The latency measurements of the operations.
+    /// * `operations` - The count of the completed operations.
+    pub fn spawn(
+        measurements: Arc<Measurements>,
+        operations: Arc<AtomicUsize>,
+        interval: Duration,
+    ) -> Self {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let stopped = stopped.clone();
+            std::thread::Builder::new()
+                .name("yay-status".to_string())
+                .spawn(move || run(&measurements, &operations, interval, &stopped))
+                .expect("spawn status reporter thread")
+        };
+        Self {
+            stopped,
+            handle: Some(handle),
+        }
+    }
+
+    /// Stop the background thread and wait for it to exit.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for StatusReporter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn run(
+    measurements: &Measurements,
+    operations: &AtomicUsize,
+    interval: Duration,
+    stopped: &AtomicBool,
+) {
+    let start = Instant::now();
+    let mut last = (start, 0);
+    let mut tick = start + interval;
+
+    loop {
+        // Park until the next tick, unparking may be spurious.
+        while !stopped.load(Ordering::Acquire) {
+            let now = Instant::now();
+            if now >= tick {
+                break;
+            }
+            std::thread::park_timeout(tick - now);
+        }
+        if stopped.load(Ordering::Acquire) {
+            return;
+        }
+        tick += interval;
+
+        let now = Instant::now();
+        let ops = operations.load(Ordering::Relaxed);
+        let throughput =
+            (ops - last.1) as f64 / now.duration_since(last.0).as_secs_f64().max(f64::EPSILON);
+        last = (now, ops);
+
+        let mut latencies = String::new();
+        for (op, report) in measurements.report().operations {
+            write!(
+                &mut latencies,
+                " [{op:?}: count={}, p95={}us, p99={}us]",
+                report.count, report.p95, report.p99
+            )
+            .unwrap();
+        }
+        tracing::info!(
+            "{} sec: {ops} operations; {throughput:.2} current ops/sec;{latencies}",
+            now.duration_since(start).as_secs()
+        );
+    }
+}
+
+/// Renders a progress bar of the completed operations towards a planned total on a background thread.
+///
+/// The bar is drawn to stderr with `indicatif`, and hidden if stderr is not a terminal. Without the `indicatif` feature,
+/// or if the total is unbounded (`usize::MAX`), the reporter does nothing.
+///
+/// The bar is redrawn in place, so other output to the terminal, e.g. the logs of [`StatusReporter`], may tear it.
+/// Write such output within [`ProgressReporter::suspend`], e.g. from the writer of the `tracing` subscriber.
+///
+/// The thread is stopped by [`ProgressReporter::stop`], or when the reporter is dropped.
+#[derive(Debug)]
+pub struct ProgressReporter {
+    #[cfg(feature = "indicatif")]
+    bar: Option<indicatif::ProgressBar>,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ProgressReporter {
+    /// Spawn a background thread that updates the progress bar every `interval`.
+    ///
+    /// * `operations` - The count of the completed operations. The progress starts from its current value.
+    /// * `total` - The planned count of the operations.
+    pub fn spawn(operations: Arc<AtomicUsize>, total: usize, interval: Duration) -> Self {
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        #[cfg(feature = "indicatif")]
+        if total != usize::MAX {
+            let bar = indicatif::ProgressBar::new(total as u64).with_style(
+                indicatif::ProgressStyle::with_template(
+                    "{elapsed_precise} [{wide_bar}] {pos}/{len} ({per_sec}, eta {eta})",
+                )
+                .expect("valid progress bar template"),
+            );
+            let handle = {
+                let (bar, stopped) = (bar.clone(), stopped.clone());
+                let base = operations.load(Ordering::Relaxed);
+                std::thread::Builder::new()
+                    .name("yay-progress".to_string())
+                    .spawn(move || loop {
+                        let done = operations.load(Ordering::Relaxed) - base;
+                        bar.set_position(done.min(total) as u64);
+                        if stopped.load(Ordering::Acquire) {
+                            bar.finish();
+                            return;
+                        }
+                        std::thread::park_timeout(interval);
+                    })
+                    .expect("spawn progress reporter thread")
+            };
+            return Self {
+                bar: Some(bar),
+                stopped,
+                handle: Some(handle),
+            };
+        }
+
+        let _ = (operations, total, interval);
+        Self {
+            #[cfg(feature = "indicatif")]
+            bar: None,
+            stopped,
+            handle: None,
+        }
+    }
+
+    /// Get the count of the completed operations shown by the progress bar, or 0 if there is no progress bar.
+    pub fn position(&self) -> usize {
+        #[cfg(feature = "indicatif")]
+        if let Some(bar) = &self.bar {
+            return bar.position() as usize;
+        }
+        0
+    }
+
+    /// Hide the progress bar while `f` runs, so the output of `f` to the terminal doesn't tear the bar.
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        #[cfg(feature = "indicatif")]
+        if let Some(bar) = &self.bar {
+            return bar.suspend(f);
+        }
+        f()
+    }
+
+    /// Update the progress bar for the last time, then stop the background thread and wait for it to exit.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            handle.thread().unpark();
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ProgressReporter {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_to_completion() {
+        let operations = Arc::new(AtomicUsize::new(5));
+        let progress = ProgressReporter::spawn(operations.clone(), 100, Duration::from_millis(1));
+        // The progress starts from the current count, and stops at the total.
+        operations.fetch_add(120, Ordering::Relaxed);
+        #[cfg(feature = "indicatif")]
+        {
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            while progress.position() < 100 && std::time::Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            assert_eq!(progress.position(), 100);
+        }
+        #[cfg(not(feature = "indicatif"))]
+        assert_eq!(progress.position(), 0);
+        assert_eq!(progress.suspend(|| 42), 42);
+        progress.stop();
+
+        // Nothing to show for an unbounded total.
+        let progress = ProgressReporter::spawn(operations, usize::MAX, Duration::from_millis(1));
+        assert_eq!(progress.position(), 0);
+        progress.stop();
+    }
+}
@@ -12,16 +12,27 @@
 //  See the License for the specific language governing permissions and
 //  limitations under the License.
 
-use std::io::{Cursor, Read, Write};
+use std::{
+    io::{Cursor, Read},
+    sync::Arc,
+};
 
 use rand::{
     distributions::{Alphanumeric, DistString},
-    thread_rng,
+    rngs::StdRng,
+    RngCore,
 };
 
+use serde::{Deserialize, Serialize};
+
+use crate::generator::Seedable;
+
 const FNV_OFFSET_BASIS_64: u64 = 0xCBF29CE484222325;
 const FNV_PRIME_64: u64 = 1099511628211;
 
+const FNV_OFFSET_BASIS_32: u32 = 0x811C9DC5;
+const FNV_PRIME_32: u32 = 16777619;
+
 /// http://en.wikipedia.org/wiki/Fowler_Noll_Vo_hash
 ///
 /// ```plain
@@ -48,31 +59,264 @@ pub fn fnvhash64(mut val: u64) -> u64 {
     hash
 }
 
+/// The variant of [`fnvhash64`] that hashes a byte string of any length instead of the 8 bytes of an integer.
+pub fn fnvhash64_bytes(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS_64, |hash, &byte| {
+        hash.wrapping_mul(FNV_PRIME_64) ^ byte as u64
+    })
+}
+
+/// Map a record key to one of the `shard_count` shards by the [`fnvhash64_bytes`] of the key, so the same key always
+/// maps to the same shard, e.g. to route the operations of a multi-node backend.
+///
+/// # Panics
+///
+/// Panics if `shard_count` is 0.
+pub fn key_to_shard(key: &str, shard_count: usize) -> usize {
+    assert!(shard_count > 0, "shard count must be positive");
+    (fnvhash64_bytes(key.as_bytes()) % shard_count as u64) as usize
+}
+
+/// The 32-bit variant of [`fnvhash64`], which hashes the 4 bytes of the value in little-endian order.
+pub fn fnvhash32(mut val: u32) -> u32 {
+    let mut hash = FNV_OFFSET_BASIS_32;
+
+    for _ in 0..4 {
+        let byte = val as u8;
+        val >>= 8;
+
+        hash = hash.wrapping_mul(FNV_PRIME_32);
+        hash ^= byte as u32;
+    }
+
+    hash
+}
+
+/// Get the thread-local random number generator of the unseeded generators and values.
+///
+/// It is [`rand::rngs::ThreadRng`] by default, which is cryptographically strong but slower than necessary for a
+/// benchmark. With the `fast-rng` feature, it is a [`SmallRng`](rand::rngs::SmallRng) seeded once per thread from
+/// [`rand::rngs::ThreadRng`], with the same distributions. On x86-64, it draws the uniform values about 1.6x as fast,
+/// and fills the binary random bytes of the values about 2.7x as fast, but the alphanumeric ones only about 1.2x as
+/// fast, which are bound by the sampling.
+#[cfg(not(feature = "fast-rng"))]
+pub(crate) fn workload_rng() -> impl RngCore {
+    rand::thread_rng()
+}
+
+/// See the variant without the `fast-rng` feature.
+#[cfg(feature = "fast-rng")]
+pub(crate) fn workload_rng() -> impl RngCore {
+    FastRng
+}
+
+#[cfg(feature = "fast-rng")]
+thread_local! {
+    static FAST_RNG: std::cell::RefCell<rand::rngs::SmallRng> = std::cell::RefCell::new(
+        rand::SeedableRng::from_rng(rand::thread_rng()).expect("seed the small rng")
+    );
+}
+
+/// A handle of the thread-local [`SmallRng`](rand::rngs::SmallRng).
+#[cfg(feature = "fast-rng")]
+struct FastRng;
+
+#[cfg(feature = "fast-rng")]
+impl RngCore for FastRng {
+    fn next_u32(&mut self) -> u32 {
+        FAST_RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        FAST_RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        FAST_RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        FAST_RNG.with(|rng| rng.borrow_mut().try_fill_bytes(dest))
+    }
+}
+
+/// The hash function to scramble the key numbers with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyHasher {
+    /// [`fnvhash64`].
+    #[default]
+    Fnv64,
+    /// [`fnvhash32`], which only takes the lower 32 bits of the key number and produces a 32-bit key space.
+    Fnv32,
+    /// Keep the key number as it is.
+    Identity,
+}
+
+impl KeyHasher {
+    /// Hash the key number.
+    pub fn hash(&self, val: u64) -> u64 {
+        match self {
+            KeyHasher::Fnv64 => fnvhash64(val),
+            KeyHasher::Fnv32 => fnvhash32(val as u32) as u64,
+            KeyHasher::Identity => val,
+        }
+    }
+
+    /// The number of decimal digits of the largest hashed key number, or 0 if the key numbers are not scrambled.
+    pub fn digits(&self) -> usize {
+        match self {
+            KeyHasher::Fnv64 => u64::MAX.to_string().len(),
+            KeyHasher::Fnv32 => u32::MAX.to_string().len(),
+            KeyHasher::Identity => 0,
+        }
+    }
+}
+
+/// The repeating pattern used to fill the compressible part of [`RandomBytes`].
+const COMPRESSIBLE_PATTERN: &[u8] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ01";
+
 /// Random lazy buf.
+///
+/// The buf is split into blocks of the pattern length. The leading part of each block is random bytes and the rest
+/// is filled with a repeating pattern, so the ratio of random bytes in each block is the requested compressibility.
+///
+/// The random bytes are alphanumeric ASCII by default, or uniformly distributed over all byte values if binary.
 #[derive(Debug, Clone)]
 pub struct RandomBytes {
+    size: usize,
     remaining: usize,
+    /// Offset of the next byte to read.
+    position: usize,
+    /// Random bytes in each block.
+    random_len: usize,
+    binary: bool,
+    rng: Option<Box<StdRng>>,
 }
 
 impl RandomBytes {
     /// Create a new random lazy buf with the given size.
     pub fn new(size: usize) -> Self {
-        Self { remaining: size }
+        Self::new_with_compressibility(size, 1.0)
+    }
+
+    /// Create a new random lazy buf with the given size, whose random bytes take all byte values.
+    pub fn new_binary(size: usize) -> Self {
+        Self::new(size).with_binary(true)
+    }
+
+    /// Create a new random lazy buf with the given size, in which only `ratio` of the bytes are random.
+    ///
+    /// `ratio` is clamped to `[0.0, 1.0]`. `1.0` means fully random, and `0.0` means fully repeated pattern.
+    pub fn new_with_compressibility(size: usize, ratio: f64) -> Self {
+        let ratio = if ratio.is_nan() {
+            1.0
+        } else {
+            ratio.clamp(0.0, 1.0)
+        };
+        let random_len = (ratio * COMPRESSIBLE_PATTERN.len() as f64).round() as usize;
+        Self {
+            size,
+            remaining: size,
+            position: 0,
+            random_len,
+            binary: false,
+            rng: None,
+        }
+    }
+
+    /// Set whether the random bytes take all byte values, or only alphanumeric ASCII.
+    pub fn with_binary(mut self, binary: bool) -> Self {
+        self.binary = binary;
+        self
+    }
+}
+
+impl Seedable for RandomBytes {
+    fn with_rng(mut self, rng: StdRng) -> Self {
+        self.rng = Some(Box::new(rng));
+        self
     }
 }
 
 impl Read for RandomBytes {
-    fn read(&mut self, mut buf: &mut [u8]) -> std::io::Result<usize> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         let len = std::cmp::min(buf.len(), self.remaining);
         self.remaining -= len;
 
-        let s = Alphanumeric.sample_string(&mut thread_rng(), len);
-        let written = buf.write(s.as_bytes())?;
-        assert_eq!(len, written);
+        // Fill segment by segment, a segment ends at the boundary between the random part and the pattern part of a
+        // block, so the position is all it takes to resume from the previous read.
+        let block = COMPRESSIBLE_PATTERN.len();
+        let mut filled = 0;
+        while filled < len {
+            let offset = self.position % block;
+            let (end, random) = if offset < self.random_len {
+                (self.random_len, true)
+            } else {
+                (block, false)
+            };
+            let n = std::cmp::min(end - offset, len - filled);
+            let dst = &mut buf[filled..filled + n];
+            if random && self.binary {
+                match &mut self.rng {
+                    Some(rng) => rng.fill_bytes(dst),
+                    None => workload_rng().fill_bytes(dst),
+                }
+            } else if random {
+                let s = match &mut self.rng {
+                    Some(rng) => Alphanumeric.sample_string(rng.as_mut(), n),
+                    None => Alphanumeric.sample_string(&mut workload_rng(), n),
+                };
+                dst.copy_from_slice(s.as_bytes());
+            } else {
+                dst.copy_from_slice(&COMPRESSIBLE_PATTERN[offset..offset + n]);
+            }
+            filled += n;
+            self.position += n;
+        }
         Ok(len)
     }
 }
 
+/// Lazy buf sliced from a shared corpus, wrapping around at the end of the corpus.
+#[derive(Debug, Clone)]
+pub struct CorpusBytes {
+    data: Arc<[u8]>,
+    offset: usize,
+    size: usize,
+    remaining: usize,
+}
+
+impl CorpusBytes {
+    /// Create a lazy buf of `size` bytes from the corpus, starting at `offset`.
+    pub fn new(data: Arc<[u8]>, offset: usize, size: usize) -> Self {
+        assert!(!data.is_empty() || size == 0);
+        Self {
+            data,
+            offset,
+            size,
+            remaining: size,
+        }
+    }
+}
+
+impl Read for CorpusBytes {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut read = 0;
+        while read < buf.len() && self.remaining > 0 {
+            let len = (buf.len() - read)
+                .min(self.remaining)
+                .min(self.data.len() - self.offset);
+            buf[read..read + len].copy_from_slice(&self.data[self.offset..self.offset + len]);
+            read += len;
+            self.remaining -= len;
+            self.offset = (self.offset + len) % self.data.len();
+        }
+        Ok(read)
+    }
+}
+
 /// Record value type.
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -80,6 +324,83 @@ pub enum Value {
     Deterministic(Cursor<String>),
     /// A random value type with minimum overhead.
     Random(RandomBytes),
+    /// A value type sliced from a corpus of real-world data.
+    Corpus(CorpusBytes),
+    /// A value of raw bytes, e.g. read from the database, which is not necessarily valid UTF-8.
+    Bytes(Cursor<Vec<u8>>),
+}
+
+impl Value {
+    /// Get the length of the bytes that are not read yet, without consuming the value.
+    pub fn len(&self) -> usize {
+        match self {
+            Value::Deterministic(c) => c.get_ref().len().saturating_sub(c.position() as usize),
+            Value::Random(r) => r.remaining,
+            Value::Corpus(c) => c.remaining,
+            Value::Bytes(c) => c.get_ref().len().saturating_sub(c.position() as usize),
+        }
+    }
+
+    /// Returns `true` if all bytes of the value are read.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the original length of the value, no matter how many bytes are read.
+    pub fn total_len(&self) -> usize {
+        match self {
+            Value::Deterministic(c) => c.get_ref().len(),
+            Value::Random(r) => r.size,
+            Value::Corpus(c) => c.size,
+            Value::Bytes(c) => c.get_ref().len(),
+        }
+    }
+
+    /// Read all the remaining bytes of the value.
+    ///
+    /// The inner buffer of a deterministic or raw bytes value is taken without copy if none of its bytes is read.
+    pub fn into_bytes(mut self) -> std::io::Result<Vec<u8>> {
+        match self {
+            Value::Deterministic(c) if c.position() == 0 => Ok(c.into_inner().into_bytes()),
+            Value::Bytes(c) if c.position() == 0 => Ok(c.into_inner()),
+            _ => self.read_all(),
+        }
+    }
+
+    /// Read all the remaining bytes of the value, leaving it empty.
+    pub fn read_all(&mut self) -> std::io::Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(self.len());
+        self.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Check whether the remaining bytes of the value equal `other`, without reading them into a full buffer.
+    ///
+    /// A deterministic or raw bytes value is compared in place and left untouched. Other values are read in small
+    /// chunks until the first mismatch, and the bytes read are consumed.
+    pub fn content_eq(&mut self, other: &[u8]) -> std::io::Result<bool> {
+        if self.len() != other.len() {
+            return Ok(false);
+        }
+        match self {
+            Value::Deterministic(c) => {
+                Ok(&c.get_ref().as_bytes()[c.position() as usize..] == other)
+            }
+            Value::Bytes(c) => Ok(&c.get_ref()[c.position() as usize..] == other),
+            _ => {
+                let mut buf = [0; 4096];
+                let mut other = other;
+                while !other.is_empty() {
+                    let n = self.read(&mut buf[..other.len().min(4096)])?;
+                    if n == 0 || buf[..n] != other[..n] {
+                        return Ok(false);
+                    }
+                    other = &other[n..];
+                }
+                Ok(true)
+            }
+        }
+    }
 }
 
 impl Read for Value {
@@ -87,6 +408,8 @@ impl Read for Value {
         match self {
             Value::Deterministic(c) => c.read(buf),
             Value::Random(r) => r.read(buf),
+            Value::Corpus(c) => c.read(buf),
+            Value::Bytes(c) => c.read(buf),
         }
     }
 }
@@ -97,8 +420,211 @@ impl From<String> for Value {
     }
 }
 
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(Cursor::new(value))
+    }
+}
+
 impl From<RandomBytes> for Value {
     fn from(value: RandomBytes) -> Self {
         Self::Random(value)
     }
 }
+
+impl From<CorpusBytes> for Value {
+    fn from(value: CorpusBytes) -> Self {
+        Self::Corpus(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode each byte that repeats the byte a pattern length earlier as a back-reference, which is a cheap codec for
+    /// the repeated pattern of [`RandomBytes`]. Returns the ratio of the literal bytes.
+    fn literal_ratio(data: &[u8]) -> f64 {
+        let block = COMPRESSIBLE_PATTERN.len();
+        let literals = (0..data.len())
+            .filter(|&i| i < block || data[i] != data[i - block])
+            .count();
+        literals as f64 / data.len() as f64
+    }
+
+    #[test]
+    fn test_compressibility() {
+        for ratio in [0.0, 0.25, 0.5, 1.0] {
+            for binary in [false, true] {
+                let bytes =
+                    RandomBytes::new_with_compressibility(1 << 16, ratio).with_binary(binary);
+                let data = Value::from(bytes).into_bytes().unwrap();
+                assert_eq!(data.len(), 1 << 16);
+                let literals = literal_ratio(&data);
+                // The random bytes repeat the byte a block earlier by chance, at most once in 62 alphanumeric bytes.
+                assert!(
+                    (literals - ratio).abs() < 0.05,
+                    "ratio: {ratio}, binary: {binary}, literals: {literals}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_value_len() {
+        let mut deterministic = Value::from("0123456789".to_string());
+        let mut random = Value::from(RandomBytes::new(10));
+        for value in [&mut deterministic, &mut random] {
+            assert_eq!(value.len(), 10);
+            assert!(!value.is_empty());
+
+            let mut buf = [0; 4];
+            value.read_exact(&mut buf).unwrap();
+            assert_eq!(value.len(), 6);
+            assert_eq!(value.total_len(), 10);
+
+            value.read_all().unwrap();
+            assert_eq!(value.len(), 0);
+            assert!(value.is_empty());
+            assert_eq!(value.total_len(), 10);
+        }
+    }
+
+    #[test]
+    fn test_value_into_bytes() {
+        let value = Value::from("0123456789".to_string());
+        assert_eq!(value.into_bytes().unwrap(), b"0123456789");
+
+        // Only the remaining bytes of a partially read value.
+        let mut value = Value::from("0123456789".to_string());
+        let mut buf = [0; 4];
+        value.read_exact(&mut buf).unwrap();
+        assert_eq!(value.clone().into_bytes().unwrap(), b"456789");
+        assert_eq!(value.read_all().unwrap(), b"456789");
+        assert!(value.read_all().unwrap().is_empty());
+
+        let value = Value::from(RandomBytes::new(10));
+        let bytes = value.into_bytes().unwrap();
+        assert_eq!(bytes.len(), 10);
+        assert!(bytes.iter().all(u8::is_ascii_alphanumeric));
+
+        let mut value = Value::from(RandomBytes::new(10));
+        value.read_exact(&mut buf).unwrap();
+        assert_eq!(value.into_bytes().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_fnvhash32() {
+        // The reference FNV-1 hashes of the 4 little-endian bytes.
+        assert_eq!(fnvhash32(0), 0x4b95f515);
+        assert_eq!(fnvhash32(1), 0x0caf41ca);
+        assert_eq!(fnvhash32(u32::from_le_bytes(*b"abcd")), 0xb9de7375);
+        assert_eq!(fnvhash32(0xdeadbeef), 0x75fc0b5b);
+
+        // The published FNV-1 test vectors.
+        assert_eq!(fnvhash64_bytes(b"a"), 0xaf63bd4c8601b7be);
+        assert_eq!(fnvhash64_bytes(b"foobar"), 0x340d8765a4dda9c2);
+
+        assert_eq!(KeyHasher::Fnv32.hash(0), 0x4b95f515);
+        assert!(KeyHasher::Fnv32.hash(u64::MAX) <= u32::MAX as u64);
+        assert_eq!(KeyHasher::Identity.hash(42), 42);
+    }
+
+    #[test]
+    fn test_binary() {
+        let bytes = Value::from(RandomBytes::new_binary(1024))
+            .into_bytes()
+            .unwrap();
+        assert_eq!(bytes.len(), 1024);
+        assert!(bytes.iter().any(|b| !b.is_ascii()));
+
+        let bytes = Value::from(RandomBytes::new(1024)).into_bytes().unwrap();
+        assert!(bytes.is_ascii());
+    }
+
+    /// The chi-squared statistic of the counts against the uniform distribution.
+    fn chi_squared(counts: &[usize]) -> f64 {
+        let expected = counts.iter().sum::<usize>() as f64 / counts.len() as f64;
+        counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    #[test]
+    fn test_workload_rng_uniformity() {
+        use rand::Rng;
+
+        use crate::generator::{uniform::UniformUsizeGenerator, Generator};
+
+        // The critical value of 9 degrees of freedom at p = 1e-6, so the test hardly ever fails by chance.
+        const CRITICAL: f64 = 45.3;
+
+        let mut rng = workload_rng();
+        let mut counts = [0; 10];
+        for _ in 0..100_000 {
+            counts[rng.gen_range(0..10)] += 1;
+        }
+        assert!(chi_squared(&counts) < CRITICAL, "{counts:?}");
+
+        let generator = UniformUsizeGenerator::new(0, 9);
+        let mut counts = [0; 10];
+        for _ in 0..100_000 {
+            counts[generator.next()] += 1;
+        }
+        assert!(chi_squared(&counts) < CRITICAL, "{counts:?}");
+
+        // Each thread draws its own sequence.
+        let draw = || {
+            (0..4)
+                .map(|_| workload_rng().next_u64())
+                .collect::<Vec<_>>()
+        };
+        let other = std::thread::spawn(draw).join().unwrap();
+        assert_ne!(draw(), other);
+    }
+
+    #[test]
+    fn test_key_to_shard() {
+        // The critical value of 15 degrees of freedom at p = 1e-6.
+        const CRITICAL: f64 = 60.0;
+
+        let mut counts = [0; 16];
+        for i in 0..100_000 {
+            let key = format!("user{i}");
+            let shard = key_to_shard(&key, counts.len());
+            assert_eq!(key_to_shard(&key, counts.len()), shard);
+            counts[shard] += 1;
+        }
+        assert!(chi_squared(&counts) < CRITICAL, "{counts:?}");
+
+        assert_eq!(key_to_shard("user0", 1), 0);
+    }
+
+    #[test]
+    fn test_content_eq() {
+        use rand::SeedableRng;
+
+        let mut value = Value::from("0123456789".to_string());
+        assert!(value.content_eq(b"0123456789").unwrap());
+        assert!(!value.content_eq(b"0123456780").unwrap());
+        assert!(!value.content_eq(b"012345678").unwrap());
+        // Compared in place, so the value is left untouched.
+        assert_eq!(value.len(), 10);
+
+        // Only the remaining bytes are compared.
+        let mut buf = [0; 4];
+        value.read_exact(&mut buf).unwrap();
+        assert!(value.content_eq(b"456789").unwrap());
+
+        // A seeded random value reads the same bytes as its clone.
+        let random = Value::from(RandomBytes::new(10_000).with_rng(StdRng::seed_from_u64(0)));
+        let expected = random.clone().into_bytes().unwrap();
+        assert!(random.clone().content_eq(&expected).unwrap());
+
+        let mut unexpected = expected.clone();
+        unexpected[5_000] ^= 1;
+        assert!(!random.clone().content_eq(&unexpected).unwrap());
+        assert!(!random.clone().content_eq(&expected[1..]).unwrap());
+    }
+}
@@ -0,0 +1,4131 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{Debug, Write},
+    io::Read,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use hdrhistogram::Histogram;
+use itertools::Itertools;
+use parking_lot::{Mutex, RwLock};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{
+    de::{value::MapDeserializer, DeserializeOwned},
+    Deserialize, Serialize,
+};
+
+use anyhow::{anyhow, Result};
+use tracing::{span::EnteredSpan, Level};
+
+use crate::{
+    db::{is_retryable, Capabilities, Db},
+    generator::{
+        acknowledge::AcknowledgedUsizeCounter,
+        constant::ConstantUsizeGenerator,
+        counter::UsizeCounter,
+        discrete::{Choice, DiscreteGenerator},
+        dynamic::DynamicRangeGenerator,
+        exponential::ExponentialUsizeGenerator,
+        file::FileValueGenerator,
+        gaussian::GaussianUsizeGenerator,
+        histogram::HistogramGenerator,
+        hotspot::HotspotUsizeGenerator,
+        latest::SkewedLatestGenerator,
+        lognormal::LogNormalGenerator,
+        sequential::SequentialUsizeGenerator,
+        uniform::UniformUsizeGenerator,
+        uuid::UuidKeyGenerator,
+        zipfian::ZipfianUsizeGenerator,
+        AcknowledgedCounter, Counter, Generator, NumberGenerator, Seedable,
+    },
+    limiter::RateLimiter,
+    measurement::{record, HistogramSnapshot, Measurements, SIGNIFICANT_FIGURES},
+    properties,
+    status::{ProgressReporter, StatusReporter},
+    utils::{workload_rng, KeyHasher, RandomBytes, Value},
+};
+
+/// Operations available for a database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Read operation.
+    Read,
+    /// Update operation.
+    Update,
+    /// Insert operation.
+    Insert,
+    /// Scan operation.
+    Scan,
+    /// Delete operation.
+    Delete,
+}
+
+/// Internal operations.
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Op {
+    Read,
+    Update,
+    Insert,
+    Scan,
+    ReadModifyWrite,
+    Delete,
+    /// The read back of a write, if `verify_writes` is enabled.
+    VerifyRead,
+}
+
+/// One experiment scenario. One object of this type will
+/// be instantiated and shared among all client threads.
+///
+/// If you implement this class, you should support the "insertstart" property. This
+/// allows the Client to proceed from multiple clients on different machines, in case
+/// the client is the bottleneck. For example, if we want to load 1 million records from
+/// 2 machines, the first machine should have insertstart=0 and the second insertstart=500000. Additionally,
+/// the "insertcount" property, which is interpreted by Client, can be used to tell each instance of the
+/// client how many inserts to do. In the example above, both clients should have insertcount=500000.
+pub trait Workload {
+    /// The configuration type for the workload.
+    type Config: Serialize + DeserializeOwned + Debug + Clone;
+
+    /// Create a new workload with the given config.
+    fn new(config: Self::Config) -> Result<Self>
+    where
+        Self: Sized;
+
+    /// Initialize any shared state for this workload, e.g. allocate the resources shared by the client threads.
+    /// Called once before the first phase.
+    fn init(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Cleanup any shared state for this workload. Called once after the last phase.
+    fn cleanup(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// An operation planned by [`CoreWorkload::plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedOp {
+    /// The operation to do.
+    pub op: Op,
+    /// The table to operate on.
+    pub table: String,
+    /// The key of the record, or the start key of a scan.
+    pub key: String,
+    /// The fields to read, empty for all the fields.
+    pub read_fields: Vec<String>,
+    /// The fields to write with the lengths of their values.
+    pub written_fields: Vec<(String, usize)>,
+    /// The number of records to scan, 0 if not a scan.
+    pub scan_length: usize,
+}
+
+/// A phase of the transactions with its own operation proportions, see `phases` of [`CoreWorkloadConfig`].
+///
+/// The proportions that are not set are 0.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PhaseConfig {
+    /// The number of transactions of the phase, of all the client threads in total. Ignored for the last phase, which
+    /// lasts until the end of the run.
+    #[serde(default)]
+    pub operation_count: usize,
+    /// Proportion of transactions that are reads.
+    #[serde(default)]
+    pub read_proportion: f64,
+    /// Proportion of transactions that are updates.
+    #[serde(default)]
+    pub update_proportion: f64,
+    /// Proportion of transactions that are inserts.
+    #[serde(default)]
+    pub insert_proportion: f64,
+    /// Proportion of transactions that are scans.
+    #[serde(default)]
+    pub scan_proportion: f64,
+    /// Proportion of transactions that are read-modify-writes.
+    #[serde(default)]
+    pub read_modify_write_proportion: f64,
+    /// Proportion of transactions that are deletes.
+    #[serde(default)]
+    pub delete_proportion: f64,
+}
+
+impl PhaseConfig {
+    fn proportions(&self) -> [(Op, f64); 6] {
+        [
+            (Op::Read, self.read_proportion),
+            (Op::Update, self.update_proportion),
+            (Op::Insert, self.insert_proportion),
+            (Op::Scan, self.scan_proportion),
+            (Op::ReadModifyWrite, self.read_modify_write_proportion),
+            (Op::Delete, self.delete_proportion),
+        ]
+    }
+}
+
+/// Configuration for the [`CoreWorkload`].
+///
+/// The fields also accept the canonical YCSB property names as aliases, see
+/// [`CoreWorkloadConfig::from_properties`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CoreWorkloadConfig {
+    /// The name of the database table to run queries against.
+    #[serde(default = "default::table")]
+    table: String,
+    /// The number of tables to spread the records across.
+    ///
+    /// If larger than 1, the tables are named `{table}{i}`, and each record lives in the table picked by its key
+    /// number, so all the operations on a record go to the same table.
+    #[serde(default = "default::table_count")]
+    table_count: usize,
+    /// The number of fields in a record.
+    #[serde(default = "default::fields", alias = "fieldcount")]
+    fields: usize,
+    /// Field name prefix.
+    #[serde(default = "default::field_name_prefix", alias = "fieldnameprefix")]
+    field_name_prefix: String,
+    /// Field length distribution.
+    ///
+    /// Options are "constant", "uniform", "zipfian", "histogram", "gaussian" and "lognormal".
+    ///
+    /// If "constant", only the `max_field_length` will be used.
+    ///
+    /// If "gaussian", the mean is the midpoint of `min_field_length` and `max_field_length`, and the standard deviation
+    /// is a sixth of the range, so 99.7% of the lengths fall in the range. The others are clamped to the range.
+    ///
+    /// If "lognormal", the logarithms of the lengths follow the same rule in the log scale, so the median is the
+    /// geometric mean of `min_field_length` (at least 1) and `max_field_length`, and the lengths have a heavy right tail
+    /// within the range.
+    #[serde(
+        default = "default::field_length_distribution",
+        alias = "fieldlengthdistribution"
+    )]
+    field_length_distribution: String,
+    /// Min field length.
+    #[serde(default = "default::min_field_length", alias = "minfieldlength")]
+    min_field_length: usize,
+    /// Max field length.
+    #[serde(default = "default::max_field_length", alias = "fieldlength")]
+    max_field_length: usize,
+    /// The field length distributions of specific fields, which override `field_length_distribution`.
+    ///
+    /// Maps the field name to the distribution, the min field length and the max field length, e.g.
+    /// `{"field0": ["constant", 0, 16]}`.
+    #[serde(default = "default::field_length_overrides")]
+    field_length_overrides: HashMap<String, (String, usize, usize)>,
+    /// The filename containing a field length histogram.
+    ///
+    /// Only used if field length distribution is "histogram".
+    #[serde(
+        default = "default::field_length_histogram_file",
+        alias = "fieldlengthhistogram"
+    )]
+    field_length_histogram_file: String,
+    /// The filename of a corpus of real-world data to slice the field values from.
+    ///
+    /// If empty, the field values are synthetic random bytes. Not used if data integrity is checked.
+    #[serde(default = "default::field_value_source")]
+    field_value_source: String,
+    /// The ratio of random bytes in the synthetic field values, from 0.0 to 1.0.
+    ///
+    /// The rest of the bytes are a repeating pattern, so lower values generate more compressible data. Not used if the
+    /// field values are sliced from a corpus.
+    #[serde(default = "default::compressibility")]
+    compressibility: f64,
+    /// Whether the random bytes of the synthetic field values take all byte values, or only alphanumeric ASCII.
+    ///
+    /// Binary values exercise the binary-safe storage paths of the backends. Not used if the field values are sliced
+    /// from a corpus.
+    #[serde(default = "default::binary_values")]
+    binary_values: bool,
+    /// Whether to record the lengths of the generated field values into a histogram.
+    ///
+    /// It is a diagnostic to verify that the field length distribution generates the intended lengths, see
+    /// [`CoreWorkload::field_length_stats`].
+    #[serde(default = "default::field_length_stats")]
+    field_length_stats: bool,
+    /// The number of records to load into the database initially.
+    #[serde(default = "default::record_count", alias = "recordcount")]
+    record_count: usize,
+    /// The distribution of requests across the keyspace.
+    ///
+    /// Options are "uniform", "zipfian", "sequential", "exponential", "hotspot" and "latest".
+    ///
+    /// If "zipfian", the popular keys are scattered throughout the keyspace instead of clustered together.
+    ///
+    /// If "exponential" or "latest", the recently inserted keys are more popular than the older ones.
+    #[serde(
+        default = "default::request_distribution",
+        alias = "requestdistribution"
+    )]
+    request_distribution: String,
+    /// The percentile of the requests that fall in the most recent `exponential_frac` portion of the keyspace.
+    ///
+    /// Only used if request distribution is "exponential".
+    #[serde(
+        default = "default::exponential_percentile",
+        alias = "exponential.percentile"
+    )]
+    exponential_percentile: f64,
+    /// The fraction of the keyspace that `exponential_percentile` percent of the requests fall in.
+    ///
+    /// Only used if request distribution is "exponential".
+    #[serde(default = "default::exponential_frac", alias = "exponential.frac")]
+    exponential_frac: f64,
+    /// The fraction of the keyspace that makes up the hot set.
+    ///
+    /// Only used if request distribution is "hotspot".
+    #[serde(
+        default = "default::hotspot_data_fraction",
+        alias = "hotspotdatafraction"
+    )]
+    hotspot_data_fraction: f64,
+    /// The fraction of the requests that access the hot set.
+    ///
+    /// Only used if request distribution is "hotspot".
+    #[serde(
+        default = "default::hotspot_operation_fraction",
+        alias = "hotspotopnfraction"
+    )]
+    hotspot_operation_fraction: f64,
+    /// The scan length distribution.
+    ///
+    /// Options are "uniform", "zipfian" and "constant". If "constant", every scan requests `max_scan_length` records
+    /// and `min_scan_length` is ignored.
+    #[serde(
+        default = "default::scan_length_distribution",
+        alias = "scanlengthdistribution"
+    )]
+    scan_length_distribution: String,
+    /// The min scan length (number of records).
+    #[serde(default = "default::min_scan_length", alias = "minscanlength")]
+    min_scan_length: usize,
+    /// The max scan length (number of records).
+    #[serde(default = "default::max_scan_length", alias = "maxscanlength")]
+    max_scan_length: usize,
+    /// The `insert_start` property allows the client to proceed from multiple clients on different machines, in case the client is
+    /// the bottleneck.
+    ///
+    /// For example, if we want to load 1 million records from 2 machines, the first machine should have insertstart=0
+    /// and the second insertstart=500000.
+    ///
+    /// Additionally, the "insert_count" property, which is interpreted by client, can be used to tell each instance of
+    /// the client how many inserts to do. In the example above, both clients should have insert_count as 500000.
+    /// [`shard_insert_range`] computes both properties of each client.
+    #[serde(default = "default::insert_start", alias = "insertstart")]
+    insert_start: usize,
+    /// The number of records to insert by this client instance, see `insert_start`.
+    ///
+    /// If not set, all the records from `insert_start` up to `record_count` are inserted. It must be positive, the
+    /// transactions choose their keys from the inserted records.
+    #[serde(default = "default::insert_count", alias = "insertcount")]
+    insert_count: Option<usize>,
+    /// The number of transactions of the run phase, of all the client threads in total.
+    ///
+    /// The run phase may stop earlier at the max duration of the [`Client`](crate::client::Client), when the client is
+    /// stopped or when `max_bytes_written` is reached.
+    #[serde(default = "default::operation_count", alias = "operationcount")]
+    operation_count: usize,
+    /// The prefix of the record keys, which is prepended to the padded record numbers, e.g. `user00000005`.
+    #[serde(default = "default::key_prefix", alias = "keyprefix")]
+    key_prefix: String,
+    /// The format of the record keys after the prefix.
+    ///
+    /// Options are "numeric" and "uuid".
+    ///
+    /// If "numeric", the keys are the padded record numbers, see `insert_order` and `zero_padding`.
+    ///
+    /// If "uuid", the keys are random UUIDs, which can't be derived from the record numbers. The workload keeps a
+    /// uniform sample of at most `max_tracked_keys` inserted keys, and the request distribution chooses from the
+    /// sampled keys. So the load and the run phases must be done by the same workload instance.
+    #[serde(default = "default::key_format")]
+    key_format: String,
+    /// The max number of inserted keys to keep for choosing the requested keys, if `key_format` is "uuid".
+    ///
+    /// It takes about 100 bytes of memory for each key, 100 MB for the default one million keys. Once it is reached, a
+    /// new key replaces a random kept one with the probability that keeps the sample uniform.
+    #[serde(default = "default::max_tracked_keys")]
+    max_tracked_keys: usize,
+    /// Adding zero padding to record numbers in order to match string sort order.
+    /// Controls the number of 0s to left pad with.
+    ///
+    /// If the insert order is "hashed", the key numbers are padded to at least the digits of the largest hashed key
+    /// number of `key_hasher`, so all the keys have the same length and their string order matches the order of the
+    /// hashed key numbers.
+    #[serde(default = "default::zero_padding", alias = "zeropadding")]
+    zero_padding: usize,
+    /// Deciding whether to read one field (false) or all fields (true) of a record.
+    #[serde(default = "default::read_all_fields", alias = "readallfields")]
+    read_all_fields: bool,
+    /// The name of the property for determining how to read all the fields when `read_all_fields` is `true`.
+    ///
+    /// If set to `true`, all the field names will be passed into the underlying client. If set to `false`,
+    /// an empty set will be passed into the underlying client, which means all the fields, see [`Db::read`]. When passed
+    /// an empty set, some clients may retrieve the entire row with a wildcard, which may be slower than naming all the
+    /// fields.
+    #[serde(
+        default = "default::read_all_fields_by_name",
+        alias = "readallfieldsbyname"
+    )]
+    read_all_fields_by_name: bool,
+    /// Deciding whether an update writes all fields (true) of a record, or the fields chosen by
+    /// `fields_to_write_distribution` (false), which is one field by default.
+    #[serde(default = "default::write_all_fields", alias = "writeallfields")]
+    write_all_fields: bool,
+    /// The distribution of the number of distinct fields that an update writes, if not `write_all_fields`.
+    ///
+    /// Options are "constant", "uniform" and "zipfian". If "constant", only the `max_fields_to_write` will be used.
+    ///
+    /// The fields not written keep their values, which are still verified by the data integrity check.
+    #[serde(default = "default::fields_to_write_distribution")]
+    fields_to_write_distribution: String,
+    /// The min number of fields that an update writes, at least 1.
+    #[serde(default = "default::min_fields_to_write")]
+    min_fields_to_write: usize,
+    /// The max number of fields that an update writes, at most `fields`.
+    #[serde(default = "default::max_fields_to_write")]
+    max_fields_to_write: usize,
+    /// Deciding whether to check all returned data against the formation template to ensure data integrity.
+    ///
+    /// The values embed their lengths, so the check works with any field length distribution.
+    #[serde(default = "default::data_integrity", alias = "dataintegrity")]
+    data_integrity: bool,
+    /// Deciding whether to read each inserted or updated record back right after the write and check that the written
+    /// values are there (read-your-writes).
+    ///
+    /// It is stricter than `data_integrity`, which only checks the records read by the transactions, and catches lost
+    /// writes of distributed stores. The reads back are measured as `VerifyRead`, apart from the reads of the workload.
+    #[serde(default = "default::verify_writes")]
+    verify_writes: bool,
+    /// The order to insert records. Options are "ordered" or "hashed".
+    #[serde(default = "default::insert_order", alias = "insertorder")]
+    insert_order: String,
+    /// The hash function to scramble the key numbers with if the insert order is "hashed". Options are "fnv64",
+    /// "fnv32" or "identity".
+    #[serde(default = "default::key_hasher", alias = "keyhasher")]
+    key_hasher: KeyHasher,
+    /// Proportion of transactions that are reads.
+    #[serde(default = "default::read_proportion", alias = "readproportion")]
+    read_proportion: f64,
+    /// Proportion of transactions that are updates.
+    #[serde(default = "default::update_proportion", alias = "updateproportion")]
+    update_proportion: f64,
+    /// Proportion of transactions that are inserts.
+    #[serde(default = "default::insert_proportion", alias = "insertproportion")]
+    insert_proportion: f64,
+    /// Proportion of transactions that are scans.
+    #[serde(default = "default::scan_proportion", alias = "scanproportion")]
+    scan_proportion: f64,
+    /// Proportion of transactions that are read-modify-writes.
+    #[serde(
+        default = "default::read_modify_write_proportion",
+        alias = "readmodifywriteproportion"
+    )]
+    read_modify_write_proportion: f64,
+    /// Proportion of transactions that are deletes.
+    #[serde(default = "default::delete_proportion", alias = "deleteproportion")]
+    delete_proportion: f64,
+    /// The phases of the transactions, each with its own operation proportions, to switch from mostly inserts to mostly
+    /// reads within one run, e.g. `[{"operation_count": 1000, "insert_proportion": 0.9, "read_proportion": 0.1},
+    /// {"read_proportion": 0.9, "update_proportion": 0.1}]`.
+    ///
+    /// The phases run in order, each for its `operation_count` transactions, counting the transactions of all the client
+    /// threads and all the phases of the client. The last phase lasts until the end. If there is any phase, the
+    /// proportions above are ignored. Empty by default, and can't be set in a properties file.
+    #[serde(default = "default::phases")]
+    phases: Vec<PhaseConfig>,
+    /// How many times to retry when insertion of a single item to a DB fails, in the load phase or the transaction
+    /// phase.
+    ///
+    /// Unlike YCSB, only the transient errors and the conflicts are retried, by all the retry limits. The other errors
+    /// fail at once, see [`is_retryable`].
+    #[serde(
+        default = "default::insertion_retry_limit",
+        alias = "core_workload_insertion_retry_limit"
+    )]
+    insertion_retry_limit: usize,
+    /// On average, how long to wait before the first retry, in seconds.
+    ///
+    /// The wait doubles with each retry until it reaches `retry_max_interval`, and is jittered by ±20%.
+    #[serde(
+        default = "default::insertion_retry_interval",
+        alias = "core_workload_insertion_retry_interval"
+    )]
+    insertion_retry_interval: usize,
+    /// On average, the longest wait between the retries of all the operations, in seconds.
+    #[serde(
+        default = "default::retry_max_interval",
+        alias = "insertion_retry_max_interval"
+    )]
+    retry_max_interval: usize,
+    /// How many times to retry when one of the reads of the transaction phase fails.
+    #[serde(default = "default::retry_limit")]
+    read_retry_limit: usize,
+    /// On average, how long to wait before the first retry of the reads, in seconds. See `insertion_retry_interval`.
+    #[serde(default = "default::retry_interval")]
+    read_retry_interval: usize,
+    /// How many times to retry when one of the updates of the transaction phase fails.
+    #[serde(default = "default::retry_limit")]
+    update_retry_limit: usize,
+    /// On average, how long to wait before the first retry of the updates, in seconds. See `insertion_retry_interval`.
+    #[serde(default = "default::retry_interval")]
+    update_retry_interval: usize,
+    /// How many times to retry when one of the scans of the transaction phase fails.
+    #[serde(default = "default::retry_limit")]
+    scan_retry_limit: usize,
+    /// On average, how long to wait before the first retry of the scans, in seconds. See `insertion_retry_interval`.
+    #[serde(default = "default::retry_interval")]
+    scan_retry_interval: usize,
+    /// How many times to retry when one of the read-modify-writes of the transaction phase fails.
+    #[serde(default = "default::retry_limit")]
+    read_modify_write_retry_limit: usize,
+    /// On average, how long to wait before the first retry of the read-modify-writes, in seconds. See `insertion_retry_interval`.
+    #[serde(default = "default::retry_interval")]
+    read_modify_write_retry_interval: usize,
+    /// How many times to retry when one of the deletes of the transaction phase fails.
+    #[serde(default = "default::retry_limit")]
+    delete_retry_limit: usize,
+    /// On average, how long to wait before the first retry of the deletes, in seconds. See `insertion_retry_interval`.
+    #[serde(default = "default::retry_interval")]
+    delete_retry_interval: usize,
+    /// The seed of the random number generators.
+    ///
+    /// If set, all the generators, the random values, the sample of the tracked keys and the jitter of the retry backoff
+    /// draw from random number generators derived from the seed, so reruns with a single client thread produce
+    /// identical operation sequences.
+    #[serde(default = "default::seed")]
+    seed: Option<u64>,
+    /// The target throughput of all the client threads in total, in operations per second.
+    ///
+    /// If 0, the operations are issued as fast as possible.
+    #[serde(default = "default::target_throughput", alias = "target")]
+    target_throughput: usize,
+    /// The distribution of the think time, in milliseconds, that each client thread sleeps after a transaction to
+    /// model the pacing of the clients.
+    ///
+    /// Options are "constant", "uniform" and "exponential".
+    ///
+    /// If "constant", only the `max_think_time` will be used.
+    ///
+    /// If "exponential", the mean is the midpoint of `min_think_time` and `max_think_time`, and the think time is not
+    /// bounded.
+    #[serde(default = "default::think_time_distribution")]
+    think_time_distribution: String,
+    /// The min think time in milliseconds.
+    #[serde(default = "default::min_think_time")]
+    min_think_time: usize,
+    /// The max think time in milliseconds. No think time by default.
+    #[serde(default = "default::max_think_time")]
+    max_think_time: usize,
+    /// The max bytes of the field values to write in total, e.g. to fill the storage to a fraction of its capacity.
+    ///
+    /// The client stops a phase once the workload has written the bytes, counting the successful inserts and updates of
+    /// all the phases. Each client thread may finish its in-flight operation, so it can be exceeded by at most one
+    /// operation per thread. Unlimited if unset.
+    #[serde(default = "default::max_bytes_written")]
+    max_bytes_written: Option<usize>,
+}
+
+impl Default for CoreWorkloadConfig {
+    /// The config with all fields set to their default values.
+    fn default() -> Self {
+        let fields =
+            MapDeserializer::<_, serde::de::value::Error>::new(std::iter::empty::<(&str, &str)>());
+        Self::deserialize(fields).expect("all fields have default values")
+    }
+}
+
+impl CoreWorkloadConfig {
+    /// Load the config from a YCSB workload file, which is a Java-style properties file with `key=value` lines.
+    ///
+    /// Each field can be set with either its canonical YCSB property name (e.g. `readproportion`) or its field name.
+    /// The properties that are not used by the workload, e.g. `workload`, are ignored.
+    pub fn from_properties(reader: impl Read) -> Result<Self> {
+        properties::from_reader(reader)
+    }
+
+    /// Create a builder of the config, whose unset fields take their default values.
+    pub fn builder() -> CoreWorkloadConfigBuilder {
+        CoreWorkloadConfigBuilder::default()
+    }
+
+    /// Set the field lengths to constant, so that the values of a record take about `bytes` bytes in total. The keys
+    /// and the field names are not counted.
+    ///
+    /// The configured number of fields is kept, unless it is more than `bytes`, in which case it is cut down to `bytes`
+    /// fields of one byte each. The field length is `bytes / fields` rounded to the nearest integer, so the record size
+    /// is off by at most half of the number of fields, e.g. 4096 bytes over 10 fields take 410 bytes per field, 4100
+    /// bytes in total.
+    pub fn with_target_record_size(mut self, bytes: usize) -> Self {
+        if bytes > 0 {
+            self.fields = self.fields.clamp(1, bytes);
+        }
+        let fields = self.fields.max(1);
+        let field_length = (bytes + fields / 2) / fields;
+        self.field_length_distribution = "constant".to_string();
+        self.min_field_length = field_length;
+        self.max_field_length = field_length;
+
+        let record_size = ConstantUsizeGenerator::new(field_length).mean() * fields as f64;
+        debug_assert!(
+            (record_size - bytes as f64).abs() <= fields as f64 / 2.0,
+            "record size {record_size} is too far from the target {bytes}"
+        );
+        self
+    }
+}
+
+/// Generates a setter for each field of [`CoreWorkloadConfig`]. The setters of the optional fields take the value.
+macro_rules! setters {
+    ($( {$field:ident, $type:ty}, )*) => {
+        $(
+            #[doc = concat!("Set `", stringify!($field), "` of the config.")]
+            pub fn $field(mut self, $field: $type) -> Self {
+                self.config.$field = $field.into();
+                self
+            }
+        )*
+    };
+}
+
+/// A builder of [`CoreWorkloadConfig`] with fluent setters, e.g.
+/// `CoreWorkloadConfig::builder().record_count(10_000).read_proportion(0.8).build()`.
+#[derive(Debug, Default, Clone)]
+pub struct CoreWorkloadConfigBuilder {
+    config: CoreWorkloadConfig,
+}
+
+impl CoreWorkloadConfigBuilder {
+    setters! {
+        {table, impl Into<String>},
+        {table_count, usize},
+        {fields, usize},
+        {field_name_prefix, impl Into<String>},
+        {field_length_distribution, impl Into<String>},
+        {min_field_length, usize},
+        {max_field_length, usize},
+        {field_length_overrides, HashMap<String, (String, usize, usize)>},
+        {field_length_histogram_file, impl Into<String>},
+        {field_value_source, impl Into<String>},
+        {compressibility, f64},
+        {binary_values, bool},
+        {field_length_stats, bool},
+        {record_count, usize},
+        {request_distribution, impl Into<String>},
+        {exponential_percentile, f64},
+        {exponential_frac, f64},
+        {hotspot_data_fraction, f64},
+        {hotspot_operation_fraction, f64},
+        {scan_length_distribution, impl Into<String>},
+        {min_scan_length, usize},
+        {max_scan_length, usize},
+        {insert_start, usize},
+        {insert_count, usize},
+        {operation_count, usize},
+        {key_prefix, impl Into<String>},
+        {key_format, impl Into<String>},
+        {max_tracked_keys, usize},
+        {zero_padding, usize},
+        {read_all_fields, bool},
+        {read_all_fields_by_name, bool},
+        {write_all_fields, bool},
+        {fields_to_write_distribution, impl Into<String>},
+        {min_fields_to_write, usize},
+        {max_fields_to_write, usize},
+        {data_integrity, bool},
+        {verify_writes, bool},
+        {insert_order, impl Into<String>},
+        {key_hasher, KeyHasher},
+        {read_proportion, f64},
+        {update_proportion, f64},
+        {insert_proportion, f64},
+        {scan_proportion, f64},
+        {read_modify_write_proportion, f64},
+        {delete_proportion, f64},
+        {phases, Vec<PhaseConfig>},
+        {insertion_retry_limit, usize},
+        {insertion_retry_interval, usize},
+        {retry_max_interval, usize},
+        {read_retry_limit, usize},
+        {read_retry_interval, usize},
+        {update_retry_limit, usize},
+        {update_retry_interval, usize},
+        {scan_retry_limit, usize},
+        {scan_retry_interval, usize},
+        {read_modify_write_retry_limit, usize},
+        {read_modify_write_retry_interval, usize},
+        {delete_retry_limit, usize},
+        {delete_retry_interval, usize},
+        {seed, u64},
+        {target_throughput, usize},
+        {think_time_distribution, impl Into<String>},
+        {min_think_time, usize},
+        {max_think_time, usize},
+        {max_bytes_written, usize},
+    }
+
+    /// Set `insert_start` and `insert_count` to load the `shard_index`-th of `shard_count` shards of the records, see
+    /// [`shard_insert_range`]. Set a non-zero `record_count` before calling it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_index` is not less than `shard_count`.
+    pub fn shard(mut self, shard_index: usize, shard_count: usize) -> Self {
+        let (insert_start, insert_count) =
+            shard_insert_range(self.config.record_count, shard_index, shard_count);
+        self.config.insert_start = insert_start;
+        self.config.insert_count = Some(insert_count);
+        self
+    }
+
+    /// Set the field lengths for the target record size, see [`CoreWorkloadConfig::with_target_record_size`]. Set
+    /// `fields` before calling it.
+    pub fn target_record_size(mut self, bytes: usize) -> Self {
+        self.config = self.config.with_target_record_size(bytes);
+        self
+    }
+
+    /// Build the config, which is validated the same way as [`CoreWorkload::new`].
+    pub fn build(self) -> Result<CoreWorkloadConfig> {
+        CoreWorkload::new(self.config.clone())?;
+        Ok(self.config)
+    }
+}
+
+/// Split the load of `total_records` records evenly across `shard_count` client instances, e.g. on different machines,
+/// and return the `insert_start` and the `insert_count` of the `shard_index`-th instance.
+///
+/// The shards are contiguous and cover all the records. If the records can't be divided evenly, the low-index shards
+/// take one more record each, e.g. 1000 records over 3 shards are split into 334, 333 and 333 records.
+///
+/// # Panics
+///
+/// Panics if `shard_index` is not less than `shard_count`.
+pub fn shard_insert_range(
+    total_records: usize,
+    shard_index: usize,
+    shard_count: usize,
+) -> (usize, usize) {
+    assert!(
+        shard_index < shard_count,
+        "shard index {shard_index} must be less than shard count {shard_count}"
+    );
+    let base = total_records / shard_count;
+    let remainder = total_records % shard_count;
+    let start = shard_index * base + shard_index.min(remainder);
+    let count = base + usize::from(shard_index < remainder);
+    (start, count)
+}
+
+/// The core benchmark scenario. Represents a set of clients doing simple CRUD operations. The
+/// relative proportion of different kinds of operations, and other properties of the workload,
+/// are controlled by parameters specified at runtime.
+///
+/// Properties to control the client:
+///
+/// - **fieldcount**: the number of fields in a record (default: 10)
+/// - **fieldlength**: the size of each field (default: 100)
+/// - **minfieldlength**: the minimum size of each field (default: 1)
+/// - **readallfields**: should reads read all fields (true) or just one (false) (default: true)
+/// - **writeallfields**: should updates and read/modify/writes update all fields (true) or just
+///   one (false) (default: false)
+/// - **readproportion**: what proportion of operations should be reads (default: 0.95)
+/// - **updateproportion**: what proportion of operations should be updates (default: 0.05)
+/// - **insertproportion**: what proportion of operations should be inserts (default: 0)
+/// - **scanproportion**: what proportion of operations should be scans (default: 0)
+/// - **readmodifywriteproportion**: what proportion of operations should be read a record,
+///   modify it, write it back (default: 0)
+/// - **deleteproportion**: what proportion of operations should be deletes (default: 0)
+/// - **requestdistribution**: what distribution should be used to select the records to operate
+///   on - uniform, zipfian, hotspot, sequential, exponential or latest (default: uniform)
+/// - **minscanlength**: for scans, what is the minimum number of records to scan (default: 1)
+/// - **maxscanlength**: for scans, what is the maximum number of records to scan (default: 1000)
+/// - **scanlengthdistribution**: for scans, what distribution should be used to choose the
+///   number of records to scan, for each scan, between 1 and maxscanlength (default: uniform)
+/// - **insertstart**: for parallel loads and runs, defines the starting record for this
+///   YCSB instance (default: 0)
+/// - **insertcount**: for parallel loads and runs, defines the number of records for this
+///   YCSB instance (default: recordcount)
+/// - **zeropadding**: for generating a record sequence compatible with string sort order by
+///   0 padding the record number. Controls the number of 0s to use for padding. (default: 1)
+///   For example for row 5, with zeropadding=1 you get 'user5' key and with zeropading=8 you get
+///   'user00000005' key. In order to see its impact, zeropadding needs to be bigger than number of
+///   digits in the record number.
+/// - **insertorder**: should records be inserted in order by key ("ordered"), or in hashed
+///   order ("hashed") (default: hashed)
+/// - **keyhasher**: the hash function to scramble the key numbers with in hashed order, "fnv64",
+///   "fnv32" or "identity" (default: fnv64)
+/// - **fieldnameprefix**: what should be a prefix for field names, the shorter may decrease the
+///   required storage size (default: "field")
+/// - **target**: the target throughput of all the client threads in total, in operations per second,
+///   0 means unthrottled (default: 0)
+pub struct CoreWorkload {
+    tables: Vec<String>,
+    field_names: Vec<String>,
+    field_length_generator: Box<dyn NumberGenerator<Output = usize> + Send + Sync>,
+    /// The field length generators of the fields with overridden distributions, indexed by field. Empty if no field
+    /// is overridden.
+    field_length_overrides: Vec<Option<Box<dyn NumberGenerator<Output = usize> + Send + Sync>>>,
+    /// The operation choosers of the phases, each with the number of transactions at the end of its phase. There is one
+    /// chooser that never ends if there are no phases.
+    operation_choosers: Vec<(usize, DiscreteGenerator<Op>)>,
+    /// The number of chosen transaction operations, which decides the current phase.
+    transactions: AtomicUsize,
+    key_sequencer: UsizeCounter,
+    ordered_inserts: bool,
+    key_hasher: KeyHasher,
+    key_prefix: String,
+    /// Generates the keys if they are random UUIDs.
+    uuid_key_generator: Option<UuidKeyGenerator>,
+    /// The sample of the inserted keys with their record numbers, only kept for the UUID keys.
+    tracked_keys: RwLock<TrackedKeys>,
+    max_tracked_keys: usize,
+    zero_padding: usize,
+    data_inategrity: bool,
+    verify_writes: bool,
+    insert_retry: RetryPolicy,
+    read_retry: RetryPolicy,
+    update_retry: RetryPolicy,
+    scan_retry: RetryPolicy,
+    read_modify_write_retry: RetryPolicy,
+    delete_retry: RetryPolicy,
+    /// Draws the jitter of the retry backoff if seeded, see [`backoff`].
+    retry_rng: Option<Mutex<StdRng>>,
+    target_throughput: usize,
+    /// Generates the think time after each transaction, in milliseconds.
+    think_time_generator: Box<dyn NumberGenerator<Output = usize> + Send + Sync>,
+    insert_count: usize,
+    operation_count: usize,
+    read_all_fields: bool,
+    read_all_fields_by_name: bool,
+    write_all_fields: bool,
+    /// Generates the number of fields that an update writes.
+    fields_to_write_generator: Box<dyn NumberGenerator<Output = usize> + Send + Sync>,
+    field_chooser: UniformUsizeGenerator,
+    transaction_insert_key_sequencer: Arc<AcknowledgedUsizeCounter>,
+    key_chooser: KeyChooser,
+    scan_length_generator: Box<dyn NumberGenerator<Output = usize> + Send + Sync>,
+    value_rng: Option<Mutex<StdRng>>,
+    value_generator: Option<FileValueGenerator>,
+    compressibility: f64,
+    binary_values: bool,
+    /// The lengths of the generated field values, only recorded if `field_length_stats` is enabled.
+    field_length_histogram: Option<Mutex<Histogram<u64>>>,
+    measurements: Arc<Measurements>,
+    operations: Arc<AtomicUsize>,
+    max_bytes_written: Option<usize>,
+    /// The bytes of the field values written by the successful inserts and updates.
+    bytes_written: Arc<AtomicUsize>,
+    /// The config the workload is created with, to create the planners of [`CoreWorkload::plan`].
+    config: CoreWorkloadConfig,
+}
+
+/// Derives the random number generators from the configured seed.
+struct Seeder {
+    rng: Option<StdRng>,
+}
+
+impl Seeder {
+    fn new(seed: Option<u64>) -> Self {
+        Self {
+            rng: seed.map(StdRng::seed_from_u64),
+        }
+    }
+
+    fn rng(&mut self) -> Option<StdRng> {
+        self.rng
+            .as_mut()
+            .map(|rng| StdRng::from_rng(rng).expect("derive rng from seed"))
+    }
+
+    fn seed<G: Seedable>(&mut self, generator: G) -> G {
+        match self.rng() {
+            Some(rng) => generator.with_rng(rng),
+            None => generator,
+        }
+    }
+
+    fn field_length_generator(
+        &mut self,
+        distribution: &str,
+        min: usize,
+        max: usize,
+        histogram_file: &str,
+    ) -> Result<Box<dyn NumberGenerator<Output = usize> + Send + Sync>> {
+        check_range("field length", distribution, min, max)?;
+        let generator: Box<dyn NumberGenerator<Output = usize> + Send + Sync> = match distribution {
+            "constant" => Box::new(ConstantUsizeGenerator::new(max)),
+            "uniform" => Box::new(self.seed(UniformUsizeGenerator::try_new(min, max)?)),
+            "zipfian" => Box::new(self.seed(ZipfianUsizeGenerator::new(min, max))),
+            "histogram" => Box::new(self.seed(HistogramGenerator::from_file(histogram_file)?)),
+            "gaussian" => Box::new(self.seed(GaussianUsizeGenerator::new(
+                (min + max) as f64 / 2.0,
+                (max - min) as f64 / 6.0,
+                min,
+                max,
+            )?)),
+            "lognormal" => {
+                let (ln_min, ln_max) = ((min.max(1) as f64).ln(), (max.max(1) as f64).ln());
+                Box::new(self.seed(LogNormalGenerator::new(
+                    (ln_min + ln_max) / 2.0,
+                    (ln_max - ln_min) / 6.0,
+                    min,
+                    max,
+                )?))
+            }
+            x => return Err(anyhow!("field length distribution not supported: {x}")),
+        };
+        Ok(generator)
+    }
+
+    fn operation_chooser(&mut self, proportions: [(Op, f64); 6]) -> Result<DiscreteGenerator<Op>> {
+        let choices = proportions
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0.0)
+            .map(|(val, weight)| Choice { val, weight })
+            .collect_vec();
+        let proportion: f64 = choices.iter().map(|choice| choice.weight).sum();
+        if proportion <= 0.0 {
+            return Err(anyhow!(
+                "the sum of all operation proportions must be positive, got: {proportion}"
+            ));
+        }
+        if (proportion - 1.0).abs() > 1e-6 {
+            tracing::warn!(
+                "the sum of all operation proportions is {proportion} instead of 1.0, the proportions are normalized"
+            );
+        }
+        Ok(self.seed(DiscreteGenerator::new(choices)?))
+    }
+}
+
+/// Check that the range of a distribution is not empty, unless the distribution is "constant", which only takes the max.
+fn check_range(name: &str, distribution: &str, min: usize, max: usize) -> Result<()> {
+    if distribution != "constant" && min > max {
+        return Err(anyhow!(
+            "the min {name} must not be larger than the max {name}, got: [{min}, {max}]"
+        ));
+    }
+    Ok(())
+}
+
+thread_local! {
+    /// The intended start time of the transaction running on the thread, if it is paced by a rate limiter. It is
+    /// thread-local to reach the measurements of the operations without threading it through every transaction.
+    static INTENDED_START: Cell<Option<Instant>> = const { Cell::new(None) };
+    /// Whether the calling thread is warming up, see [`CoreWorkload::warmup`].
+    static WARMUP: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A uniform sample of the inserted keys with their key numbers.
+#[derive(Debug, Default)]
+struct TrackedKeys {
+    keys: Vec<(usize, String)>,
+    /// The number of the inserted keys, including the ones not in the sample.
+    inserted: usize,
+    /// Draws the replaced keys of the sample if seeded.
+    rng: Option<StdRng>,
+}
+
+/// Chooser of the key numbers to operate on.
+enum KeyChooser {
+    /// Generates the key number directly.
+    Absolute(Box<dyn NumberGenerator<Output = usize> + Send + Sync>),
+    /// Generates the distance from the last acknowledged inserted key number.
+    Recent(Box<dyn NumberGenerator<Output = usize> + Send + Sync>),
+}
+
+impl Workload for CoreWorkload {
+    type Config = CoreWorkloadConfig;
+
+    fn new(config: Self::Config) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Self::with_insert_key_sequencer(config, None)
+    }
+}
+
+impl CoreWorkload {
+    /// Create the workload, which shares the insert key sequence of the transactions if it is given.
+    fn with_insert_key_sequencer(
+        config: CoreWorkloadConfig,
+        transaction_insert_key_sequencer: Option<Arc<AcknowledgedUsizeCounter>>,
+    ) -> Result<Self> {
+        let original = config.clone();
+        let mut seeder = Seeder::new(config.seed);
+
+        let field_length_generator = seeder.field_length_generator(
+            &config.field_length_distribution,
+            config.min_field_length,
+            config.max_field_length,
+            &config.field_length_histogram_file,
+        )?;
+
+        check_range(
+            "scan length",
+            &config.scan_length_distribution,
+            config.min_scan_length,
+            config.max_scan_length,
+        )?;
+        let scan_length_generator: Box<dyn NumberGenerator<Output = usize> + Send + Sync> =
+            match config.scan_length_distribution.as_str() {
+                "constant" => Box::new(ConstantUsizeGenerator::new(config.max_scan_length)),
+                "uniform" => Box::new(seeder.seed(UniformUsizeGenerator::try_new(
+                    config.min_scan_length,
+                    config.max_scan_length,
+                )?)),
+                "zipfian" => Box::new(seeder.seed(ZipfianUsizeGenerator::new(
+                    config.min_scan_length,
+                    config.max_scan_length,
+                ))),
+                x => return Err(anyhow!("scan length distribution not supported: {x}")),
+            };
+
+        let record_count = if config.record_count == 0 {
+            usize::MAX
+        } else {
+            config.record_count
+        };
+
+        let insert_start = config.insert_start;
+        let insert_count = match config.insert_count {
+            Some(insert_count) => insert_count,
+            None => record_count.checked_sub(insert_start).ok_or_else(|| {
+                anyhow!("insert_start ({insert_start}) must not be larger than record_count ({record_count})")
+            })?,
+        };
+        if insert_start
+            .checked_add(insert_count)
+            .map_or(true, |end| end > record_count)
+        {
+            return Err(anyhow!("invalid combination of insert_start ({insert_start}), insert_count ({insert_count}) and record_count ({record_count}): record_count must be equal to or larger than insert_start + insert_count"));
+        }
+        if insert_count == 0 {
+            return Err(anyhow!(
+                "insert_count must be positive, there are no records to choose from otherwise"
+            ));
+        }
+
+        let key_sequencer = UsizeCounter::new(insert_start);
+
+        let operation_choosers = if config.phases.is_empty() {
+            let proportions = PhaseConfig {
+                operation_count: 0,
+                read_proportion: config.read_proportion,
+                update_proportion: config.update_proportion,
+                insert_proportion: config.insert_proportion,
+                scan_proportion: config.scan_proportion,
+                read_modify_write_proportion: config.read_modify_write_proportion,
+                delete_proportion: config.delete_proportion,
+            }
+            .proportions();
+            vec![(usize::MAX, seeder.operation_chooser(proportions)?)]
+        } else {
+            let mut end = 0usize;
+            let mut operation_choosers = vec![];
+            for (i, phase) in config.phases.iter().enumerate() {
+                end = if i + 1 == config.phases.len() {
+                    usize::MAX
+                } else if phase.operation_count == 0 {
+                    return Err(anyhow!("the operation count of phase {i} must be positive"));
+                } else {
+                    end.saturating_add(phase.operation_count)
+                };
+                let operation_chooser = seeder
+                    .operation_chooser(phase.proportions())
+                    .map_err(|e| anyhow!("invalid phase {i}: {e}"))?;
+                operation_choosers.push((end, operation_chooser));
+            }
+            operation_choosers
+        };
+
+        let ordered_inserts = config.insert_order.as_str() != "hashed";
+        let zero_padding = if ordered_inserts {
+            config.zero_padding
+        } else {
+            config.zero_padding.max(config.key_hasher.digits())
+        };
+
+        let uuid_key_generator = match config.key_format.as_str() {
+            "numeric" => None,
+            "uuid" => Some(seeder.seed(UuidKeyGenerator::new())),
+            x => return Err(anyhow!("key format not supported: {x}")),
+        };
+        if uuid_key_generator.is_some() && config.max_tracked_keys == 0 {
+            return Err(anyhow!("the max number of tracked keys must be positive"));
+        }
+
+        let tables = match config.table_count {
+            0 => return Err(anyhow!("the number of tables must be positive")),
+            1 => vec![config.table],
+            n => (0..n)
+                .map(|i| format!("{table}{i}", table = config.table))
+                .collect_vec(),
+        };
+
+        if config.fields == 0 {
+            return Err(anyhow!("the number of fields must be positive"));
+        }
+        let field_names = (0..config.fields)
+            .map(|i| format!("{prefix}{i}", prefix = config.field_name_prefix))
+            .collect_vec();
+
+        let mut field_length_overrides = vec![];
+        for (field, (distribution, min, max)) in &config.field_length_overrides {
+            let Some(index) = field_names.iter().position(|name| name == field) else {
+                return Err(anyhow!("field length override of unknown field: {field}"));
+            };
+            field_length_overrides.resize_with(field_names.len(), || None);
+            field_length_overrides[index] = Some(seeder.field_length_generator(
+                distribution,
+                *min,
+                *max,
+                &config.field_length_histogram_file,
+            )?);
+        }
+        let field_chooser = seeder.seed(UniformUsizeGenerator::new(0, field_names.len() - 1));
+
+        if config.min_fields_to_write == 0 || config.max_fields_to_write > field_names.len() {
+            return Err(anyhow!(
+                "fields to write must be in [1, {}], got: [{}, {}]",
+                field_names.len(),
+                config.min_fields_to_write,
+                config.max_fields_to_write
+            ));
+        }
+        check_range(
+            "fields to write",
+            &config.fields_to_write_distribution,
+            config.min_fields_to_write,
+            config.max_fields_to_write,
+        )?;
+        let fields_to_write_generator: Box<dyn NumberGenerator<Output = usize> + Send + Sync> =
+            match config.fields_to_write_distribution.as_str() {
+                "constant" => Box::new(ConstantUsizeGenerator::new(config.max_fields_to_write)),
+                "uniform" => Box::new(seeder.seed(UniformUsizeGenerator::try_new(
+                    config.min_fields_to_write,
+                    config.max_fields_to_write,
+                )?)),
+                "zipfian" => Box::new(seeder.seed(ZipfianUsizeGenerator::new(
+                    config.min_fields_to_write,
+                    config.max_fields_to_write,
+                ))),
+                x => return Err(anyhow!("fields to write distribution not supported: {x}")),
+            };
+
+        let transaction_insert_key_sequencer = transaction_insert_key_sequencer
+            .unwrap_or_else(|| Arc::new(AcknowledgedUsizeCounter::new(record_count)));
+
+        let key_chooser = match config.request_distribution.as_str() {
+            // The keyspace grows with the inserts of the run phase, choose the new keys too.
+            "uniform" => {
+                KeyChooser::Absolute(Box::new(seeder.seed(DynamicRangeGenerator::uniform(
+                    transaction_insert_key_sequencer.clone(),
+                    insert_start,
+                    insert_start + insert_count - 1,
+                ))))
+            }
+            "zipfian" => KeyChooser::Absolute(Box::new(seeder.seed(
+                DynamicRangeGenerator::scrambled_zipfian(
+                    transaction_insert_key_sequencer.clone(),
+                    insert_start,
+                    insert_start + insert_count - 1,
+                ),
+            ))),
+            "sequential" => KeyChooser::Absolute(Box::new(SequentialUsizeGenerator::new(
+                insert_start,
+                insert_start + insert_count - 1,
+            ))),
+            "exponential" => {
+                KeyChooser::Recent(Box::new(seeder.seed(ExponentialUsizeGenerator::new(
+                    config.exponential_percentile,
+                    insert_count as f64 * config.exponential_frac,
+                ))))
+            }
+            "hotspot" => KeyChooser::Absolute(Box::new(seeder.seed(HotspotUsizeGenerator::new(
+                insert_start,
+                insert_start + insert_count - 1,
+                config.hotspot_data_fraction,
+                config.hotspot_operation_fraction,
+            )))),
+            "latest" => KeyChooser::Absolute(Box::new(seeder.seed(SkewedLatestGenerator::new(
+                transaction_insert_key_sequencer.clone(),
+                insert_count,
+            )?))),
+            x => return Err(anyhow!("request distribution not supported: {x}")),
+        };
+
+        check_range(
+            "think time",
+            &config.think_time_distribution,
+            config.min_think_time,
+            config.max_think_time,
+        )?;
+        let think_time_generator: Box<dyn NumberGenerator<Output = usize> + Send + Sync> =
+            match config.think_time_distribution.as_str() {
+                "constant" => Box::new(ConstantUsizeGenerator::new(config.max_think_time)),
+                "uniform" => Box::new(seeder.seed(UniformUsizeGenerator::try_new(
+                    config.min_think_time,
+                    config.max_think_time,
+                )?)),
+                "exponential" => Box::new(seeder.seed(ExponentialUsizeGenerator::with_mean(
+                    (config.min_think_time + config.max_think_time) as f64 / 2.0,
+                ))),
+                x => return Err(anyhow!("think time distribution not supported: {x}")),
+            };
+
+        let value_rng = seeder.rng().map(Mutex::new);
+        let tracked_keys = TrackedKeys {
+            rng: seeder.rng(),
+            ..Default::default()
+        };
+        let retry_rng = seeder.rng().map(Mutex::new);
+
+        let value_generator = if config.field_value_source.is_empty() {
+            None
+        } else {
+            Some(FileValueGenerator::new(&config.field_value_source)?)
+        };
+
+        Ok(Self {
+            tables,
+            field_names,
+            field_length_generator,
+            field_length_overrides,
+            operation_choosers,
+            transactions: AtomicUsize::new(0),
+            key_sequencer,
+            ordered_inserts,
+            key_hasher: config.key_hasher,
+            key_prefix: config.key_prefix,
+            uuid_key_generator,
+            tracked_keys: RwLock::new(tracked_keys),
+            max_tracked_keys: config.max_tracked_keys,
+            zero_padding,
+            data_inategrity: config.data_integrity,
+            verify_writes: config.verify_writes,
+            insert_retry: RetryPolicy::new(
+                config.insertion_retry_limit,
+                config.insertion_retry_interval,
+                config.retry_max_interval,
+            ),
+            read_retry: RetryPolicy::new(
+                config.read_retry_limit,
+                config.read_retry_interval,
+                config.retry_max_interval,
+            ),
+            update_retry: RetryPolicy::new(
+                config.update_retry_limit,
+                config.update_retry_interval,
+                config.retry_max_interval,
+            ),
+            scan_retry: RetryPolicy::new(
+                config.scan_retry_limit,
+                config.scan_retry_interval,
+                config.retry_max_interval,
+            ),
+            read_modify_write_retry: RetryPolicy::new(
+                config.read_modify_write_retry_limit,
+                config.read_modify_write_retry_interval,
+                config.retry_max_interval,
+            ),
+            delete_retry: RetryPolicy::new(
+                config.delete_retry_limit,
+                config.delete_retry_interval,
+                config.retry_max_interval,
+            ),
+            retry_rng,
+            target_throughput: config.target_throughput,
+            think_time_generator,
+            insert_count,
+            operation_count: config.operation_count,
+            read_all_fields: config.read_all_fields,
+            read_all_fields_by_name: config.read_all_fields_by_name,
+            write_all_fields: config.write_all_fields,
+            fields_to_write_generator,
+            field_chooser,
+            transaction_insert_key_sequencer,
+            key_chooser,
+            scan_length_generator,
+            value_rng,
+            value_generator,
+            compressibility: config.compressibility,
+            binary_values: config.binary_values,
+            field_length_histogram: config.field_length_stats.then(|| {
+                Mutex::new(Histogram::new(SIGNIFICANT_FIGURES).expect("valid significant figures"))
+            }),
+            measurements: Arc::new(Measurements::new()),
+            operations: Arc::new(AtomicUsize::new(0)),
+            max_bytes_written: config.max_bytes_written,
+            bytes_written: Arc::new(AtomicUsize::new(0)),
+            config: original,
+        })
+    }
+
+    /// Do one insert operation. Because it will be called concurrently from multiple client threads,
+    /// this function must be thread safe. However, avoid synchronized, or the threads will block waiting
+    /// for each other, and it will be difficult to reach the target throughput. Ideally, this function would
+    /// have no side effects other than DB operations.
+    pub fn insert(&self, db: impl Db) -> Result<()> {
+        let key_num = self.key_sequencer.next();
+        let db_key = self.new_key_name(key_num);
+        let values = self.build_values(&db_key);
+
+        let res = self.retry("insert", &self.insert_retry, || {
+            self.measure(Op::Insert, || {
+                db.insert(self.table(key_num), db_key.clone(), values.clone())
+            })
+        });
+        if res.is_ok() {
+            self.record_written(&values);
+            self.track_key(key_num, &db_key);
+        }
+        let res = res.and_then(|_| self.verify_write(&db, key_num, &db_key, &values));
+        self.operations.fetch_add(1, Ordering::Relaxed);
+        res
+    }
+
+    /// Load the records of this client instance, which inserts exactly `insert_count` records starting from
+    /// `insert_start` one by one.
+    ///
+    /// It drives the whole load phase on the calling thread and must not be called concurrently with other inserts of
+    /// the load phase, which share the same key sequence. If neither `record_count` nor `insert_count` is set, there is
+    /// no end of the records and the load only stops on error or when `max_bytes_written` is reached.
+    pub fn load(&self, db: impl Db + Clone) -> Result<()> {
+        for _ in 0..self.insert_count {
+            if self.is_write_limit_reached() {
+                break;
+            }
+            self.insert(db.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Do a batch of insert operations with `batch_size` records. See [`CoreWorkload::insert`].
+    ///
+    /// The records are grouped by their tables, with one batch for each table.
+    pub fn insert_batch(&self, db: impl Db, batch_size: usize) -> Result<()> {
+        let mut batches: BTreeMap<String, Vec<_>> = BTreeMap::new();
+        let mut keys = Vec::with_capacity(batch_size);
+        for _ in 0..batch_size {
+            let key_num = self.key_sequencer.next();
+            let db_key = self.new_key_name(key_num);
+            let values = self.build_values(&db_key);
+            keys.push((key_num, db_key.clone()));
+            batches
+                .entry(self.table(key_num))
+                .or_default()
+                .push((db_key, values));
+        }
+
+        let res = self.retry("insert batch", &self.insert_retry, || {
+            self.measure(Op::Insert, || {
+                batches.iter().try_for_each(|(table, records)| {
+                    db.insert_batch(table.clone(), records.clone())
+                })
+            })
+        });
+        if res.is_ok() {
+            batches
+                .values()
+                .flatten()
+                .for_each(|(_, values)| self.record_written(values));
+            keys.iter()
+                .for_each(|(key_num, key)| self.track_key(*key_num, key));
+        }
+        let res = res.and_then(|_| {
+            keys.iter().try_for_each(|(key_num, key)| {
+                let values = batches[&self.table(*key_num)]
+                    .iter()
+                    .find_map(|(k, values)| (k == key).then_some(values))
+                    .expect("the record of the batch");
+                self.verify_write(&db, *key_num, key, values)
+            })
+        });
+        self.operations.fetch_add(batch_size, Ordering::Relaxed);
+        res
+    }
+
+    /// Do one transaction operation. Because it will be called concurrently from multiple client
+    /// threads, this function must be thread safe. However, avoid synchronized, or the threads will block waiting
+    /// for each other, and it will be difficult to reach the target throughput. Ideally, this function would
+    /// have no side effects other than DB operations.
+    ///
+    /// Each operation runs in an info-level span named after it, e.g. `txn_read`, for profiling with a tracing
+    /// subscriber. The span records the accessed key as the `key` field only if trace level is enabled.
+    ///
+    /// After the operation, the calling thread sleeps for the generated think time, which is not measured.
+    ///
+    /// The operation is chosen with the proportions of the current phase if `phases` is set, see [`CoreWorkloadConfig`].
+    ///
+    /// Returns the operation performed.
+    pub fn transaction(&self, db: impl Db) -> Result<Op> {
+        let op = self.next_op();
+        let res = match op {
+            Op::Read => self.txn_read(db),
+            Op::Update => self.txn_update(db),
+            Op::Insert => self.txn_insert(db),
+            Op::Scan => self.txn_scan(db),
+            Op::ReadModifyWrite => self.txn_read_modify_read(db),
+            Op::Delete => self.txn_delete(db),
+            Op::VerifyRead => unreachable!("verify reads are not chosen as transactions"),
+        };
+        if !WARMUP.with(Cell::get) {
+            self.operations.fetch_add(1, Ordering::Relaxed);
+        }
+        let think_time = self.think_time_generator.next();
+        if think_time > 0 {
+            std::thread::sleep(Duration::from_millis(think_time as u64));
+        }
+        res.map(|()| op)
+    }
+
+    /// Do one transaction operation after taking a token from the limiter, which paces the transactions of the client
+    /// thread to the target throughput. See [`CoreWorkload::transaction`].
+    ///
+    /// If the limiter is not unlimited, the intended latencies of the operations are measured from the intended start
+    /// time returned by the limiter too.
+    pub fn transaction_rate_limited(&self, db: impl Db, limiter: &RateLimiter) -> Result<Op> {
+        let intended = limiter.acquire();
+        if limiter.is_unlimited() {
+            return self.transaction(db);
+        }
+        INTENDED_START.with(|start| start.set(Some(intended)));
+        let res = self.transaction(db);
+        INTENDED_START.with(|start| start.set(None));
+        res
+    }
+
+    /// Run `f` as a warm-up on the calling thread. The transactions done by `f` hit the database like the other ones, but
+    /// they are excluded from the measurements and the operation count, and they don't advance the phases, so they
+    /// leave the report and the phase boundaries as they are.
+    pub fn warmup<T>(&self, f: impl FnOnce() -> T) -> T {
+        WARMUP.with(|warmup| warmup.set(true));
+        let res = f();
+        WARMUP.with(|warmup| warmup.set(false));
+        res
+    }
+
+    /// Plan `n` transaction operations without a database, to inspect what the config does, e.g. which keys are hot
+    /// and how large the values are.
+    ///
+    /// The operations are drawn from a copy of the workload, whose generators are created and seeded like the ones of
+    /// [`CoreWorkload::transaction`], and whose phases go on from the current transaction. So a plan leaves the generators
+    /// and the phases of the workload as they are, and doesn't change the following transactions. The planned inserts
+    /// take the keys following the last inserted one without advancing the insert key sequence, so the other planned
+    /// operations never choose them.
+    ///
+    /// It fails if the `key_format` is "uuid" and an operation other than an insert is planned before any key is
+    /// inserted, since there are no keys to choose.
+    pub fn plan(&self, n: usize) -> Result<Vec<PlannedOp>> {
+        let planner = Self::with_insert_key_sequencer(
+            self.config.clone(),
+            Some(self.transaction_insert_key_sequencer.clone()),
+        )?;
+        planner
+            .transactions
+            .store(self.transactions.load(Ordering::Relaxed), Ordering::Relaxed);
+        {
+            let tracked = self.tracked_keys.read();
+            let mut planned = planner.tracked_keys.write();
+            planned.keys.clone_from(&tracked.keys);
+            planned.inserted = tracked.inserted;
+        }
+        planner.plan_ops(n)
+    }
+
+    fn plan_ops(&self, n: usize) -> Result<Vec<PlannedOp>> {
+        let mut inserts = 0;
+        (0..n)
+            .map(|_| {
+                let op = self.next_op();
+                let (key_num, key) = match op {
+                    Op::Insert => {
+                        inserts += 1;
+                        let key_num = self.transaction_insert_key_sequencer.last() + inserts;
+                        (key_num, self.new_key_name(key_num))
+                    }
+                    _ => self.next_key()?,
+                };
+                let scan_length = match op {
+                    Op::Scan => self.scan_length_generator.next(),
+                    _ => 0,
+                };
+                let read_fields = match op {
+                    Op::Read | Op::Scan | Op::ReadModifyWrite => {
+                        self.choose_read_fields().into_iter().sorted().collect()
+                    }
+                    Op::Update | Op::Insert | Op::Delete | Op::VerifyRead => vec![],
+                };
+                let written_fields = match op {
+                    Op::Insert => self.draw_written_fields(true),
+                    Op::Update | Op::ReadModifyWrite => {
+                        self.draw_written_fields(self.write_all_fields)
+                    }
+                    Op::Read | Op::Scan | Op::Delete | Op::VerifyRead => (vec![], vec![]),
+                };
+                Ok(PlannedOp {
+                    table: self.table(key_num),
+                    key,
+                    op,
+                    read_fields,
+                    written_fields: written_fields.0.into_iter().zip(written_fields.1).collect(),
+                    scan_length,
+                })
+            })
+            .collect()
+    }
+
+    /// Check that the backend supports all the operations issued by the transactions of the workload, i.e. the
+    /// operations with positive proportions in any phase. A read-modify-write needs both reads and updates, and so do the
+    /// writes if `verify_writes` is enabled.
+    pub fn check_capabilities(&self, capabilities: &Capabilities) -> Result<()> {
+        for (op, probability) in self
+            .operation_choosers
+            .iter()
+            .flat_map(|(_, operation_chooser)| operation_chooser.probabilities())
+        {
+            if probability <= 0.0 {
+                continue;
+            }
+            let operations: &[Operation] = match op {
+                Op::Read | Op::VerifyRead => &[Operation::Read],
+                Op::Update if self.verify_writes => &[Operation::Update, Operation::Read],
+                Op::Insert if self.verify_writes => &[Operation::Insert, Operation::Read],
+                Op::Update => &[Operation::Update],
+                Op::Insert => &[Operation::Insert],
+                Op::Scan => &[Operation::Scan],
+                Op::ReadModifyWrite => &[Operation::Read, Operation::Update],
+                Op::Delete => &[Operation::Delete],
+            };
+            if let Some(operation) = operations
+                .iter()
+                .find(|&&operation| !capabilities.supports(operation))
+            {
+                return Err(anyhow!(
+                    "{op:?} transactions need {operation:?} operations, which the db doesn't support"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the backend supports the operations issued by the load phase, the inserts and the reads back if
+    /// `verify_writes` is enabled.
+    pub fn check_load_capabilities(&self, capabilities: &Capabilities) -> Result<()> {
+        if !capabilities.supports(Operation::Insert) {
+            return Err(anyhow!(
+                "the load phase needs inserts, which the db doesn't support"
+            ));
+        }
+        if self.verify_writes && !capabilities.supports(Operation::Read) {
+            return Err(anyhow!(
+                "verifying the writes needs reads, which the db doesn't support"
+            ));
+        }
+        Ok(())
+    }
+
+    /// Create the rate limiter for one of the `threads` client threads, which paces the thread to its share of the
+    /// target throughput.
+    ///
+    /// The limiter is unlimited if the target throughput is 0.
+    pub fn rate_limiter(&self, threads: usize) -> RateLimiter {
+        if self.target_throughput == 0 {
+            return RateLimiter::unlimited();
+        }
+        RateLimiter::new(self.target_throughput as f64 / threads.max(1) as f64)
+    }
+
+    /// Get the number of records to insert by this client instance in the load phase.
+    pub fn insert_count(&self) -> usize {
+        self.insert_count
+    }
+
+    /// Get the number of transactions of the run phase.
+    pub fn operation_count(&self) -> usize {
+        self.operation_count
+    }
+
+    /// Get the latency measurements of the database operations issued by the workload.
+    ///
+    /// Each insert of a batch insert is not measured separately, the whole batch is measured as one insert.
+    pub fn measurements(&self) -> &Arc<Measurements> {
+        &self.measurements
+    }
+
+    /// Get a snapshot of the lengths of the field values generated so far, or `None` if `field_length_stats` is not
+    /// enabled.
+    ///
+    /// It is not a latency measurement, but a diagnostic to verify the field length distribution, e.g. the mean of the
+    /// lengths drawn from a zipfian distribution.
+    pub fn field_length_stats(&self) -> Option<HistogramSnapshot> {
+        self.field_length_histogram
+            .as_ref()
+            .map(|histogram| HistogramSnapshot::new(histogram.lock().clone()))
+    }
+
+    /// Get the count of the completed operations, including the failed ones.
+    ///
+    /// Each transaction and each insert counts as one operation, and a batch insert counts as `batch_size`
+    /// operations.
+    pub fn operations(&self) -> &Arc<AtomicUsize> {
+        &self.operations
+    }
+
+    /// Get the bytes of the field values written by the successful inserts and updates so far.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the workload has written `max_bytes_written` bytes, after which no more operations should be
+    /// issued.
+    pub fn is_write_limit_reached(&self) -> bool {
+        self.max_bytes_written
+            .map_or(false, |max| self.bytes_written() >= max)
+    }
+
+    /// Spawn a [`StatusReporter`] that reports the status of the workload every `interval`.
+    pub fn status_reporter(&self, interval: Duration) -> StatusReporter {
+        StatusReporter::spawn(self.measurements.clone(), self.operations.clone(), interval)
+    }
+
+    /// Spawn a [`ProgressReporter`] that shows the progress of the workload towards `total` operations every
+    /// `interval`.
+    pub fn progress_reporter(&self, total: usize, interval: Duration) -> ProgressReporter {
+        ProgressReporter::spawn(self.operations.clone(), total, interval)
+    }
+
+    fn txn_read(&self, db: impl Db) -> Result<()> {
+        let span = tracing::info_span!("txn_read", key = tracing::field::Empty).entered();
+        let (key_num, key_name) = self.next_key()?;
+        record_key(&span, &key_name);
+
+        let fields = self.choose_read_fields();
+
+        let cells = self.retry("read", &self.read_retry, || {
+            self.measure(Op::Read, || {
+                db.read(self.table(key_num), key_name.clone(), fields.clone())
+            })
+        })?;
+        if self.data_inategrity {
+            self.verify_row(key_name.clone(), fields.clone(), cells)?;
+        }
+        Ok(())
+    }
+
+    fn txn_update(&self, db: impl Db) -> Result<()> {
+        let span = tracing::info_span!("txn_update", key = tracing::field::Empty).entered();
+        let (key_num, key_name) = self.next_key()?;
+        record_key(&span, &key_name);
+
+        let values = if self.write_all_fields {
+            self.build_values(&key_name)
+        } else {
+            self.build_single_value(&key_name)
+        };
+
+        self.retry("update", &self.update_retry, || {
+            self.measure(Op::Update, || {
+                db.update(self.table(key_num), key_name.clone(), values.clone())
+            })
+        })?;
+        self.record_written(&values);
+        self.verify_write(&db, key_num, &key_name, &values)
+    }
+
+    fn txn_insert(&self, db: impl Db) -> Result<()> {
+        let span = tracing::info_span!("txn_insert", key = tracing::field::Empty).entered();
+        let key_num = self.transaction_insert_key_sequencer.next();
+
+        let key_name = self.new_key_name(key_num);
+        record_key(&span, &key_name);
+        let values = self.build_values(&key_name);
+
+        let res = self.retry("insert", &self.insert_retry, || {
+            self.measure(Op::Insert, || {
+                db.insert(self.table(key_num), key_name.clone(), values.clone())
+            })
+        });
+        if res.is_ok() {
+            self.record_written(&values);
+            self.track_key(key_num, &key_name);
+        }
+        let res = res.and_then(|_| self.verify_write(&db, key_num, &key_name, &values));
+
+        self.transaction_insert_key_sequencer.acknowledge(key_num);
+        res
+    }
+
+    fn txn_scan(&self, db: impl Db) -> Result<()> {
+        let span = tracing::info_span!("txn_scan", key = tracing::field::Empty).entered();
+        let (key_num, start_key_name) = self.next_key()?;
+        record_key(&span, &start_key_name);
+        let len = self.scan_length_generator.next();
+
+        let fields = self.choose_read_fields();
+
+        if self.data_inategrity {
+            // Verify the records one by one, which needs the boundaries of the records.
+            let ordered = db.scan_is_ordered();
+            let rows = self.retry("scan", &self.scan_retry, || {
+                self.measure(Op::Scan, || {
+                    db.scan_rows(
+                        self.table(key_num),
+                        start_key_name.clone(),
+                        len,
+                        fields.clone(),
+                    )
+                })
+            })?;
+            let start_key_name = ordered.then_some(start_key_name);
+            self.verify_scan(fields, start_key_name, len, rows)?;
+        } else {
+            self.retry("scan", &self.scan_retry, || {
+                self.measure(Op::Scan, || {
+                    db.scan(
+                        self.table(key_num),
+                        start_key_name.clone(),
+                        len,
+                        fields.clone(),
+                    )
+                })
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn txn_read_modify_read(&self, db: impl Db) -> Result<()> {
+        let span =
+            tracing::info_span!("txn_read_modify_read", key = tracing::field::Empty).entered();
+        let (key_num, key_name) = self.next_key()?;
+        record_key(&span, &key_name);
+
+        let fields = self.choose_read_fields();
+
+        let (written_fields, sizes) = self.choose_written_fields(self.write_all_fields);
+
+        let start = Instant::now();
+        let cells = self.retry(
+            "read-modify-write read",
+            &self.read_modify_write_retry,
+            || {
+                self.measure(Op::Read, || {
+                    db.read(self.table(key_num), key_name.clone(), fields.clone())
+                })
+            },
+        )?;
+        let values = self.build_field_values(&key_name, written_fields, sizes);
+        self.retry(
+            "read-modify-write update",
+            &self.read_modify_write_retry,
+            || {
+                self.measure(Op::Update, || {
+                    db.update(self.table(key_num), key_name.clone(), values.clone())
+                })
+            },
+        )?;
+        self.record_written(&values);
+        self.measurements
+            .measure(Op::ReadModifyWrite, start.elapsed());
+        self.measure_intended(Op::ReadModifyWrite);
+        self.verify_write(&db, key_num, &key_name, &values)?;
+
+        if self.data_inategrity {
+            self.verify_row(key_name.clone(), fields.clone(), cells)?;
+        }
+
+        Ok(())
+    }
+
+    fn txn_delete(&self, db: impl Db) -> Result<()> {
+        let span = tracing::info_span!("txn_delete", key = tracing::field::Empty).entered();
+        let (key_num, key_name) = self.next_key()?;
+        record_key(&span, &key_name);
+
+        self.retry("delete", &self.delete_retry, || {
+            self.measure(Op::Delete, || {
+                db.delete(self.table(key_num), key_name.clone())
+            })
+        })
+    }
+
+    /// The table of the record with the key number.
+    fn table(&self, key_num: usize) -> String {
+        self.tables[key_num % self.tables.len()].clone()
+    }
+
+    fn build_key_name(&self, mut key_num: usize) -> String {
+        if !self.ordered_inserts {
+            key_num = self.key_hasher.hash(key_num as _) as _;
+        }
+        format!(
+            "{prefix}{key_num:0width$}",
+            prefix = self.key_prefix,
+            width = self.zero_padding
+        )
+    }
+
+    /// Choose the operation of the next transaction with the operation chooser of the current phase.
+    ///
+    /// The warm-up transactions take the current phase without advancing it.
+    fn next_op(&self) -> Op {
+        let transaction = if WARMUP.with(Cell::get) {
+            self.transactions.load(Ordering::Relaxed)
+        } else {
+            self.transactions.fetch_add(1, Ordering::Relaxed)
+        };
+        let phase = self
+            .operation_choosers
+            .partition_point(|(end, _)| *end <= transaction)
+            .min(self.operation_choosers.len() - 1);
+        self.operation_choosers[phase].1.next()
+    }
+
+    /// The key of a new record, a random UUID or derived from the key number.
+    fn new_key_name(&self, key_num: usize) -> String {
+        match &self.uuid_key_generator {
+            Some(generator) => format!("{}{}", self.key_prefix, generator.next()),
+            None => self.build_key_name(key_num),
+        }
+    }
+
+    /// Keep the inserted UUID key in the sample of the inserted keys, which stays uniform with reservoir sampling once
+    /// it reaches `max_tracked_keys`.
+    fn track_key(&self, key_num: usize, key: &str) {
+        if self.uuid_key_generator.is_none() {
+            return;
+        }
+        let mut tracked = self.tracked_keys.write();
+        tracked.inserted += 1;
+        if tracked.keys.len() < self.max_tracked_keys {
+            tracked.keys.push((key_num, key.to_string()));
+        } else {
+            let inserted = tracked.inserted;
+            let i = match &mut tracked.rng {
+                Some(rng) => rng.gen_range(0..inserted),
+                None => workload_rng().gen_range(0..inserted),
+            };
+            if let Some(slot) = tracked.keys.get_mut(i) {
+                *slot = (key_num, key.to_string());
+            }
+        }
+    }
+
+    /// Choose the key number and the key to operate on with the request distribution. For UUID keys, the key number
+    /// picks one of the sampled inserted keys.
+    fn next_key(&self) -> Result<(usize, String)> {
+        let key_num = self.next_key_num();
+        if self.uuid_key_generator.is_none() {
+            return Ok((key_num, self.build_key_name(key_num)));
+        }
+        let tracked = self.tracked_keys.read();
+        if tracked.keys.is_empty() {
+            return Err(anyhow!(
+                "no inserted uuid keys to choose, the load and run phases must use the same workload"
+            ));
+        }
+        Ok(tracked.keys[key_num % tracked.keys.len()].clone())
+    }
+
+    /// Read the written fields of the record back and check that they hold the written values, if `verify_writes` is
+    /// enabled.
+    fn verify_write(
+        &self,
+        db: &impl Db,
+        key_num: usize,
+        key: &str,
+        values: &HashMap<String, Value>,
+    ) -> Result<()> {
+        if !self.verify_writes {
+            return Ok(());
+        }
+        let fields = values.keys().cloned().collect();
+        let mut cells = self.measure(Op::VerifyRead, || {
+            db.read(self.table(key_num), key.to_string(), fields)
+        })?;
+        for (field, value) in values {
+            let Some(mut got) = cells.remove(field) else {
+                return Err(anyhow!("lost write of field {field}, key: {key}"));
+            };
+            if !got.content_eq(&value.clone().into_bytes()?)? {
+                return Err(anyhow!(
+                    "value mismatch after write of field {field}, key: {key}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn record_written(&self, values: &HashMap<String, Value>) {
+        let bytes = values.values().map(Value::len).sum::<usize>();
+        self.bytes_written.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn build_single_value(&self, key: &str) -> HashMap<String, Value> {
+        let (fields, sizes) = self.choose_written_fields(false);
+        self.build_field_values(key, fields, sizes)
+    }
+
+    fn build_values(&self, key: &str) -> HashMap<String, Value> {
+        let (fields, sizes) = self.choose_written_fields(true);
+        self.build_field_values(key, fields, sizes)
+    }
+
+    /// Choose the fields to read, empty for all the fields.
+    fn choose_read_fields(&self) -> HashSet<String> {
+        let mut fields = HashSet::new();
+        if !self.read_all_fields {
+            fields.insert(self.field_names[self.field_chooser.next()].clone());
+        } else if self.data_inategrity || self.read_all_fields_by_name {
+            fields.extend(self.field_names.iter().cloned());
+        }
+        fields
+    }
+
+    /// Choose the fields to write, all of them or a random one, and draw the lengths of their values.
+    fn choose_written_fields(&self, all_fields: bool) -> (Vec<String>, Vec<usize>) {
+        let (fields, sizes) = self.draw_written_fields(all_fields);
+        if let Some(histogram) = &self.field_length_histogram {
+            let mut histogram = histogram.lock();
+            for &size in &sizes {
+                record(&mut histogram, size as u64);
+            }
+        }
+        (fields, sizes)
+    }
+
+    /// Choose the distinct indices of the fields that an update writes.
+    fn choose_field_indices(&self) -> Vec<usize> {
+        let count = self
+            .fields_to_write_generator
+            .next()
+            .clamp(1, self.field_names.len());
+        if count == 1 {
+            return vec![self.field_chooser.next()];
+        }
+        // Draw from the field chooser until there are enough distinct fields, which takes a few more draws than the
+        // count unless most of the fields are written.
+        let mut chosen = vec![false; self.field_names.len()];
+        let mut indices = Vec::with_capacity(count);
+        while indices.len() < count {
+            let index = self.field_chooser.next();
+            if !std::mem::replace(&mut chosen[index], true) {
+                indices.push(index);
+            }
+        }
+        indices
+    }
+
+    /// Draw the fields to write and the lengths of their values, without recording the lengths.
+    fn draw_written_fields(&self, all_fields: bool) -> (Vec<String>, Vec<usize>) {
+        let indices = if all_fields {
+            (0..self.field_names.len()).collect_vec()
+        } else {
+            self.choose_field_indices()
+        };
+        let fields = indices
+            .iter()
+            .map(|&index| self.field_names[index].clone())
+            .collect_vec();
+        let mut sizes = Vec::with_capacity(fields.len());
+        if self.field_length_overrides.is_empty() {
+            self.field_length_generator
+                .next_batch(fields.len(), &mut sizes);
+        } else {
+            sizes.extend(
+                indices
+                    .iter()
+                    .map(|&index| match &self.field_length_overrides[index] {
+                        Some(generator) => generator.next(),
+                        None => self.field_length_generator.next(),
+                    }),
+            );
+        }
+        (fields, sizes)
+    }
+
+    /// Build the values of the fields with the given lengths.
+    fn build_field_values(
+        &self,
+        key: &str,
+        fields: Vec<String>,
+        sizes: Vec<usize>,
+    ) -> HashMap<String, Value> {
+        let mut ret = HashMap::with_capacity(fields.len());
+
+        for (field_key, size) in fields.into_iter().zip_eq(sizes) {
+            let value = if self.data_inategrity {
+                self.build_deterministic_value(size, key, field_key.as_str())
+                    .into()
+            } else {
+                self.build_random_value(size)
+            };
+            ret.insert(field_key, value);
+        }
+
+        ret
+    }
+
+    fn build_random_value(&self, size: usize) -> Value {
+        if let Some(generator) = &self.value_generator {
+            return generator.next_value(size);
+        }
+        let bytes = RandomBytes::new_with_compressibility(size, self.compressibility)
+            .with_binary(self.binary_values);
+        let bytes = match &self.value_rng {
+            Some(rng) => {
+                bytes.with_rng(StdRng::from_rng(&mut *rng.lock()).expect("derive rng from seed"))
+            }
+            None => bytes,
+        };
+        if self.verify_writes {
+            // Each clone of the random bytes draws new bytes, take them once so the written value can be compared with
+            // the value read back.
+            let mut bytes = bytes;
+            let mut buf = Vec::with_capacity(size);
+            bytes
+                .read_to_end(&mut buf)
+                .expect("random bytes never fail to read");
+            return buf.into();
+        }
+        bytes.into()
+    }
+
+    /// Build the value `{size}:{key}:{field}:{hash}:{hash}...` truncated to `size`. The value embeds its length, so a
+    /// truncated value doesn't verify as a shorter value.
+    fn build_deterministic_value(&self, size: usize, key: &str, field_key: &str) -> String {
+        let mut ret = String::with_capacity(size);
+        write!(&mut ret, "{size}:").unwrap();
+        ret.write_str(key).unwrap();
+        ret.write_char(':').unwrap();
+        ret.write_str(field_key).unwrap();
+        while ret.len() < size {
+            ret.write_char(':').unwrap();
+            let hash = ahash::RandomState::with_seed(0).hash_one(&ret);
+            write!(&mut ret, "{hash}").unwrap();
+        }
+        ret.truncate(size);
+        ret
+    }
+
+    fn verify_row(
+        &self,
+        key: String,
+        fields: HashSet<String>,
+        mut cells: HashMap<String, Value>,
+    ) -> Result<()> {
+        for field in fields.into_iter() {
+            let Some(mut value) = cells.remove(&field) else {
+                return Err(anyhow!("missing value for field {field}"));
+            };
+            // Compare without copying the stored value, it is only read out to report a mismatch.
+            let expected = self.build_deterministic_value(value.len(), &key, &field);
+            if !value.content_eq(expected.as_bytes())? {
+                let got = String::from_utf8_lossy(&value.read_all()?).into_owned();
+                return Err(anyhow!(
+                    "value mismitch for field {field}, got: {got}, expected: {expected}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify the rows of a scan. The scan result doesn't carry the keys of the rows, so the key of each row is
+    /// recovered from its deterministic values after the lengths, and all the fields of the row must agree with it.
+    ///
+    /// If the start key is given, the backend scans in the order of the keys, and the recovered keys must be in order
+    /// from the start key too. The recovered keys may be truncated to the field length, which keeps their order.
+    fn verify_scan(
+        &self,
+        fields: HashSet<String>,
+        start_key: Option<String>,
+        len: usize,
+        rows: Vec<HashMap<String, Value>>,
+    ) -> Result<()> {
+        if rows.len() > len {
+            return Err(anyhow!(
+                "too many records scanned, got: {}, expected at most: {len}",
+                rows.len()
+            ));
+        }
+        // Walk the fields in a fixed order, so the key of each record is always recovered from the same field.
+        let fields = self
+            .field_names
+            .iter()
+            .filter(|field| fields.contains(*field))
+            .collect_vec();
+
+        let mut prev = start_key;
+        for mut row in rows {
+            let mut values = Vec::with_capacity(fields.len());
+            for field in &fields {
+                let Some(value) = row.remove(*field) else {
+                    return Err(anyhow!("missing value for field {field}"));
+                };
+                values.push(String::from_utf8(value.into_bytes()?)?);
+            }
+            // A value that ends within the key holds a prefix of the key, which verifies against itself. Take the
+            // longest recovered key, the shorter values of the same record verify against it too.
+            let Some(key) = values
+                .iter()
+                .map(|got| self.recover_key(got))
+                .max_by_key(|key| key.len())
+                .map(|key| key.to_string())
+            else {
+                continue;
+            };
+            for (field, got) in fields.iter().zip_eq(values) {
+                self.verify_value(&key, field, got)?;
+            }
+            if let Some(prev) = &mut prev {
+                let truncated = &prev[..prev.len().min(key.len())];
+                if truncated > key.as_str() {
+                    return Err(anyhow!("record {key} is scanned after {prev} out of order"));
+                }
+                *prev = key;
+            }
+        }
+        Ok(())
+    }
+
+    /// Recover the key, or a prefix of it if the value ends within the key, from a deterministic value
+    /// `{len}:{key}:{field}...`. Only the key prefix may contain ':', so the key ends at the first ':' after it.
+    fn recover_key<'a>(&self, value: &'a str) -> &'a str {
+        let rest = value.split_once(':').map_or("", |(_, rest)| rest);
+        match rest.strip_prefix(self.key_prefix.as_str()) {
+            Some(suffix) => {
+                &rest[..rest.len() - suffix.len() + suffix.find(':').unwrap_or(suffix.len())]
+            }
+            None => rest,
+        }
+    }
+
+    fn verify_value(&self, key: &str, field: &str, got: String) -> Result<()> {
+        // The deterministic value is only decided by its length, key and field. Take the length of the stored value
+        // instead of drawing a new one from the field length generator, the embedded length must agree with it.
+        let expected = self.build_deterministic_value(got.len(), key, field);
+        if got != expected {
+            return Err(anyhow!(
+                "value mismitch for field {field}, got: {got}, expected: {expected}"
+            ));
+        }
+        Ok(())
+    }
+
+    fn next_key_num(&self) -> usize {
+        // The insert key sequencer starts at the record count, which is positive, so the keys below it are loaded and
+        // `last()` never underflows before the first transaction insert is acknowledged.
+        match &self.key_chooser {
+            KeyChooser::Absolute(generator) => loop {
+                let key_num = generator.next();
+                if key_num <= self.transaction_insert_key_sequencer.last() {
+                    return key_num;
+                }
+            },
+            KeyChooser::Recent(generator) => loop {
+                let last = self.transaction_insert_key_sequencer.last();
+                if let Some(key_num) = last.checked_sub(generator.next()) {
+                    return key_num;
+                }
+            },
+        }
+    }
+
+    /// Run a database operation and record its latency, no matter whether it succeeds, unless it is warming up.
+    ///
+    /// The error of the operation, e.g. a [`DbError`](crate::db::DbError), is converted into an [`anyhow::Error`],
+    /// which keeps its kind for [`is_retryable`].
+    fn measure<T, E>(&self, op: Op, f: impl FnOnce() -> std::result::Result<T, E>) -> Result<T>
+    where
+        E: Into<anyhow::Error>,
+    {
+        let start = Instant::now();
+        let res = f().map_err(Into::into);
+        if WARMUP.with(Cell::get) {
+            return res;
+        }
+        self.measurements.measure(op.clone(), start.elapsed());
+        self.measure_intended(op);
+        res
+    }
+
+    /// Record the latency from the intended start time of the transaction, if it is paced by a rate limiter.
+    fn measure_intended(&self, op: Op) {
+        if let Some(start) = INTENDED_START.with(Cell::get) {
+            self.measurements.measure_intended(op, start.elapsed());
+        }
+    }
+
+    /// Call `f` until it succeeds, for at most `policy.limit` retries with exponential backoff from `policy.interval`
+    /// up to `policy.max_interval`. Returns the error of the last attempt if all the attempts fail.
+    ///
+    /// Only the transient errors and the conflicts are retried. The other errors, including the ones that are not
+    /// classified as a [`DbError`](crate::db::DbError), are returned at once, see [`is_retryable`].
+    fn retry<T, F>(&self, label: &str, policy: &RetryPolicy, f: F) -> Result<T>
+    where
+        F: Fn() -> Result<T>,
+    {
+        let limits = policy.limit;
+        // The first attempt is not a retry, so there are at most `limits + 1` attempts.
+        let mut retry = 0;
+        loop {
+            let e = match f() {
+                Ok(res) => return Ok(res),
+                Err(e) => e,
+            };
+
+            if limits == 0 || !is_retryable(&e) {
+                // Not retried at all, keep the error as it is.
+                return Err(e);
+            }
+            if retry == limits {
+                return Err(anyhow!("{label} exceeded retry limit {limits}: {e}"));
+            }
+
+            tracing::warn!("{label} error: {e}");
+            tracing::warn!("retrying {label}, retry times: {retry}");
+
+            let wait = match &self.retry_rng {
+                Some(rng) => backoff(
+                    policy.interval,
+                    policy.max_interval,
+                    retry,
+                    &mut *rng.lock(),
+                ),
+                None => backoff(
+                    policy.interval,
+                    policy.max_interval,
+                    retry,
+                    &mut workload_rng(),
+                ),
+            };
+            std::thread::sleep(wait);
+            retry += 1;
+        }
+    }
+}
+
+/// How to retry the failed operations of a kind, see [`CoreWorkload::retry`].
+#[derive(Debug, Clone, Copy)]
+struct RetryPolicy {
+    limit: usize,
+    interval: Duration,
+    max_interval: Duration,
+}
+
+impl RetryPolicy {
+    /// Create a policy with the intervals in seconds.
+    fn new(limit: usize, interval: usize, max_interval: usize) -> Self {
+        Self {
+            limit,
+            interval: Duration::from_secs(interval as _),
+            max_interval: Duration::from_secs(max_interval as _),
+        }
+    }
+}
+
+/// Record the accessed key in the span of a transaction, only at trace level to keep the key out of the hot path.
+fn record_key(span: &EnteredSpan, key: &str) {
+    if tracing::enabled!(Level::TRACE) {
+        span.record("key", key);
+    }
+}
+
+/// The wait before the `retry`-th retry, which is `interval * 2^retry` capped at `max_interval`, with ±20% jitter drawn
+/// from `rng`.
+fn backoff(
+    interval: Duration,
+    max_interval: Duration,
+    retry: usize,
+    rng: &mut impl Rng,
+) -> Duration {
+    let exp = 2f64.powi(retry.min(i32::MAX as usize) as i32);
+    let wait = (interval.as_secs_f64() * exp).min(max_interval.as_secs_f64());
+    Duration::from_secs_f64(wait * rng.gen_range(0.8..=1.2))
+}
+
+/// Time series workload.
+pub mod timeseries;
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn table() -> String {
+        "ycsb".to_string()
+    }
+
+    pub fn table_count() -> usize {
+        1
+    }
+
+    pub fn fields() -> usize {
+        10
+    }
+
+    pub fn key_prefix() -> String {
+        "user".to_string()
+    }
+
+    pub fn field_name_prefix() -> String {
+        "field".to_string()
+    }
+
+    pub fn field_length_distribution() -> String {
+        "constant".to_string()
+    }
+
+    pub fn min_field_length() -> usize {
+        1
+    }
+
+    pub fn max_field_length() -> usize {
+        100
+    }
+
+    pub fn field_length_histogram_file() -> String {
+        "hist.txt".to_string()
+    }
+
+    pub fn field_value_source() -> String {
+        String::new()
+    }
+
+    pub fn compressibility() -> f64 {
+        1.0
+    }
+
+    pub fn field_length_stats() -> bool {
+        false
+    }
+
+    pub fn binary_values() -> bool {
+        false
+    }
+
+    pub fn record_count() -> usize {
+        0
+    }
+
+    pub fn request_distribution() -> String {
+        "uniform".to_string()
+    }
+
+    pub fn exponential_percentile() -> f64 {
+        95.0
+    }
+
+    pub fn exponential_frac() -> f64 {
+        0.8571428571
+    }
+
+    pub fn hotspot_data_fraction() -> f64 {
+        0.2
+    }
+
+    pub fn hotspot_operation_fraction() -> f64 {
+        0.8
+    }
+
+    pub fn min_scan_length() -> usize {
+        1
+    }
+
+    pub fn max_scan_length() -> usize {
+        1000
+    }
+
+    pub fn scan_length_distribution() -> String {
+        "uniform".to_string()
+    }
+
+    pub fn insert_start() -> usize {
+        0
+    }
+
+    pub fn insert_count() -> Option<usize> {
+        None
+    }
+
+    pub fn operation_count() -> usize {
+        1000
+    }
+
+    pub fn zero_padding() -> usize {
+        1
+    }
+
+    pub fn read_all_fields() -> bool {
+        true
+    }
+
+    pub fn read_all_fields_by_name() -> bool {
+        false
+    }
+
+    pub fn write_all_fields() -> bool {
+        false
+    }
+
+    pub fn fields_to_write_distribution() -> String {
+        "constant".to_string()
+    }
+
+    pub fn min_fields_to_write() -> usize {
+        1
+    }
+
+    pub fn max_fields_to_write() -> usize {
+        1
+    }
+
+    pub fn field_length_overrides() -> std::collections::HashMap<String, (String, usize, usize)> {
+        Default::default()
+    }
+
+    pub fn data_integrity() -> bool {
+        false
+    }
+
+    pub fn insert_order() -> String {
+        "hashed".to_string()
+    }
+
+    pub fn key_hasher() -> crate::utils::KeyHasher {
+        crate::utils::KeyHasher::Fnv64
+    }
+
+    pub fn read_proportion() -> f64 {
+        0.95
+    }
+
+    pub fn update_proportion() -> f64 {
+        0.05
+    }
+
+    pub fn insert_proportion() -> f64 {
+        0.0
+    }
+
+    pub fn scan_proportion() -> f64 {
+        0.0
+    }
+
+    pub fn read_modify_write_proportion() -> f64 {
+        0.0
+    }
+
+    pub fn delete_proportion() -> f64 {
+        0.0
+    }
+
+    pub fn phases() -> Vec<crate::workload::PhaseConfig> {
+        vec![]
+    }
+
+    pub fn insertion_retry_limit() -> usize {
+        0
+    }
+
+    pub fn insertion_retry_interval() -> usize {
+        3
+    }
+
+    pub fn retry_max_interval() -> usize {
+        60
+    }
+
+    pub fn retry_limit() -> usize {
+        0
+    }
+
+    pub fn retry_interval() -> usize {
+        3
+    }
+
+    pub fn seed() -> Option<u64> {
+        None
+    }
+
+    pub fn target_throughput() -> usize {
+        0
+    }
+
+    pub fn think_time_distribution() -> String {
+        "constant".to_string()
+    }
+
+    pub fn min_think_time() -> usize {
+        0
+    }
+
+    pub fn max_think_time() -> usize {
+        0
+    }
+
+    pub fn max_bytes_written() -> Option<usize> {
+        None
+    }
+
+    pub fn key_format() -> String {
+        "numeric".to_string()
+    }
+
+    pub fn max_tracked_keys() -> usize {
+        1_000_000
+    }
+
+    pub fn verify_writes() -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+
+    use super::*;
+    use crate::db::{faulty::FaultyDb, memory::MemoryDb, DbError, DbResult};
+
+    /// A [`MemoryDb`] that loses every write silently.
+    #[derive(Debug, Clone, Default)]
+    struct LossyDb(MemoryDb);
+
+    impl Db for LossyDb {
+        type Config = ();
+
+        fn from_config(_: Self::Config) -> DbResult<Self> {
+            Ok(Self::default())
+        }
+
+        fn init(&self) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn cleanup(&self) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn insert(&self, table: String, key: String, _: HashMap<String, Value>) -> DbResult<()> {
+            // Keep the record, but lose the values.
+            self.0.insert(table, key, HashMap::new())
+        }
+
+        fn read(
+            &self,
+            table: String,
+            key: String,
+            fields: HashSet<String>,
+        ) -> DbResult<HashMap<String, Value>> {
+            self.0.read(table, key, fields)
+        }
+
+        fn update(&self, _: String, _: String, _: HashMap<String, Value>) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn scan(
+            &self,
+            table: String,
+            start_key: String,
+            len: usize,
+            fields: HashSet<String>,
+        ) -> DbResult<HashMap<String, Vec<Value>>> {
+            self.0.scan(table, start_key, len, fields)
+        }
+
+        fn delete(&self, table: String, key: String) -> DbResult<()> {
+            self.0.delete(table, key)
+        }
+    }
+
+    /// A [`MemoryDb`] that records the start keys and the lengths of the scans.
+    #[derive(Debug, Clone, Default)]
+    struct ScanRecordingDb {
+        db: MemoryDb,
+        scans: Arc<Mutex<Vec<(String, usize)>>>,
+    }
+
+    impl Db for ScanRecordingDb {
+        type Config = ();
+
+        fn from_config(_: Self::Config) -> DbResult<Self> {
+            Ok(Self::default())
+        }
+
+        fn init(&self) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn cleanup(&self) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn insert(
+            &self,
+            table: String,
+            key: String,
+            values: HashMap<String, Value>,
+        ) -> DbResult<()> {
+            self.db.insert(table, key, values)
+        }
+
+        fn read(
+            &self,
+            table: String,
+            key: String,
+            fields: HashSet<String>,
+        ) -> DbResult<HashMap<String, Value>> {
+            self.db.read(table, key, fields)
+        }
+
+        fn update(
+            &self,
+            table: String,
+            key: String,
+            values: HashMap<String, Value>,
+        ) -> DbResult<()> {
+            self.db.update(table, key, values)
+        }
+
+        fn scan(
+            &self,
+            table: String,
+            start_key: String,
+            len: usize,
+            fields: HashSet<String>,
+        ) -> DbResult<HashMap<String, Vec<Value>>> {
+            self.scans.lock().push((start_key.clone(), len));
+            self.db.scan(table, start_key, len, fields)
+        }
+
+        fn delete(&self, table: String, key: String) -> DbResult<()> {
+            self.db.delete(table, key)
+        }
+    }
+
+    /// A [`MemoryDb`] that returns the scanned records in the reverse order, like a hash-partitioned store.
+    #[derive(Debug, Clone, Default)]
+    struct ShufflingDb {
+        db: MemoryDb,
+        /// What [`Db::scan_is_ordered`] reports, regardless of the actual order.
+        ordered: bool,
+    }
+
+    impl Db for ShufflingDb {
+        type Config = ();
+
+        fn from_config(_: Self::Config) -> DbResult<Self> {
+            Ok(Self::default())
+        }
+
+        fn init(&self) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn cleanup(&self) -> DbResult<()> {
+            Ok(())
+        }
+
+        fn insert(
+            &self,
+            table: String,
+            key: String,
+            values: HashMap<String, Value>,
+        ) -> DbResult<()> {
+            self.db.insert(table, key, values)
+        }
+
+        fn read(
+            &self,
+            table: String,
+            key: String,
+            fields: HashSet<String>,
+        ) -> DbResult<HashMap<String, Value>> {
+            self.db.read(table, key, fields)
+        }
+
+        fn update(
+            &self,
+            table: String,
+            key: String,
+            values: HashMap<String, Value>,
+        ) -> DbResult<()> {
+            self.db.update(table, key, values)
+        }
+
+        fn scan(
+            &self,
+            table: String,
+            start_key: String,
+            len: usize,
+            fields: HashSet<String>,
+        ) -> DbResult<HashMap<String, Vec<Value>>> {
+            self.scan_rows(table, start_key, len, fields)
+                .map(crate::db::into_columns)
+        }
+
+        fn scan_rows(
+            &self,
+            table: String,
+            start_key: String,
+            len: usize,
+            fields: HashSet<String>,
+        ) -> DbResult<Vec<HashMap<String, Value>>> {
+            let mut rows = self.db.scan_rows(table, start_key, len, fields)?;
+            rows.reverse();
+            Ok(rows)
+        }
+
+        fn scan_is_ordered(&self) -> bool {
+            self.ordered
+        }
+
+        fn delete(&self, table: String, key: String) -> DbResult<()> {
+            self.db.delete(table, key)
+        }
+    }
+
+    /// A subscriber that captures the names of the new spans and the keys recorded to them.
+    #[derive(Debug, Default)]
+    struct CapturingSubscriber {
+        spans: Mutex<Vec<String>>,
+        keys: Mutex<Vec<String>>,
+        ids: AtomicU64,
+    }
+
+    impl tracing::field::Visit for &CapturingSubscriber {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn Debug) {
+            if field.name() == "key" {
+                self.keys.lock().push(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl tracing::Subscriber for CapturingSubscriber {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            self.spans.lock().push(span.metadata().name().to_string());
+            tracing::span::Id::from_u64(self.ids.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            values.record(&mut &*self);
+        }
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    fn workload(builder: CoreWorkloadConfigBuilder) -> CoreWorkload {
+        CoreWorkload::new(builder.build().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_verify_writes() {
+        // Random values with the default config, which are neither deterministic nor seeded.
+        let config = CoreWorkloadConfig::builder()
+            .record_count(10)
+            .read_proportion(0.0)
+            .update_proportion(0.5)
+            .insert_proportion(0.5)
+            .verify_writes(true);
+
+        let w = workload(config.clone());
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            w.transaction(db.clone()).unwrap();
+        }
+        assert!(w.measurements().report().operations[&Op::VerifyRead].count >= 110);
+
+        let w = workload(config.clone());
+        let db = LossyDb::default();
+        let e = w.load(db.clone()).unwrap_err();
+        assert!(e.to_string().contains("lost write"), "{e}");
+
+        // The lost updates leave the loaded values behind.
+        let w = workload(config.insert_proportion(0.0).update_proportion(1.0));
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        let e = w.transaction(LossyDb(db)).unwrap_err();
+        assert!(e.to_string().contains("value mismatch"), "{e}");
+    }
+
+    #[test]
+    fn test_latest_request_distribution() {
+        // The default record count is unbounded, which builds in constant time.
+        let w = workload(CoreWorkloadConfig::builder().request_distribution("latest"));
+        assert!(w.next_key_num() < usize::MAX);
+
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(100)
+                .request_distribution("latest"),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            w.transaction(db.clone()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_bad_configs() {
+        let bad_configs = [
+            CoreWorkloadConfig::builder().field_length_distribution("unknown"),
+            CoreWorkloadConfig::builder().scan_length_distribution("unknown"),
+            CoreWorkloadConfig::builder().request_distribution("unknown"),
+            CoreWorkloadConfig::builder().fields_to_write_distribution("unknown"),
+            CoreWorkloadConfig::builder().think_time_distribution("unknown"),
+            CoreWorkloadConfig::builder().key_format("unknown"),
+            CoreWorkloadConfig::builder()
+                .field_length_distribution("histogram")
+                .field_length_histogram_file("/nonexistent"),
+            CoreWorkloadConfig::builder()
+                .field_length_distribution("gaussian")
+                .min_field_length(100)
+                .max_field_length(10),
+            CoreWorkloadConfig::builder()
+                .field_length_distribution("zipfian")
+                .min_field_length(100)
+                .max_field_length(10),
+            CoreWorkloadConfig::builder()
+                .field_length_distribution("uniform")
+                .min_field_length(100)
+                .max_field_length(10),
+            CoreWorkloadConfig::builder()
+                .field_length_distribution("lognormal")
+                .min_field_length(100)
+                .max_field_length(10),
+            CoreWorkloadConfig::builder()
+                .scan_length_distribution("zipfian")
+                .min_scan_length(100)
+                .max_scan_length(10),
+            CoreWorkloadConfig::builder()
+                .fields_to_write_distribution("zipfian")
+                .min_fields_to_write(5)
+                .max_fields_to_write(2),
+            CoreWorkloadConfig::builder()
+                .think_time_distribution("uniform")
+                .min_think_time(100)
+                .max_think_time(10),
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .insert_start(20),
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .insert_start(5)
+                .insert_count(10),
+            CoreWorkloadConfig::builder().fields(0),
+            CoreWorkloadConfig::builder().table_count(0),
+            CoreWorkloadConfig::builder()
+                .read_proportion(0.0)
+                .update_proportion(0.0),
+        ];
+        for (i, config) in bad_configs.into_iter().enumerate() {
+            let res = std::panic::catch_unwind(|| config.build());
+            match res {
+                Ok(Err(_)) => {}
+                Ok(Ok(_)) => panic!("bad config {i} is accepted"),
+                Err(_) => panic!("bad config {i} panics"),
+            }
+        }
+
+        // The min of a constant distribution is ignored.
+        CoreWorkloadConfig::builder()
+            .min_field_length(100)
+            .max_field_length(10)
+            .build()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_insert_count() {
+        for distribution in [
+            "uniform",
+            "zipfian",
+            "sequential",
+            "exponential",
+            "hotspot",
+            "latest",
+        ] {
+            let res = CoreWorkloadConfig::builder()
+                .record_count(10)
+                .insert_count(0)
+                .request_distribution(distribution)
+                .build();
+            assert!(res.is_err(), "{distribution}");
+        }
+        // An insert start at the record count leaves no records either.
+        assert!(CoreWorkloadConfig::builder()
+            .record_count(10)
+            .insert_start(10)
+            .build()
+            .is_err());
+
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(100)
+                .insert_start(10)
+                .insert_count(20),
+        );
+        assert_eq!(w.insert_count(), 20);
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        assert_eq!(db.len("ycsb"), 20);
+        assert!(db.get("ycsb", &w.build_key_name(10)).is_some());
+        assert!(db.get("ycsb", &w.build_key_name(29)).is_some());
+        assert!(db.get("ycsb", &w.build_key_name(30)).is_none());
+    }
+
+    #[test]
+    fn test_retry_by_error_kind() {
+        let kinds = [
+            (DbError::NotFound as fn(_) -> _, false),
+            (DbError::Conflict, true),
+            (DbError::Transient, true),
+            (DbError::Fatal, false),
+            (DbError::Other, false),
+        ];
+        for (kind, retryable) in kinds {
+            let w = workload(
+                CoreWorkloadConfig::builder()
+                    .record_count(10)
+                    .read_proportion(1.0)
+                    .update_proportion(0.0)
+                    .read_retry_limit(2)
+                    .read_retry_interval(0),
+            );
+            let db = MemoryDb::new();
+            w.load(db.clone()).unwrap();
+
+            let db = FaultyDb::new(db)
+                .with_failures(Operation::Read, 2)
+                .with_error_kind(kind);
+            let res = w.transaction(db.clone());
+            if retryable {
+                // Both failures are retried within the limit.
+                assert_eq!(res.unwrap(), Op::Read);
+                assert_eq!(db.remaining_failures(), 0);
+            } else {
+                // Failed at the first attempt.
+                let e = res.unwrap_err();
+                assert!(!is_retryable(&e), "{e:?}");
+                assert_eq!(db.remaining_failures(), 1);
+            }
+        }
+
+        // More failures than the retries.
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .read_proportion(1.0)
+                .update_proportion(0.0)
+                .read_retry_limit(2)
+                .read_retry_interval(0),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        let db = FaultyDb::new(db).with_failures(Operation::Read, 5);
+        let e = w.transaction(db.clone()).unwrap_err();
+        assert!(e.to_string().contains("exceeded retry limit 2"), "{e}");
+        assert_eq!(db.remaining_failures(), 2);
+
+        // The errors that are not classified as a `DbError` fail fast.
+        let attempts = AtomicUsize::new(0);
+        let e = w
+            .retry("read", &w.read_retry, || -> Result<()> {
+                attempts.fetch_add(1, Ordering::Relaxed);
+                Err(anyhow!("unclassified error"))
+            })
+            .unwrap_err();
+        assert_eq!(e.to_string(), "unclassified error");
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_insert_retry_by_error_kind() {
+        let w = |record_count| {
+            workload(
+                CoreWorkloadConfig::builder()
+                    .record_count(record_count)
+                    .insertion_retry_limit(3)
+                    .insertion_retry_interval(0),
+            )
+        };
+
+        // The transient errors of the drivers are retried.
+        let db = FaultyDb::new(MemoryDb::new()).with_failures(Operation::Insert, 3);
+        w(10).load(db.clone()).unwrap();
+        assert_eq!(db.remaining_failures(), 0);
+        assert_eq!(db.inner().len("ycsb"), 10);
+
+        // The unclassified errors are not retried, unlike YCSB.
+        let db = FaultyDb::new(MemoryDb::new())
+            .with_failures(Operation::Insert, 3)
+            .with_error_kind(DbError::Other);
+        let e = w(10).load(db.clone()).unwrap_err();
+        assert!(!is_retryable(&e), "{e:?}");
+        assert_eq!(db.remaining_failures(), 2);
+        assert_eq!(db.inner().len("ycsb"), 0);
+    }
+
+    #[test]
+    fn test_phases() {
+        let phases = vec![
+            PhaseConfig {
+                operation_count: 10,
+                insert_proportion: 1.0,
+                ..Default::default()
+            },
+            PhaseConfig {
+                operation_count: 5,
+                update_proportion: 1.0,
+                ..Default::default()
+            },
+            // The operation count of the last phase is ignored.
+            PhaseConfig {
+                operation_count: 1,
+                read_proportion: 1.0,
+                ..Default::default()
+            },
+        ];
+        // The proportions out of the phases are ignored.
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .scan_proportion(1.0)
+                .phases(phases.clone()),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        let ops = (0..30)
+            .map(|_| w.transaction(db.clone()).unwrap())
+            .collect::<Vec<_>>();
+        assert!(ops[..10].iter().all(|op| *op == Op::Insert));
+        assert!(ops[10..15].iter().all(|op| *op == Op::Update));
+        assert!(ops[15..].iter().all(|op| *op == Op::Read));
+        assert_eq!(db.len("ycsb"), 20);
+
+        // Only the last phase may have no operation count.
+        let mut bad_phases = phases.clone();
+        bad_phases[1].operation_count = 0;
+        assert!(CoreWorkloadConfig::builder()
+            .phases(bad_phases)
+            .build()
+            .is_err());
+        let mut bad_phases = phases;
+        bad_phases[1].update_proportion = 0.0;
+        assert!(CoreWorkloadConfig::builder()
+            .phases(bad_phases)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(100)
+                .insert_start(50)
+                .insert_count(20)
+                .read_proportion(1.0)
+                .update_proportion(0.0)
+                .request_distribution("zipfian"),
+        );
+        let plan = w.plan(1000).unwrap();
+        assert_eq!(plan.len(), 1000);
+        let keys = (50..70)
+            .map(|n| w.build_key_name(n))
+            .collect::<HashSet<_>>();
+        for planned in plan {
+            assert_eq!(planned.op, Op::Read);
+            assert!(keys.contains(&planned.key), "{}", planned.key);
+            assert!(planned.written_fields.is_empty());
+            assert_eq!(planned.scan_length, 0);
+        }
+
+        // There are no UUID keys to choose before the load.
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .key_format("uuid"),
+        );
+        let e = w.plan(10).unwrap_err();
+        assert!(e.to_string().contains("no inserted uuid keys"), "{e}");
+        w.load(MemoryDb::new()).unwrap();
+        let keys = w.tracked_keys.read().keys.clone();
+        for planned in w.plan(10).unwrap() {
+            assert!(
+                keys.iter().any(|(_, key)| *key == planned.key),
+                "{}",
+                planned.key
+            );
+        }
+    }
+
+    #[test]
+    fn test_plan_before_run() {
+        let run = |plan: bool| {
+            let w = workload(
+                CoreWorkloadConfig::builder()
+                    .record_count(10)
+                    .seed(42)
+                    .phases(vec![
+                        PhaseConfig {
+                            operation_count: 20,
+                            read_proportion: 0.5,
+                            update_proportion: 0.5,
+                            ..Default::default()
+                        },
+                        PhaseConfig {
+                            operation_count: 1,
+                            insert_proportion: 0.5,
+                            scan_proportion: 0.5,
+                            ..Default::default()
+                        },
+                    ]),
+            );
+            let db = MemoryDb::new();
+            w.load(db.clone()).unwrap();
+            if plan {
+                assert_eq!(w.plan(30).unwrap().len(), 30);
+            }
+            (0..40)
+                .map(|_| w.transaction(db.clone()).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let ops = run(false);
+        assert!(ops[..20]
+            .iter()
+            .all(|op| matches!(op, Op::Read | Op::Update)));
+        assert!(ops[20..]
+            .iter()
+            .all(|op| matches!(op, Op::Insert | Op::Scan)));
+        assert_eq!(run(true), ops);
+    }
+
+    #[test]
+    fn test_seed() {
+        fn run(config: CoreWorkloadConfigBuilder) -> (Vec<Op>, Vec<BTreeMap<String, Vec<u8>>>) {
+            let w = workload(config);
+            let db = MemoryDb::new();
+            w.load(db.clone()).unwrap();
+            let ops = (0..200)
+                .map(|_| w.transaction(db.clone()).unwrap())
+                .collect();
+            let rows = db
+                .scan_rows(
+                    "ycsb".to_string(),
+                    String::new(),
+                    usize::MAX,
+                    HashSet::new(),
+                )
+                .unwrap()
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|(field, value)| (field, value.into_bytes().unwrap()))
+                        .collect()
+                })
+                .collect();
+            (ops, rows)
+        }
+
+        for (request, field_length, scan_length) in [
+            ("uniform", "uniform", "uniform"),
+            ("zipfian", "zipfian", "zipfian"),
+            ("hotspot", "gaussian", "uniform"),
+            ("exponential", "lognormal", "zipfian"),
+            ("latest", "zipfian", "uniform"),
+        ] {
+            let config = CoreWorkloadConfig::builder()
+                .record_count(100)
+                .read_proportion(0.2)
+                .update_proportion(0.2)
+                .insert_proportion(0.2)
+                .scan_proportion(0.2)
+                .read_modify_write_proportion(0.2)
+                .write_all_fields(false)
+                .fields_to_write_distribution("zipfian")
+                .max_fields_to_write(5)
+                .request_distribution(request)
+                .field_length_distribution(field_length)
+                .scan_length_distribution(scan_length)
+                .seed(42);
+            let (ops, rows) = run(config.clone());
+            assert_eq!(
+                rows.len(),
+                100 + ops.iter().filter(|op| **op == Op::Insert).count()
+            );
+            assert_eq!((ops, rows), run(config.clone()), "{request}");
+
+            let (plan, rerun) = (
+                workload(config.clone()).plan(200).unwrap(),
+                workload(config).plan(200).unwrap(),
+            );
+            assert_eq!(plan, rerun, "{request}");
+        }
+
+        // The jitter of the retry backoff too.
+        let backoffs = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..5)
+                .map(|retry| {
+                    backoff(
+                        Duration::from_millis(10),
+                        Duration::from_secs(1),
+                        retry,
+                        &mut rng,
+                    )
+                })
+                .collect_vec()
+        };
+        assert_eq!(backoffs(42), backoffs(42));
+    }
+
+    #[test]
+    fn test_data_integrity() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .read_proportion(1.0)
+                .update_proportion(0.0)
+                .data_integrity(true),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            assert_eq!(w.transaction(db.clone()).unwrap(), Op::Read);
+        }
+
+        // Corrupt every record.
+        for key_num in 0..10 {
+            let key = w.build_key_name(key_num);
+            db.update(
+                "ycsb".to_string(),
+                key,
+                HashMap::from([("field0".to_string(), "x".to_string().into())]),
+            )
+            .unwrap();
+        }
+        let e = w.transaction(db.clone()).unwrap_err();
+        assert!(
+            e.to_string().contains("value mismitch for field field0"),
+            "{e}"
+        );
+    }
+
+    #[test]
+    fn test_scan_key_chooser() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(100)
+                .insert_start(50)
+                .insert_count(50)
+                .read_proportion(0.0)
+                .update_proportion(0.0)
+                .scan_proportion(1.0),
+        );
+        let db = ScanRecordingDb::default();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            assert_eq!(w.transaction(db.clone()).unwrap(), Op::Scan);
+        }
+
+        // The scans start from the loaded keys, and leave the insert key sequence as it is.
+        let keys = (50..100)
+            .map(|n| w.build_key_name(n))
+            .collect::<HashSet<_>>();
+        let scans = db.scans.lock();
+        assert_eq!(scans.len(), 100);
+        assert!(scans.iter().all(|(start_key, _)| keys.contains(start_key)));
+        assert_eq!(w.transaction_insert_key_sequencer.last(), 99);
+        assert_eq!(w.transaction_insert_key_sequencer.next(), 100);
+    }
+
+    #[test]
+    fn test_operation_proportions() {
+        let res = CoreWorkloadConfig::builder()
+            .read_proportion(0.0)
+            .update_proportion(0.0)
+            .insert_proportion(0.0)
+            .scan_proportion(0.0)
+            .read_modify_write_proportion(0.0)
+            .delete_proportion(0.0)
+            .build();
+        assert!(res.is_err());
+
+        // Normalized to 0.5 each.
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .read_proportion(0.55)
+                .update_proportion(0.55)
+                .seed(0),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        let reads = (0..1000)
+            .filter(|_| w.transaction(db.clone()).unwrap() == Op::Read)
+            .count();
+        assert!((400..600).contains(&reads), "{reads}");
+    }
+
+    #[test]
+    fn test_field_value_source() {
+        let path = std::env::temp_dir().join(format!("yay-values-{}", std::process::id()));
+        std::fs::write(&path, b"0123456789").unwrap();
+        let w = CoreWorkloadConfig::builder()
+            .record_count(1)
+            .fields(2)
+            .field_length_distribution("constant")
+            .max_field_length(4)
+            .field_value_source(path.to_str().unwrap())
+            .build()
+            .and_then(CoreWorkload::new);
+        std::fs::remove_file(&path).unwrap();
+        let w = w.unwrap();
+
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        let record = db.get("ycsb", &w.build_key_name(0)).unwrap();
+        let mut values = record.into_values().collect_vec();
+        values.sort();
+        assert_eq!(values, vec![b"0123".to_vec(), b"4567".to_vec()]);
+    }
+
+    #[test]
+    fn test_from_properties() {
+        // The standard workload A of YCSB.
+        let workloada = "\
+# Yahoo! Cloud System Benchmark
+# Workload A: Update heavy workload
+#   Application example: Session store recording recent actions
+#
+#   Read/update ratio: 50/50
+#   Default data size: 1 KB records (10 fields, 100 bytes each, plus key)
+#   Request distribution: zipfian
+
+recordcount=1000
+operationcount=1000
+workload=site.ycsb.workloads.CoreWorkload
+
+readallfields=true
+
+readproportion=0.5
+updateproportion=0.5
+scanproportion=0
+insertproportion=0
+
+requestdistribution=zipfian
+";
+        let config = CoreWorkloadConfig::from_properties(workloada.as_bytes()).unwrap();
+        assert_eq!(config.record_count, 1000);
+        assert_eq!(config.operation_count, 1000);
+        assert!(config.read_all_fields);
+        assert_eq!(config.read_proportion, 0.5);
+        assert_eq!(config.update_proportion, 0.5);
+        assert_eq!(config.scan_proportion, 0.0);
+        assert_eq!(config.insert_proportion, 0.0);
+        assert_eq!(config.request_distribution, "zipfian");
+        // The defaults of the properties that are not set.
+        assert_eq!(config.fields, 10);
+        assert_eq!(config.max_field_length, 100);
+
+        let w = CoreWorkload::new(config).unwrap();
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        assert_eq!(db.len("ycsb"), 1000);
+
+        assert!(CoreWorkloadConfig::from_properties("recordcount=many".as_bytes()).is_err());
+        assert!(CoreWorkloadConfig::from_properties("recordcount".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_fields() {
+        assert!(CoreWorkloadConfig::builder().fields(0).build().is_err());
+
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(1)
+                .fields(1)
+                .read_all_fields(false)
+                .write_all_fields(false),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        for _ in 0..10 {
+            w.transaction(db.clone()).unwrap();
+        }
+        assert_eq!(db.get("ycsb", &w.build_key_name(0)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_scan_integrity() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .read_proportion(0.0)
+                .update_proportion(0.0)
+                .scan_proportion(1.0)
+                .data_integrity(true),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            assert_eq!(w.transaction(db.clone()).unwrap(), Op::Scan);
+        }
+
+        // Corrupt every record, so each scan returns a corrupted row.
+        for key_num in 0..10 {
+            db.update(
+                "ycsb".to_string(),
+                w.build_key_name(key_num),
+                HashMap::from([("field3".to_string(), "x".to_string().into())]),
+            )
+            .unwrap();
+        }
+        let e = w.transaction(db.clone()).unwrap_err();
+        assert!(
+            e.to_string().contains("value mismitch for field field3"),
+            "{e}"
+        );
+
+        // The key prefix may contain ':', with the values that end within the key or the field too.
+        for max_field_length in [100, 12] {
+            let w = workload(
+                CoreWorkloadConfig::builder()
+                    .record_count(10)
+                    .read_proportion(0.0)
+                    .update_proportion(0.0)
+                    .scan_proportion(1.0)
+                    .key_prefix("user:")
+                    .field_length_distribution("uniform")
+                    .max_field_length(max_field_length)
+                    .data_integrity(true),
+            );
+            assert_eq!(
+                w.recover_key(&w.build_deterministic_value(100, "user:12", "field0")),
+                "user:12"
+            );
+            assert_eq!(
+                w.recover_key(&w.build_deterministic_value(6, "user:12", "field0")),
+                "user"
+            );
+            let db = MemoryDb::new();
+            w.load(db.clone()).unwrap();
+            for _ in 0..100 {
+                assert_eq!(w.transaction(db.clone()).unwrap(), Op::Scan);
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_modify_write_integrity() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(10)
+            .read_proportion(0.0)
+            .update_proportion(0.0)
+            .data_integrity(true);
+        let w = workload(config.clone().read_modify_write_proportion(1.0));
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            assert_eq!(w.transaction(db.clone()).unwrap(), Op::ReadModifyWrite);
+        }
+
+        // The written values verify on the following reads.
+        let w = workload(config.read_proportion(1.0));
+        for _ in 0..100 {
+            assert_eq!(w.transaction(db.clone()).unwrap(), Op::Read);
+        }
+    }
+
+    /// A workload that only counts the calls of its lifecycle hooks.
+    #[derive(Debug, Default)]
+    struct CountingWorkload {
+        inits: AtomicUsize,
+        cleanups: AtomicUsize,
+    }
+
+    impl Workload for CountingWorkload {
+        type Config = ();
+
+        fn new(_: Self::Config) -> Result<Self> {
+            Ok(Self::default())
+        }
+
+        fn init(&self) -> Result<()> {
+            self.inits.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+
+        fn cleanup(&self) -> Result<()> {
+            self.cleanups.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        // Drive the hooks through the trait like a runner.
+        fn run<W: Workload>(w: &W) -> Result<()> {
+            w.init()?;
+            w.cleanup()
+        }
+
+        let w = CountingWorkload::new(()).unwrap();
+        run(&w).unwrap();
+        assert_eq!(w.inits.load(Ordering::Relaxed), 1);
+        assert_eq!(w.cleanups.load(Ordering::Relaxed), 1);
+
+        // The provided hooks do nothing.
+        let w = workload(CoreWorkloadConfig::builder());
+        run(&w).unwrap();
+    }
+
+    #[test]
+    fn test_transaction_op() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(10)
+            .read_proportion(0.0)
+            .update_proportion(0.0);
+        let db = MemoryDb::new();
+        workload(config.clone().insert_proportion(1.0))
+            .load(db.clone())
+            .unwrap();
+
+        for (config, op) in [
+            (config.clone().read_proportion(1.0), Op::Read),
+            (config.clone().update_proportion(1.0), Op::Update),
+            (config.clone().scan_proportion(1.0), Op::Scan),
+        ] {
+            let w = workload(config);
+            for _ in 0..10 {
+                assert_eq!(w.transaction(db.clone()).unwrap(), op);
+            }
+        }
+    }
+
+    #[test]
+    fn test_binary_values() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .max_field_length(256)
+                .binary_values(true),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        let record = db.get("ycsb", &w.build_key_name(0)).unwrap();
+        assert!(record.values().flatten().any(|b| !b.is_ascii()));
+    }
+
+    #[test]
+    fn test_table_count() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(100)
+                .table_count(4)
+                .read_proportion(0.5)
+                .update_proportion(0.5),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        assert_eq!(db.tables(), vec!["ycsb0", "ycsb1", "ycsb2", "ycsb3"]);
+        let lens = db.tables().iter().map(|table| db.len(table)).collect_vec();
+        assert_eq!(lens.iter().sum::<usize>(), 100);
+        assert!(lens.iter().all(|&len| len > 0), "{lens:?}");
+
+        // The transactions find the records in their tables.
+        for _ in 0..100 {
+            w.transaction(db.clone()).unwrap();
+        }
+        assert_eq!(db.tables().len(), 4);
+    }
+
+    #[test]
+    fn test_field_length_overrides() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .field_length_distribution("constant")
+                .max_field_length(100)
+                .field_length_overrides(HashMap::from([
+                    ("field0".to_string(), ("constant".to_string(), 1, 8)),
+                    ("field9".to_string(), ("constant".to_string(), 1, 4096)),
+                ])),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        for key_num in 0..10 {
+            let record = db.get("ycsb", &w.build_key_name(key_num)).unwrap();
+            assert_eq!(record["field0"].len(), 8);
+            assert_eq!(record["field9"].len(), 4096);
+            assert_eq!(record["field5"].len(), 100);
+        }
+    }
+
+    #[test]
+    fn test_read_inserted_keys() {
+        for distribution in ["uniform", "zipfian"] {
+            let w = workload(
+                CoreWorkloadConfig::builder()
+                    .record_count(10)
+                    .read_proportion(0.5)
+                    .update_proportion(0.0)
+                    .insert_proportion(0.5)
+                    .request_distribution(distribution),
+            );
+            let db = MemoryDb::new();
+            w.load(db.clone()).unwrap();
+            // The reads fail if they choose a key that is not inserted.
+            for _ in 0..400 {
+                w.transaction(db.clone()).unwrap();
+            }
+            let records = db.len("ycsb");
+            assert!(records > 100, "{records}");
+
+            // The chooser of the reads covers the keys inserted by the transactions.
+            let key_nums = (0..10_000).map(|_| w.next_key_num()).collect_vec();
+            assert!(key_nums.iter().all(|&n| n < records));
+            assert!(key_nums.iter().any(|&n| n >= 10), "{distribution}");
+        }
+    }
+
+    #[test]
+    fn test_unordered_scan() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .read_proportion(0.0)
+                .update_proportion(0.0)
+                .scan_proportion(1.0)
+                .min_scan_length(2)
+                .max_scan_length(10)
+                .data_integrity(true),
+        );
+        let db = ShufflingDb::default();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            w.transaction(db.clone()).unwrap();
+        }
+
+        // The same records fail the order check if the backend claims they are ordered.
+        let db = ShufflingDb {
+            ordered: true,
+            ..db
+        };
+        let e = (0..100)
+            .find_map(|_| w.transaction(db.clone()).err())
+            .unwrap();
+        assert!(e.to_string().contains("out of order"), "{e}");
+    }
+
+    #[test]
+    fn test_builder() {
+        // The unset fields take their default values.
+        let config = CoreWorkloadConfig::builder().build().unwrap();
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            serde_json::to_value(CoreWorkloadConfig::default()).unwrap()
+        );
+
+        let config = CoreWorkloadConfig::builder()
+            .record_count(1000)
+            .read_proportion(0.8)
+            .update_proportion(0.2)
+            .table("usertable")
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["record_count"], 1000);
+        assert_eq!(json["read_proportion"], 0.8);
+        assert_eq!(json["fields"], default::fields());
+
+        let w = CoreWorkload::new(config).unwrap();
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        assert_eq!(db.len("usertable"), 1000);
+        for _ in 0..1000 {
+            let op = w.transaction(db.clone()).unwrap();
+            assert!(matches!(op, Op::Read | Op::Update), "{op:?}");
+        }
+
+        // The config is validated like the workload.
+        assert!(CoreWorkloadConfig::builder()
+            .read_proportion(0.0)
+            .update_proportion(0.0)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_integrity_with_uniform_field_lengths() {
+        for distribution in ["uniform", "zipfian"] {
+            let w = workload(
+                CoreWorkloadConfig::builder()
+                    .record_count(20)
+                    .field_length_distribution(distribution)
+                    .min_field_length(1)
+                    .max_field_length(200)
+                    .read_proportion(0.4)
+                    .update_proportion(0.4)
+                    .scan_proportion(0.1)
+                    .read_modify_write_proportion(0.1)
+                    .data_integrity(true),
+            );
+            let db = MemoryDb::new();
+            w.load(db.clone()).unwrap();
+            let lens = (0..20)
+                .flat_map(|key_num| db.get("ycsb", &w.build_key_name(key_num)).unwrap())
+                .map(|(_, value)| value.len())
+                .collect::<HashSet<_>>();
+            assert!(lens.len() > 1, "{distribution}: {lens:?}");
+
+            for _ in 0..200 {
+                w.transaction(db.clone()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let interval = Duration::from_millis(10);
+        let max_interval = Duration::from_millis(200);
+        let waits = (0..8)
+            .map(|retry| backoff(interval, max_interval, retry, &mut rng))
+            .collect_vec();
+        for (retry, wait) in waits.iter().enumerate() {
+            let expected = (interval * 2u32.pow(retry as u32)).min(max_interval);
+            assert!(
+                *wait >= expected.mul_f64(0.8) && *wait <= expected.mul_f64(1.2),
+                "{retry}: {wait:?}"
+            );
+        }
+        // The waits double until the cap, which the jitter can't hide.
+        assert!(waits[..5].windows(2).all(|w| w[0] < w[1]), "{waits:?}");
+        assert!(waits[5..]
+            .iter()
+            .all(|wait| *wait <= max_interval.mul_f64(1.2)));
+    }
+
+    #[test]
+    fn test_field_length_stats() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(100)
+            .field_length_distribution("constant")
+            .max_field_length(64);
+        assert!(workload(config.clone()).field_length_stats().is_none());
+
+        let w = workload(config.field_length_stats(true));
+        w.load(MemoryDb::new()).unwrap();
+        let stats = w.field_length_stats().unwrap();
+        assert_eq!(stats.count(), 100 * default::fields() as u64);
+        assert_eq!((stats.min(), stats.max()), (64, 64));
+        assert_eq!(stats.histogram().iter_recorded().count(), 1);
+    }
+
+    #[test]
+    fn test_shard_insert_range() {
+        // Even.
+        assert_eq!(
+            (0..4).map(|i| shard_insert_range(1000, i, 4)).collect_vec(),
+            vec![(0, 250), (250, 250), (500, 250), (750, 250)]
+        );
+        // Uneven, the low-index shards take the remainder.
+        assert_eq!(
+            (0..3).map(|i| shard_insert_range(1000, i, 3)).collect_vec(),
+            vec![(0, 334), (334, 333), (667, 333)]
+        );
+        assert_eq!(shard_insert_range(1000, 0, 1), (0, 1000));
+
+        // The shards load all the records together.
+        let db = MemoryDb::new();
+        for i in 0..3 {
+            let w = workload(CoreWorkloadConfig::builder().record_count(100).shard(i, 3));
+            w.load(db.clone()).unwrap();
+        }
+        assert_eq!(db.len("ycsb"), 100);
+    }
+
+    #[test]
+    fn test_transaction_spans() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(10)
+                .read_proportion(0.5)
+                .update_proportion(0.5),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+
+        let subscriber = Arc::new(CapturingSubscriber::default());
+        let ops = tracing::subscriber::with_default(subscriber.clone(), || {
+            (0..100)
+                .map(|_| w.transaction(db.clone()).unwrap())
+                .collect_vec()
+        });
+
+        // A span for each transaction, which carries the key.
+        let spans = subscriber.spans.lock().clone();
+        let expected = ops
+            .iter()
+            .map(|op| match op {
+                Op::Read => "txn_read",
+                Op::Update => "txn_update",
+                op => unreachable!("{op:?}"),
+            })
+            .collect_vec();
+        assert_eq!(spans, expected);
+        let keys = subscriber.keys.lock().clone();
+        assert_eq!(keys.len(), 100);
+        assert!(
+            keys.iter().all(|key| key.contains(&w.key_prefix)),
+            "{keys:?}"
+        );
+    }
+
+    #[test]
+    fn test_hashed_key_length() {
+        for (hasher, len) in [
+            (KeyHasher::Fnv64, "user".len() + 20),
+            (KeyHasher::Fnv32, "user".len() + 10),
+        ] {
+            let w = workload(
+                CoreWorkloadConfig::builder()
+                    .record_count(1000)
+                    .insert_order("hashed")
+                    .key_hasher(hasher),
+            );
+            assert!(
+                (0..1000).all(|key_num| w.build_key_name(key_num).len() == len),
+                "{hasher:?}"
+            );
+        }
+
+        // A larger padding is kept.
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .insert_order("hashed")
+                .key_hasher(KeyHasher::Fnv32)
+                .zero_padding(16),
+        );
+        assert_eq!(w.build_key_name(0).len(), "user".len() + 16);
+
+        // The ordered keys are only padded to the configured width.
+        let w = workload(CoreWorkloadConfig::builder().insert_order("ordered"));
+        assert_eq!(w.build_key_name(5), "user5");
+    }
+
+    #[test]
+    fn test_retry_by_operation() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(10)
+            .read_proportion(0.0)
+            .update_proportion(0.0);
+        let cases = [
+            (
+                config
+                    .clone()
+                    .read_proportion(1.0)
+                    .read_retry_limit(2)
+                    .read_retry_interval(0),
+                Operation::Read,
+            ),
+            (
+                config
+                    .clone()
+                    .update_proportion(1.0)
+                    .update_retry_limit(2)
+                    .update_retry_interval(0),
+                Operation::Update,
+            ),
+            (
+                config
+                    .clone()
+                    .scan_proportion(1.0)
+                    .scan_retry_limit(2)
+                    .scan_retry_interval(0),
+                Operation::Scan,
+            ),
+            (
+                config
+                    .clone()
+                    .read_modify_write_proportion(1.0)
+                    .read_modify_write_retry_limit(2)
+                    .read_modify_write_retry_interval(0),
+                Operation::Read,
+            ),
+            (
+                config
+                    .clone()
+                    .delete_proportion(1.0)
+                    .delete_retry_limit(2)
+                    .delete_retry_interval(0),
+                Operation::Delete,
+            ),
+        ];
+        for (config, operation) in cases {
+            let w = workload(config);
+            let db = MemoryDb::new();
+            w.load(db.clone()).unwrap();
+
+            // The first attempt and the 2 retries.
+            let faulty = FaultyDb::new(db.clone()).with_failures(operation, 2);
+            w.transaction(faulty.clone()).unwrap();
+            assert_eq!(faulty.remaining_failures(), 0, "{operation:?}");
+
+            let faulty = FaultyDb::new(db).with_failures(operation, 4);
+            let e = w.transaction(faulty.clone()).unwrap_err();
+            assert!(e.to_string().contains("exceeded retry limit 2"), "{e}");
+            assert_eq!(faulty.remaining_failures(), 1, "{operation:?}");
+        }
+
+        // The operations other than the inserts are not retried by default.
+        let w = workload(config.read_proportion(1.0));
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        let faulty = FaultyDb::new(db).with_failures(Operation::Read, 2);
+        assert!(w.transaction(faulty.clone()).is_err());
+        assert_eq!(faulty.remaining_failures(), 1);
+    }
+
+    #[test]
+    fn test_key_prefix() {
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .insert_order("ordered")
+                .zero_padding(8),
+        );
+        assert_eq!(w.build_key_name(5), "user00000005");
+
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .insert_order("ordered")
+                .key_prefix("k")
+                .zero_padding(3),
+        );
+        assert_eq!(w.build_key_name(5), "k005");
+        assert_eq!(w.build_key_name(12345), "k12345");
+
+        // Only the numeric part is hashed.
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .insert_order("hashed")
+                .key_hasher(KeyHasher::Fnv32),
+        );
+        assert_eq!(
+            w.build_key_name(0),
+            format!("user{:010}", crate::utils::fnvhash32(0))
+        );
+    }
+
+    #[test]
+    fn test_think_time() {
+        const OPS: u32 = 10;
+
+        let db = MemoryDb::new();
+        let config = CoreWorkloadConfig::builder().record_count(10);
+        workload(config.clone().insert_proportion(1.0))
+            .load(db.clone())
+            .unwrap();
+
+        let w = workload(
+            config
+                .think_time_distribution("constant")
+                .max_think_time(10),
+        );
+        let start = Instant::now();
+        for _ in 0..OPS {
+            w.transaction(db.clone()).unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(10) * OPS);
+    }
+
+    #[test]
+    fn test_intended_latency() {
+        let db = MemoryDb::new();
+        let config = CoreWorkloadConfig::builder()
+            .record_count(10)
+            .read_proportion(1.0)
+            .update_proportion(0.0);
+        workload(config.clone().insert_proportion(1.0))
+            .load(db.clone())
+            .unwrap();
+
+        // Each read takes 5ms but the schedule issues one every 1ms, so the reads fall behind the schedule.
+        let w = workload(config.target_throughput(1000));
+        let db = FaultyDb::new(db).with_latency(Duration::from_millis(5));
+        let limiter = w.rate_limiter(1);
+        for _ in 0..50 {
+            w.transaction_rate_limited(db.clone(), &limiter).unwrap();
+        }
+
+        let report = w.measurements().report();
+        let measured = &report.operations[&Op::Read];
+        let intended = &report.intended[&Op::Read];
+        assert_eq!(intended.count, 50);
+        assert!(intended.mean > measured.mean, "{intended:?} {measured:?}");
+        assert!(intended.max > measured.max, "{intended:?} {measured:?}");
+    }
+
+    #[test]
+    fn test_fields_to_write() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(1)
+            .read_proportion(0.0)
+            .update_proportion(1.0)
+            .fields_to_write_distribution("constant")
+            .max_fields_to_write(3);
+        let w = workload(config.clone());
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+
+        let key = w.build_key_name(0);
+        for _ in 0..10 {
+            let before = db.get("ycsb", &key).unwrap();
+            assert_eq!(w.transaction(db.clone()).unwrap(), Op::Update);
+            let after = db.get("ycsb", &key).unwrap();
+            assert_eq!(after.len(), 10);
+            let changed = after
+                .iter()
+                .filter(|(field, value)| before[*field] != **value)
+                .count();
+            assert_eq!(changed, 3);
+        }
+
+        // The partial updates pass the integrity checks of both the writes and the reads.
+        let w = workload(
+            config
+                .read_proportion(0.5)
+                .update_proportion(0.5)
+                .data_integrity(true)
+                .verify_writes(true),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            w.transaction(db.clone()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_uuid_keys() {
+        let config = CoreWorkloadConfig::builder()
+            .record_count(100)
+            .key_format("uuid")
+            .read_proportion(1.0)
+            .update_proportion(0.0)
+            .data_integrity(true);
+        let w = workload(config.clone());
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        assert_eq!(db.len("ycsb"), 100);
+
+        let keys = w.tracked_keys.read().keys.clone();
+        assert_eq!(keys.len(), 100);
+        for (_, key) in &keys {
+            assert!(key.starts_with("user") && key.len() == 40, "{key}");
+            assert!(db.get("ycsb", key).is_some());
+        }
+        // The reads choose the inserted keys and verify their values.
+        for _ in 0..100 {
+            assert_eq!(w.transaction(db.clone()).unwrap(), Op::Read);
+        }
+
+        // Only a sample of the keys is tracked, but all of them can still be read.
+        let w = workload(config.max_tracked_keys(10));
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        assert_eq!(db.len("ycsb"), 100);
+        assert_eq!(w.tracked_keys.read().keys.len(), 10);
+        for _ in 0..100 {
+            w.transaction(db.clone()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_constant_scan_length() {
+        // The min scan length is ignored, even if it is larger than the max.
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(100)
+                .read_proportion(0.0)
+                .update_proportion(0.0)
+                .scan_proportion(1.0)
+                .scan_length_distribution("constant")
+                .min_scan_length(50)
+                .max_scan_length(7),
+        );
+        let db = ScanRecordingDb::default();
+        w.load(db.clone()).unwrap();
+        for _ in 0..100 {
+            assert_eq!(w.transaction(db.clone()).unwrap(), Op::Scan);
+        }
+
+        let scans = db.scans.lock();
+        assert_eq!(scans.len(), 100);
+        assert!(scans.iter().all(|&(_, len)| len == 7));
+    }
+
+    #[test]
+    fn test_target_record_size() {
+        let w = workload(CoreWorkloadConfig::builder().target_record_size(4096));
+        let field_length = w.field_length_generator.mean();
+        let record_size = field_length * w.field_names.len() as f64;
+        assert!(
+            (record_size - 4096.0).abs() <= field_length,
+            "{record_size}"
+        );
+
+        let w = workload(
+            CoreWorkloadConfig::builder()
+                .record_count(1)
+                .fields(3)
+                .target_record_size(4096),
+        );
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        let record = db.get("ycsb", &w.build_key_name(0)).unwrap();
+        assert_eq!(record.len(), 3);
+        assert_eq!(record.values().map(Vec::len).sum::<usize>(), 4095);
+
+        // Fewer bytes than fields.
+        let w = workload(CoreWorkloadConfig::builder().target_record_size(5));
+        assert_eq!(w.field_names.len(), 5);
+        assert_eq!(w.field_length_generator.mean(), 1.0);
+    }
+}
@@ -0,0 +1,365 @@
+//  Copyright 2024 MrCroxx
+//
+//  Licensed under the Apache License, Version 2.0 (the "License");
+//  you may not use this file except in compliance with the License.
+//  You may obtain a copy of the License at
+//
+//  http://www.apache.org/licenses/LICENSE-2.0
+//
+//  Unless required by applicable law or agreed to in writing, software
+//  distributed under the License is distributed on an "AS IS" BASIS,
+//  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+//  See the License for the specific language governing permissions and
+//  limitations under the License.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use serde::{de::value::MapDeserializer, Deserialize, Serialize};
+
+use super::{Op, Workload};
+use crate::{
+    db::Db,
+    generator::{
+        acknowledge::AcknowledgedUsizeCounter,
+        counter::UsizeCounter,
+        discrete::{Choice, DiscreteGenerator},
+        uniform::UniformUsizeGenerator,
+        AcknowledgedCounter, Counter, Generator,
+    },
+    measurement::Measurements,
+    utils::{RandomBytes, Value},
+};
+
+/// The name of the field that holds the value of a data point. It is the first field of the core workload, so the
+/// backends with a fixed schema work with their default config.
+const VALUE_FIELD: &str = "field0";
+
+/// The size of the acknowledgement window of the points of each series.
+const WINDOW_SIZE: usize = 1 << 10;
+
+/// Configuration for the [`TimeSeriesWorkload`].
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TimeSeriesWorkloadConfig {
+    /// The name of the database table to run queries against.
+    #[serde(default = "default::table")]
+    table: String,
+    /// The number of series.
+    #[serde(default = "default::series")]
+    series: usize,
+    /// The number of points of each series to insert in the load phase.
+    #[serde(default = "default::points_per_series")]
+    points_per_series: usize,
+    /// The length of the value of each point, in bytes.
+    #[serde(default = "default::value_length")]
+    value_length: usize,
+    /// The timestamp of the first point of each series.
+    #[serde(default = "default::start_timestamp")]
+    start_timestamp: u64,
+    /// The interval between the timestamps of the consecutive points of a series.
+    #[serde(default = "default::timestamp_interval")]
+    timestamp_interval: u64,
+    /// The number of the most recent points of a series that reads and scans query.
+    #[serde(default = "default::window")]
+    window: usize,
+    /// What proportion of operations should be reads of a recent point.
+    #[serde(default = "default::read_proportion")]
+    read_proportion: f64,
+    /// What proportion of operations should be inserts of a new point.
+    #[serde(default = "default::insert_proportion")]
+    insert_proportion: f64,
+    /// What proportion of operations should be scans of the recent window.
+    #[serde(default = "default::scan_proportion")]
+    scan_proportion: f64,
+}
+
+impl Default for TimeSeriesWorkloadConfig {
+    /// The config with all fields set to their default values.
+    fn default() -> Self {
+        let fields =
+            MapDeserializer::<_, serde::de::value::Error>::new(std::iter::empty::<(&str, &str)>());
+        Self::deserialize(fields).expect("all fields have default values")
+    }
+}
+
+/// A metric ingestion scenario. Each record is a data point of a series, keyed by the series and its timestamp.
+///
+/// Inserts append a new point to a random series with the next timestamp of the series, while reads and scans query
+/// the most recent window of points of a random series. The keys are `{series}:{timestamp}` with the timestamp
+/// zero-padded, so the points of a series are ordered by their timestamps.
+///
+/// Reads and scans only query the points whose inserts are complete. If the chosen series has no point yet, the
+/// operation inserts one instead.
+pub struct TimeSeriesWorkload {
+    table: String,
+    value_length: usize,
+    start_timestamp: u64,
+    timestamp_interval: u64,
+    window: usize,
+    points_per_series: usize,
+    operation_chooser: DiscreteGenerator<Op>,
+    series_chooser: UniformUsizeGenerator,
+    load_sequencer: UsizeCounter,
+    /// The sequence numbers of the points of each series, starting from 1.
+    points: Vec<AcknowledgedUsizeCounter>,
+    measurements: Arc<Measurements>,
+    operations: Arc<AtomicUsize>,
+}
+
+impl Workload for TimeSeriesWorkload {
+    type Config = TimeSeriesWorkloadConfig;
+
+    fn new(config: Self::Config) -> Result<Self> {
+        if config.series == 0 {
+            return Err(anyhow!("series must be positive"));
+        }
+        if config.window == 0 {
+            return Err(anyhow!("window must be positive"));
+        }
+
+        let operation_chooser = DiscreteGenerator::new(vec![
+            Choice {
+                val: Op::Read,
+                weight: config.read_proportion,
+            },
+            Choice {
+                val: Op::Insert,
+                weight: config.insert_proportion,
+            },
+            Choice {
+                val: Op::Scan,
+                weight: config.scan_proportion,
+            },
+        ])?;
+
+        // The counters start at 1, so the last acknowledged point is 0 if the series has no point.
+        let points = (0..config.series)
+            .map(|_| AcknowledgedUsizeCounter::with_window_size(1, WINDOW_SIZE))
+            .collect_vec();
+
+        Ok(Self {
+            table: config.table,
+            value_length: config.value_length,
+            start_timestamp: config.start_timestamp,
+            timestamp_interval: config.timestamp_interval,
+            window: config.window,
+            points_per_series: config.points_per_series,
+            operation_chooser,
+            series_chooser: UniformUsizeGenerator::new(0, config.series - 1),
+            load_sequencer: UsizeCounter::new(0),
+            points,
+            measurements: Arc::new(Measurements::new()),
+            operations: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+}
+
+impl TimeSeriesWorkload {
+    /// Insert the next point of the series in a round-robin manner. It is safe to call concurrently.
+    pub fn insert(&self, db: impl Db) -> Result<()> {
+        let series = self.load_sequencer.next() % self.points.len();
+        let res = self.insert_point(db, series);
+        self.operations.fetch_add(1, Ordering::Relaxed);
+        res
+    }
+
+    /// Load `points_per_series` points of each series one by one.
+    pub fn load(&self, db: impl Db + Clone) -> Result<()> {
+        for _ in 0..self.points_per_series * self.points.len() {
+            self.insert(db.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Do one transaction operation on a random series. It is safe to call concurrently.
+    ///
+    /// Returns the operation performed.
+    pub fn transaction(&self, db: impl Db) -> Result<Op> {
+        let series = self.series_chooser.next();
+        let mut op = self.operation_chooser.next();
+        // The series has no point to query yet.
+        if op != Op::Insert && self.points[series].last() == 0 {
+            op = Op::Insert;
+        }
+        let res = match op {
+            Op::Read => self.txn_read(db, series),
+            Op::Scan => self.txn_scan(db, series),
+            _ => self.insert_point(db, series),
+        };
+        self.operations.fetch_add(1, Ordering::Relaxed);
+        res.map(|()| op)
+    }
+
+    /// Get the latency measurements of the database operations issued by the workload.
+    pub fn measurements(&self) -> &Arc<Measurements> {
+        &self.measurements
+    }
+
+    /// Get the count of the completed operations, including the failed ones.
+    pub fn operations(&self) -> &Arc<AtomicUsize> {
+        &self.operations
+    }
+
+    fn insert_point(&self, db: impl Db, series: usize) -> Result<()> {
+        let seq = self.points[series].next();
+        let key = self.build_key_name(series, seq);
+        let mut values = HashMap::with_capacity(1);
+        values.insert(
+            VALUE_FIELD.to_string(),
+            Value::from(RandomBytes::new(self.value_length)),
+        );
+
+        let res = self.measure(Op::Insert, || db.insert(self.table.clone(), key, values));
+
+        self.points[series].acknowledge(seq);
+        res
+    }
+
+    fn txn_read(&self, db: impl Db, series: usize) -> Result<()> {
+        let last = self.points[series].last();
+        let first = self.window_start(last);
+        let seq = UniformUsizeGenerator::new(first, last).next();
+        let key = self.build_key_name(series, seq);
+
+        self.measure(Op::Read, || {
+            db.read(self.table.clone(), key, HashSet::new())
+        })?;
+        Ok(())
+    }
+
+    fn txn_scan(&self, db: impl Db, series: usize) -> Result<()> {
+        let last = self.points[series].last();
+        let first = self.window_start(last);
+        let key = self.build_key_name(series, first);
+
+        self.measure(Op::Scan, || {
+            db.scan(self.table.clone(), key, last - first + 1, HashSet::new())
+        })?;
+        Ok(())
+    }
+
+    /// The sequence number of the first point of the recent window that ends at `last`.
+    fn window_start(&self, last: usize) -> usize {
+        last.saturating_sub(self.window - 1).max(1)
+    }
+
+    fn build_key_name(&self, series: usize, seq: usize) -> String {
+        let timestamp = self
+            .start_timestamp
+            .saturating_add((seq as u64 - 1).saturating_mul(self.timestamp_interval));
+        format!("{series}:{timestamp:020}")
+    }
+
+    fn measure<T, E>(&self, op: Op, f: impl FnOnce() -> std::result::Result<T, E>) -> Result<T>
+    where
+        E: Into<anyhow::Error>,
+    {
+        let start = Instant::now();
+        let res = f().map_err(Into::into);
+        self.measurements.measure(op, start.elapsed());
+        res
+    }
+}
+
+/// Default values for configurations.
+#[allow(missing_docs)]
+pub mod default {
+    pub fn table() -> String {
+        "timeseries".to_string()
+    }
+
+    pub fn series() -> usize {
+        100
+    }
+
+    pub fn points_per_series() -> usize {
+        100
+    }
+
+    pub fn value_length() -> usize {
+        8
+    }
+
+    pub fn start_timestamp() -> u64 {
+        0
+    }
+
+    pub fn timestamp_interval() -> u64 {
+        1
+    }
+
+    pub fn window() -> usize {
+        10
+    }
+
+    pub fn read_proportion() -> f64 {
+        0.1
+    }
+
+    pub fn insert_proportion() -> f64 {
+        0.8
+    }
+
+    pub fn scan_proportion() -> f64 {
+        0.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memory::MemoryDb;
+
+    #[test]
+    fn test_load_and_query() {
+        let config: TimeSeriesWorkloadConfig = serde_json::from_value(serde_json::json!({
+            "series": 4,
+            "points_per_series": 5,
+            "timestamp_interval": 10,
+            "window": 3,
+            "read_proportion": 0.5,
+            "insert_proportion": 0.0,
+            "scan_proportion": 0.5,
+        }))
+        .unwrap();
+        let w = TimeSeriesWorkload::new(config).unwrap();
+        let db = MemoryDb::new();
+        w.load(db.clone()).unwrap();
+        assert_eq!(db.len("timeseries"), 20);
+        assert!(db
+            .get("timeseries", &format!("3:{:020}", 40))
+            .is_some_and(|record| record[VALUE_FIELD].len() == 8));
+
+        for _ in 0..100 {
+            let op = w.transaction(db.clone()).unwrap();
+            assert!(matches!(op, Op::Read | Op::Scan), "{op:?}");
+        }
+        let report = w.measurements().report();
+        assert_eq!(report.operations[&Op::Insert].count, 20);
+        assert_eq!(
+            report.operations[&Op::Read].count + report.operations[&Op::Scan].count,
+            100
+        );
+        assert_eq!(w.operations().load(Ordering::Relaxed), 120);
+
+        // The scans query the recent window of the series.
+        let rows = db
+            .scan_rows(
+                "timeseries".to_string(),
+                w.build_key_name(1, w.window_start(5)),
+                3,
+                HashSet::new(),
+            )
+            .unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(w.build_key_name(1, 3), format!("1:{:020}", 20));
+    }
+}